@@ -0,0 +1,27 @@
+use ai_subsystems::{batch_api, text_api};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let api_key = std::fs::read_to_string("secrets/open-ai.key").unwrap();
+    let client_configuration = batch_api::client::ClientConfigurationBuilder::default()
+        .with_api_key(api_key)
+        .with_api_url(batch_api::client::URL::openai_v1_batches())
+        .with_poll_interval(std::time::Duration::from_secs(60))
+        .build()
+        .unwrap();
+    let globals = text_api::xml_dsl::object!({});
+    let mut batch = batch_api::request::RequestBuilder::default();
+    for name in ["question-1", "question-2"] {
+        let prompt = text_api::xml_dsl::Prompt::open_with("assets/basic.prompt.liquid", name, &globals)?;
+        batch = batch.with_request(name, prompt.request.build().unwrap());
+    }
+    let results = batch.build().unwrap().execute(&client_configuration).await?;
+    for (custom_id, response) in results.responses.iter() {
+        let content = response.choices[0].message.content.clone().unwrap_or_default();
+        println!("{custom_id}: {content}");
+    }
+    for (custom_id, error) in results.errors.iter() {
+        println!("{custom_id}: ERROR {}", error.message);
+    }
+    Ok(())
+}
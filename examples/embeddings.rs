@@ -0,0 +1,25 @@
+use ai_subsystems::embeddings_api;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let api_key = std::fs::read_to_string("secrets/open-ai.key").unwrap();
+    let api_url = embeddings_api::client::URL::openai_v1_embeddings();
+    let client_configuration = embeddings_api::client::ClientConfigurationBuilder::default()
+        .with_api_key(api_key)
+        .with_api_url(api_url)
+        .build()
+        .unwrap();
+    let request = embeddings_api::request::RequestBuilder::default()
+        .with_model(embeddings_api::request::Model::text_embedding_3_small())
+        .with_input(vec!["The quick brown fox.", "The lazy dog.", "Jumped over."])
+        .with_encoding_format(embeddings_api::request::EncodingFormat::base64())
+        .with_dimensions(256)
+        .build()
+        .unwrap();
+    let response = request.execute_batched(&client_configuration, 2).await.unwrap();
+    for vector in response.vectors()? {
+        println!("{:?}", &vector[..8]);
+    }
+    println!("USAGE: {:#?}", response.usage);
+    Ok(())
+}
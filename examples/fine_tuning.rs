@@ -0,0 +1,37 @@
+use ai_subsystems::{files_api, fine_tuning_api, text_api::request::Message};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let api_key = std::fs::read_to_string("secrets/open-ai.key").unwrap();
+    let files_configuration = files_api::client::ClientConfigurationBuilder::default()
+        .with_api_key(api_key.as_str())
+        .with_api_url(files_api::client::URL::openai_v1_files())
+        .build()
+        .unwrap();
+    let client_configuration = fine_tuning_api::client::ClientConfigurationBuilder::default()
+        .with_api_key(api_key.as_str())
+        .with_api_url(fine_tuning_api::client::URL::openai_v1_fine_tuning_jobs())
+        .build()
+        .unwrap();
+    let conversations = (1..=10).map(|x| vec![
+        Message::system("You are a calculator."),
+        Message::user(format!("What is {x} + {x}?")),
+        Message::assistant(format!("{}", x + x)),
+    ]);
+    let training_data = fine_tuning_api::training_data::TrainingDataBuilder::default()
+        .with_conversations(conversations)
+        .build()?;
+    let training_file = training_data.upload("calculator.jsonl", &files_configuration).await?;
+    let job = fine_tuning_api::request::JobRequestBuilder::default()
+        .with_model("gpt-4o-mini-2024-07-18")
+        .with_training_file(training_file.id)
+        .with_hyperparameters(fine_tuning_api::request::Hyperparameters::default().with_n_epochs(3))
+        .with_suffix("calculator")
+        .build()
+        .unwrap()
+        .execute(&client_configuration)
+        .await?;
+    let job = job.watch(&client_configuration, |event| println!("[{}] {}", event.level, event.message)).await?;
+    println!("FINE-TUNED MODEL: {:?}", job.fine_tuned_model);
+    Ok(())
+}
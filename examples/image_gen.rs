@@ -13,7 +13,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .with_prompt("the european knight.")
         .with_model(images_api::request::Model::dall_e3())
         .with_response_format(images_api::request::ResponseFormat::url())
-        .build()
+        .build(&client_configuration.backend)
         .unwrap();
     let response = request.execute(&client_configuration).await.unwrap();
     println!("RESULT: {:#?}", response);
@@ -1,10 +1,12 @@
 pub use crate::text_api::client::ApiError;
+use crate::cost::CostAccountant;
 
 #[derive(Default)]
 pub struct ClientConfigurationBuilder {
     pub api_url: Option<URL>,
     pub api_key: Option<ApiKey>,
     pub timeout: Option<Timeout>,
+    pub cost_accountant: Option<CostAccountant>,
 }
 
 impl ClientConfigurationBuilder {
@@ -20,11 +22,17 @@ impl ClientConfigurationBuilder {
         self.timeout = Some(timeout.into());
         self
     }
+    /// Records the cost of each request by character, and refuses to send once the budget is spent.
+    pub fn with_cost_accountant(mut self, cost_accountant: CostAccountant) -> Self {
+        self.cost_accountant = Some(cost_accountant);
+        self
+    }
     pub fn build(self) -> Option<ClientConfiguration> {
         Some(ClientConfiguration {
             api_url: self.api_url?,
             api_key: self.api_key?,
-            timeout: self.timeout
+            timeout: self.timeout,
+            cost_accountant: self.cost_accountant,
         })
     }
 }
@@ -33,6 +41,7 @@ pub struct ClientConfiguration {
     pub api_url: URL,
     pub api_key: ApiKey,
     pub timeout: Option<Timeout>,
+    pub cost_accountant: Option<CostAccountant>,
 }
 
 pub struct URL(pub String);
@@ -81,6 +90,9 @@ impl super::request::Request {
                 reqwest::ClientBuilder::new().build().unwrap()
             }
         };
+        if let Some(cost_accountant) = client_configuration.cost_accountant.as_ref() {
+            cost_accountant.check_budget()?;
+        }
         let json_data = serde_json::to_string(&self).unwrap();
         let http_response = client
             .post(api_url)
@@ -94,6 +106,9 @@ impl super::request::Request {
             return Err(Box::new(error))
         }
         let response_body = http_response.bytes().await?.to_vec();
+        if let Some(cost_accountant) = client_configuration.cost_accountant.as_ref() {
+            cost_accountant.record_units(&self.model.0, self.input.chars().count() as f64);
+        }
         Ok(response_body)
     }
 }
\ No newline at end of file
@@ -0,0 +1,246 @@
+use colored::Colorize;
+
+use crate::files_api;
+use super::response::{Batch, BatchResults};
+
+pub use crate::text_api::client::{ApiError, RetryPolicy};
+
+#[derive(Default)]
+pub struct ClientConfigurationBuilder {
+    pub api_url: Option<URL>,
+    pub files_api_url: Option<files_api::client::URL>,
+    pub api_key: Option<ApiKey>,
+    pub timeout: Option<Timeout>,
+    pub retry_policy: Option<RetryPolicy>,
+    pub poll_interval: Option<std::time::Duration>,
+}
+
+impl ClientConfigurationBuilder {
+    pub fn with_api_url(mut self, api_url: impl Into<URL>) -> Self {
+        self.api_url = Some(api_url.into());
+        self
+    }
+    /// Where the input file is uploaded and the output files are downloaded from. Defaults to OpenAI.
+    pub fn with_files_api_url(mut self, files_api_url: impl Into<files_api::client::URL>) -> Self {
+        self.files_api_url = Some(files_api_url.into());
+        self
+    }
+    pub fn with_api_key(mut self, api_key: impl Into<ApiKey>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+    pub fn with_timeout(mut self, timeout: impl Into<Timeout>) -> Self {
+        self.timeout = Some(timeout.into());
+        self
+    }
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+    /// How often [`Batch::wait`] checks the batch status. Defaults to 30 seconds.
+    pub fn with_poll_interval(mut self, poll_interval: std::time::Duration) -> Self {
+        self.poll_interval = Some(poll_interval);
+        self
+    }
+    pub fn build(self) -> Option<ClientConfiguration> {
+        Some(ClientConfiguration {
+            api_url: self.api_url?,
+            files_api_url: self.files_api_url.unwrap_or_else(files_api::client::URL::openai_v1_files),
+            api_key: self.api_key?,
+            timeout: self.timeout,
+            retry_policy: self.retry_policy.unwrap_or_default(),
+            poll_interval: self.poll_interval.unwrap_or(std::time::Duration::from_secs(30)),
+        })
+    }
+}
+
+pub struct ClientConfiguration {
+    pub api_url: URL,
+    pub files_api_url: files_api::client::URL,
+    pub api_key: ApiKey,
+    pub timeout: Option<Timeout>,
+    pub retry_policy: RetryPolicy,
+    pub poll_interval: std::time::Duration,
+}
+
+impl ClientConfiguration {
+    fn http_client(&self) -> reqwest::Client {
+        if let Some(timeout) = self.timeout.as_ref() {
+            reqwest::ClientBuilder::new()
+                .timeout(timeout.0)
+                .build()
+                .unwrap()
+        } else {
+            reqwest::ClientBuilder::new().build().unwrap()
+        }
+    }
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}{path}", self.api_url.0.trim_end_matches('/'))
+    }
+    fn files(&self) -> files_api::client::ClientConfiguration {
+        files_api::client::ClientConfiguration {
+            api_url: files_api::client::URL(self.files_api_url.0.clone()),
+            api_key: files_api::client::ApiKey(self.api_key.0.clone()),
+            timeout: self.timeout.as_ref().map(|x| files_api::client::Timeout(x.0)),
+            retry_policy: self.retry_policy.clone(),
+        }
+    }
+    /// Calls the batches endpoint. Calls that create a batch aren’t retried
+    /// after a timeout or dropped connection, which could create it twice.
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<Batch, Box<dyn std::error::Error>> {
+        let client = self.http_client();
+        let api_url = self.endpoint(path);
+        let api_key = self.api_key.0.as_str();
+        let creates = method == reqwest::Method::POST && path.is_empty();
+        let call = || async {
+            let mut request = client
+                .request(method.clone(), api_url.as_str())
+                .header("Authorization", format!("Bearer {}", api_key));
+            if let Some(body) = body.as_ref() {
+                request = request.json(body);
+            }
+            let response = request.send().await?;
+            if let Some(error) = ApiError::from_code(response.status().as_u16()) {
+                return Err(Box::new(error) as Box<dyn std::error::Error>)
+            }
+            let result = response.text().await?;
+            let result = serde_json::from_str::<Batch>(&result)?;
+            Ok(result)
+        };
+        match creates {
+            true => self.retry_policy.run_once_accepted(call).await,
+            false => self.retry_policy.run(call).await,
+        }
+    }
+}
+
+pub struct URL(pub String);
+
+impl URL {
+    /// Creates and manages batches of API requests.
+    pub fn openai_v1_batches() -> Self {
+        Self(String::from("https://api.openai.com/v1/batches"))
+    }
+}
+
+pub struct ApiKey(pub String);
+
+pub struct Timeout(pub std::time::Duration);
+
+impl From<String> for URL {
+    fn from(value: String) -> Self { Self(value) }
+}
+impl From<String> for ApiKey {
+    fn from(value: String) -> Self { Self(value) }
+}
+impl From<&str> for URL {
+    fn from(value: &str) -> Self { Self(value.to_string()) }
+}
+impl From<&str> for ApiKey {
+    fn from(value: &str) -> Self { Self(value.to_string()) }
+}
+impl From<std::time::Duration> for Timeout {
+    fn from(value: std::time::Duration) -> Self { Self(value) }
+}
+
+impl super::request::Request {
+    /// Uploads the input file and creates the batch, without waiting for it.
+    pub async fn submit(
+        self,
+        client_configuration: &ClientConfiguration
+    ) -> Result<Batch, Box<dyn std::error::Error>> {
+        let input_file = files_api::request::UploadRequestBuilder::default()
+            .with_file_name("batch-input.jsonl")
+            .with_purpose(files_api::request::Purpose::batch())
+            .with_contents(self.to_jsonl())
+            .build()
+            .unwrap()
+            .execute(&client_configuration.files())
+            .await?;
+        let body = serde_json::json!({
+            "input_file_id": input_file.id,
+            "endpoint": Self::ENDPOINT,
+            "completion_window": self.completion_window,
+            "metadata": self.metadata,
+        });
+        client_configuration.send(reqwest::Method::POST, "", Some(body)).await
+    }
+    /// Submits the batch, waits for it to finish, and downloads the results.
+    pub async fn execute(
+        self,
+        client_configuration: &ClientConfiguration
+    ) -> Result<BatchResults, Box<dyn std::error::Error>> {
+        let batch = self.submit(client_configuration).await?;
+        let batch = batch.wait(client_configuration).await?;
+        if batch.status == super::response::BatchStatus::Failed {
+            return Err(Box::new(BatchFailed(batch)))
+        }
+        batch.results(client_configuration).await
+    }
+}
+
+impl Batch {
+    /// Fetches the current state of the batch.
+    pub async fn refresh(
+        &self,
+        client_configuration: &ClientConfiguration
+    ) -> Result<Batch, Box<dyn std::error::Error>> {
+        client_configuration.send(reqwest::Method::GET, &format!("/{}", self.id), None).await
+    }
+    /// Cancels an in-progress batch. Requests already completed remain in the output file.
+    pub async fn cancel(
+        &self,
+        client_configuration: &ClientConfiguration
+    ) -> Result<Batch, Box<dyn std::error::Error>> {
+        client_configuration.send(reqwest::Method::POST, &format!("/{}/cancel", self.id), None).await
+    }
+    /// Polls the batch every `poll_interval` until it reaches a terminal status.
+    pub async fn wait(
+        self,
+        client_configuration: &ClientConfiguration
+    ) -> Result<Batch, Box<dyn std::error::Error>> {
+        let mut batch = self;
+        while !batch.status.is_terminal() {
+            tokio::time::sleep(client_configuration.poll_interval).await;
+            batch = batch.refresh(client_configuration).await?;
+        }
+        Ok(batch)
+    }
+    /// Downloads the output and error files and maps each line back to its `custom_id`.
+    pub async fn results(
+        &self,
+        client_configuration: &ClientConfiguration
+    ) -> Result<BatchResults, Box<dyn std::error::Error>> {
+        let files = client_configuration.files();
+        let mut results = BatchResults::new(self.clone());
+        for file_id in [self.output_file_id.as_ref(), self.error_file_id.as_ref()].into_iter().flatten() {
+            let contents = files_api::client::retrieve_content(&files, file_id).await?;
+            results.extend_from_jsonl(&String::from_utf8_lossy(&contents))?;
+        }
+        Ok(results)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BatchFailed(pub Batch);
+
+impl std::fmt::Display for BatchFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reasons = self.0.errors
+            .iter()
+            .flat_map(|x| x.data.iter())
+            .filter_map(|x| x.message.clone())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let msg = format!("Error: Batch Failed! {:?}: {reasons}", self.0.id);
+        let msg = msg.red();
+        write!(f, "{msg}")
+    }
+}
+
+impl std::error::Error for BatchFailed {}
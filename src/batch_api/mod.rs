@@ -0,0 +1,3 @@
+pub mod request;
+pub mod response;
+pub mod client;
\ No newline at end of file
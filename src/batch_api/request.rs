@@ -0,0 +1,97 @@
+//! Chat completion requests bundled into a Batch API job.
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::text_api;
+
+#[derive(Debug, Clone, Default)]
+pub struct RequestBuilder {
+    /// The chat completion requests, keyed by a `custom_id` that must be unique within the batch.
+    pub requests: Vec<(String, text_api::request::Request)>,
+    /// The time frame within which the batch should be processed. Currently only `24h` is supported.
+    pub completion_window: Option<String>,
+    /// Set of 16 key-value pairs that can be attached to the batch.
+    pub metadata: HashMap<String, String>,
+}
+
+impl RequestBuilder {
+    /// Adds a chat completion request, to be matched back to its response by `custom_id`.
+    pub fn with_request(mut self, custom_id: impl Into<String>, request: text_api::request::Request) -> Self {
+        self.requests.push((custom_id.into(), request));
+        self
+    }
+    pub fn with_requests(
+        mut self,
+        requests: impl IntoIterator<Item = (String, text_api::request::Request)>
+    ) -> Self {
+        self.requests.extend(requests);
+        self
+    }
+    /// The time frame within which the batch should be processed. Currently only `24h` is supported.
+    pub fn with_completion_window(mut self, completion_window: impl Into<String>) -> Self {
+        self.completion_window = Some(completion_window.into());
+        self
+    }
+    /// Set of 16 key-value pairs that can be attached to the batch.
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+    /// Returns `None` if there are no requests, a `custom_id` is repeated, or
+    /// a request has `stream` enabled (batches can’t stream).
+    pub fn build(self) -> Option<Request> {
+        if self.requests.is_empty() {
+            return None
+        }
+        let mut custom_ids = HashSet::new();
+        for (custom_id, request) in self.requests.iter() {
+            if !custom_ids.insert(custom_id.as_str()) || request.stream.unwrap_or(false) {
+                return None
+            }
+        }
+        let lines = self.requests
+            .into_iter()
+            .map(|(custom_id, body)| RequestLine {
+                custom_id,
+                method: String::from("POST"),
+                url: String::from(Request::ENDPOINT),
+                body,
+            })
+            .collect::<Vec<_>>();
+        Some(Request {
+            lines,
+            completion_window: self.completion_window.unwrap_or_else(|| String::from("24h")),
+            metadata: self.metadata,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub lines: Vec<RequestLine>,
+    pub completion_window: String,
+    pub metadata: HashMap<String, String>,
+}
+
+impl Request {
+    /// The endpoint every request in the batch is sent to.
+    pub const ENDPOINT: &'static str = "/v1/chat/completions";
+    /// The batch input file: one JSON encoded [`RequestLine`] per line.
+    pub fn to_jsonl(&self) -> String {
+        self.lines
+            .iter()
+            .map(|x| serde_json::to_string(x).unwrap())
+            .map(|x| format!("{x}\n"))
+            .collect()
+    }
+}
+
+/// A line of the batch input file.
+#[derive(Debug, Clone, Serialize)]
+pub struct RequestLine {
+    pub custom_id: String,
+    pub method: String,
+    pub url: String,
+    pub body: text_api::request::Request,
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::text_api;
+
+/// The `Batch` object.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Batch {
+    pub id: String,
+    pub object: String,
+    /// The OpenAI API endpoint used by the batch.
+    pub endpoint: String,
+    pub errors: Option<BatchErrors>,
+    /// The ID of the input file for the batch.
+    pub input_file_id: String,
+    /// The time frame within which the batch should be processed.
+    pub completion_window: String,
+    pub status: BatchStatus,
+    /// The ID of the file containing the outputs of successfully executed requests.
+    pub output_file_id: Option<String>,
+    /// The ID of the file containing the outputs of requests with errors.
+    pub error_file_id: Option<String>,
+    pub created_at: isize,
+    pub in_progress_at: Option<isize>,
+    pub expires_at: Option<isize>,
+    pub finalizing_at: Option<isize>,
+    pub completed_at: Option<isize>,
+    pub failed_at: Option<isize>,
+    pub expired_at: Option<isize>,
+    pub cancelling_at: Option<isize>,
+    pub cancelled_at: Option<isize>,
+    pub request_counts: Option<RequestCounts>,
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchStatus {
+    Validating,
+    Failed,
+    InProgress,
+    Finalizing,
+    Completed,
+    Expired,
+    Cancelling,
+    Cancelled,
+}
+
+impl BatchStatus {
+    /// Whether the batch will no longer change.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            BatchStatus::Failed | BatchStatus::Completed | BatchStatus::Expired | BatchStatus::Cancelled
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchErrors {
+    pub data: Vec<BatchError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchError {
+    pub code: Option<String>,
+    pub message: Option<String>,
+    pub param: Option<String>,
+    pub line: Option<isize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestCounts {
+    pub total: isize,
+    pub completed: isize,
+    pub failed: isize,
+}
+
+/// A line of the batch output or error file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResponseLine {
+    pub id: String,
+    pub custom_id: String,
+    pub response: Option<ResponseLineBody>,
+    pub error: Option<RequestError>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResponseLineBody {
+    pub status_code: u16,
+    pub request_id: String,
+    pub body: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestError {
+    pub code: Option<String>,
+    pub message: String,
+}
+
+/// The outcome of every request in a batch, keyed by `custom_id`.
+#[derive(Debug, Clone)]
+pub struct BatchResults {
+    pub batch: Batch,
+    pub responses: HashMap<String, text_api::response::batch::Response>,
+    pub errors: HashMap<String, RequestError>,
+}
+
+impl BatchResults {
+    /// No results yet; add them with [`BatchResults::extend_from_jsonl`].
+    pub fn new(batch: Batch) -> Self {
+        Self { batch, responses: HashMap::new(), errors: HashMap::new() }
+    }
+    /// Adds the lines of an output or error file, e.g. one downloaded
+    /// elsewhere. Fails only when a line isn’t a batch output line at all.
+    pub fn extend_from_jsonl(&mut self, source: &str) -> Result<(), serde_json::Error> {
+        for line in source.lines().filter(|x| !x.trim().is_empty()) {
+            let line = serde_json::from_str::<ResponseLine>(line)?;
+            if let Some(error) = line.error {
+                self.errors.insert(line.custom_id, error);
+                continue
+            }
+            let Some(response) = line.response else { continue };
+            if response.status_code != 200 {
+                let error = RequestError {
+                    code: response.body["error"]["code"].as_str().map(str::to_string),
+                    message: response.body["error"]["message"]
+                        .as_str()
+                        .map(str::to_string)
+                        .unwrap_or_else(|| format!("HTTP {}", response.status_code)),
+                };
+                self.errors.insert(line.custom_id, error);
+                continue
+            }
+            // A body that doesn’t parse only fails its own request.
+            match serde_json::from_value::<text_api::response::batch::Response>(response.body) {
+                Ok(body) => {
+                    self.responses.insert(line.custom_id, body);
+                }
+                Err(error) => {
+                    let error = RequestError {
+                        code: Some(String::from("invalid_response")),
+                        message: format!("the response body doesn’t parse: {error}"),
+                    };
+                    self.errors.insert(line.custom_id, error);
+                }
+            }
+        }
+        Ok(())
+    }
+}
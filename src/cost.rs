@@ -0,0 +1,223 @@
+//! Turns token usage and billed units into dollars, using the prices in the
+//! model registry, and keeps running totals across calls.
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use colored::Colorize;
+
+use crate::models_api::registry::ModelRegistry;
+use crate::text_api::response::batch::Usage;
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// COST ACCOUNTANT
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+/// A handle to a shared ledger; clones record into the same totals.
+///
+/// Give each caller its own labels with [`CostAccountant::with_prompt`] and
+/// [`CostAccountant::with_tag`], then pass the handle to
+/// `ApiCallBuilder::with_cost_accountant` or an images/audio client
+/// configuration.
+#[derive(Debug, Clone, Default)]
+pub struct CostAccountant {
+    state: Arc<Mutex<State>>,
+    prompt: Option<String>,
+    tag: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    registry: ModelRegistry,
+    budget: Option<f64>,
+    ledger: Ledger,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Ledger {
+    charges: Vec<Charge>,
+    total: Totals,
+    by_model: HashMap<String, Totals>,
+    by_prompt: HashMap<String, Totals>,
+    by_tag: HashMap<String, Totals>,
+}
+
+/// A single recorded call.
+#[derive(Debug, Clone)]
+pub struct Charge {
+    pub model: String,
+    pub prompt: Option<String>,
+    pub tag: Option<String>,
+    pub prompt_tokens: usize,
+    pub completion_tokens: usize,
+    /// Images generated, characters spoken, etc., for models billed by unit.
+    pub units: f64,
+    /// USD, or `None` when the registry has no price for the model.
+    pub cost: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Totals {
+    pub calls: usize,
+    /// Calls whose model has no price in the registry, and so cost nothing here.
+    pub unpriced_calls: usize,
+    pub prompt_tokens: usize,
+    pub completion_tokens: usize,
+    pub units: f64,
+    /// USD.
+    pub cost: f64,
+}
+
+impl Totals {
+    fn add(&mut self, charge: &Charge) {
+        self.calls += 1;
+        self.unpriced_calls += usize::from(charge.cost.is_none());
+        self.prompt_tokens += charge.prompt_tokens;
+        self.completion_tokens += charge.completion_tokens;
+        self.units += charge.units;
+        self.cost += charge.cost.unwrap_or_default();
+    }
+}
+
+impl CostAccountant {
+    /// Prices calls with the embedded model registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with_registry(self, registry: ModelRegistry) -> Self {
+        self.state.lock().unwrap().registry = registry;
+        self
+    }
+    /// Once total spend reaches `usd`, further calls fail with [`BudgetExceeded`]
+    /// before anything is sent.
+    pub fn with_budget(self, usd: f64) -> Self {
+        self.state.lock().unwrap().budget = Some(usd);
+        self
+    }
+    /// A handle to the same ledger that labels its charges with the prompt name.
+    pub fn with_prompt(&self, prompt: impl Into<String>) -> Self {
+        Self { prompt: Some(prompt.into()), ..self.clone() }
+    }
+    /// A handle to the same ledger that labels its charges with a caller tag
+    /// (e.g. a user or feature name).
+    pub fn with_tag(&self, tag: impl Into<String>) -> Self {
+        Self { tag: Some(tag.into()), ..self.clone() }
+    }
+    /// Fails once the budget is spent.
+    pub fn check_budget(&self) -> Result<(), BudgetExceeded> {
+        let state = self.state.lock().unwrap();
+        match state.budget {
+            Some(budget) if state.ledger.total.cost >= budget => {
+                Err(BudgetExceeded { budget, spent: state.ledger.total.cost })
+            }
+            _ => Ok(()),
+        }
+    }
+    /// Whether the registry has a price for the model.
+    pub fn has_price(&self, model: impl AsRef<str>) -> bool {
+        let state = self.state.lock().unwrap();
+        state.registry
+            .get(model.as_ref())
+            .map(|x| x.unit_price.is_some() || x.input_price_per_token().is_some())
+            .unwrap_or(false)
+    }
+    /// Records a chat completion, returning its cost.
+    pub fn record_usage(&self, model: impl AsRef<str>, usage: &Usage) -> Option<f64> {
+        let prompt_tokens = usize::try_from(usage.prompt_tokens).unwrap_or_default();
+        let completion_tokens = usize::try_from(usage.completion_tokens).unwrap_or_default();
+        self.record(model.as_ref(), None, prompt_tokens, completion_tokens, 0.0)
+    }
+    /// Records a call billed by unit (images, characters of speech), returning its cost.
+    pub fn record_units(&self, model: impl AsRef<str>, units: f64) -> Option<f64> {
+        self.record(model.as_ref(), None, 0, 0, units)
+    }
+    /// Records units of a priced variant (e.g. `hd 1024x1792` for an image),
+    /// see [`crate::models_api::registry::ModelInfo::unit_prices`].
+    pub fn record_variant_units(&self, model: impl AsRef<str>, variant: &str, units: f64) -> Option<f64> {
+        self.record(model.as_ref(), Some(variant), 0, 0, units)
+    }
+    fn record(
+        &self,
+        model: &str,
+        variant: Option<&str>,
+        prompt_tokens: usize,
+        completion_tokens: usize,
+        units: f64,
+    ) -> Option<f64> {
+        let mut state = self.state.lock().unwrap();
+        let cost = state.registry.get(model).and_then(|info| {
+            if units > 0.0 {
+                return info.unit_price_of(variant).map(|x| x * units)
+            }
+            let input = info.input_price_per_token()? * prompt_tokens as f64;
+            let output = info.output_price_per_token()? * completion_tokens as f64;
+            Some(input + output)
+        });
+        let charge = Charge {
+            model: model.to_string(),
+            prompt: self.prompt.clone(),
+            tag: self.tag.clone(),
+            prompt_tokens,
+            completion_tokens,
+            units,
+            cost,
+        };
+        let ledger = &mut state.ledger;
+        ledger.total.add(&charge);
+        ledger.by_model.entry(charge.model.clone()).or_default().add(&charge);
+        if let Some(prompt) = charge.prompt.clone() {
+            ledger.by_prompt.entry(prompt).or_default().add(&charge);
+        }
+        if let Some(tag) = charge.tag.clone() {
+            ledger.by_tag.entry(tag).or_default().add(&charge);
+        }
+        ledger.charges.push(charge);
+        cost
+    }
+    pub fn total(&self) -> Totals {
+        self.state.lock().unwrap().ledger.total.clone()
+    }
+    /// USD left before the budget is reached, if there is one.
+    pub fn remaining(&self) -> Option<f64> {
+        let state = self.state.lock().unwrap();
+        state.budget.map(|x| (x - state.ledger.total.cost).max(0.0))
+    }
+    pub fn by_model(&self) -> HashMap<String, Totals> {
+        self.state.lock().unwrap().ledger.by_model.clone()
+    }
+    pub fn by_prompt(&self) -> HashMap<String, Totals> {
+        self.state.lock().unwrap().ledger.by_prompt.clone()
+    }
+    pub fn by_tag(&self) -> HashMap<String, Totals> {
+        self.state.lock().unwrap().ledger.by_tag.clone()
+    }
+    /// Every recorded call, oldest first.
+    pub fn charges(&self) -> Vec<Charge> {
+        self.state.lock().unwrap().ledger.charges.clone()
+    }
+    /// Clears the ledger, keeping the registry and budget.
+    pub fn reset(&self) {
+        self.state.lock().unwrap().ledger = Ledger::default();
+    }
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// ERRORS
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+#[derive(Debug, Clone)]
+pub struct BudgetExceeded {
+    pub budget: f64,
+    pub spent: f64,
+}
+
+impl std::fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let msg = format!(
+            "Error: Budget Exceeded! Spent ${:.4} of a ${:.4} budget.",
+            self.spent,
+            self.budget,
+        );
+        let msg = msg.red();
+        write!(f, "{msg}")
+    }
+}
+
+impl std::error::Error for BudgetExceeded {}
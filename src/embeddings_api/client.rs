@@ -0,0 +1,157 @@
+use futures::{StreamExt, TryStreamExt};
+
+pub use crate::text_api::client::{ApiError, RetryPolicy};
+
+#[derive(Default)]
+pub struct ClientConfigurationBuilder {
+    pub api_url: Option<URL>,
+    pub api_key: Option<ApiKey>,
+    pub timeout: Option<Timeout>,
+    pub retry_policy: Option<RetryPolicy>,
+    pub max_concurrency: Option<usize>,
+}
+
+impl ClientConfigurationBuilder {
+    pub fn with_api_url(mut self, api_url: impl Into<URL>) -> Self {
+        self.api_url = Some(api_url.into());
+        self
+    }
+    pub fn with_api_key(mut self, api_key: impl Into<ApiKey>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+    pub fn with_timeout(mut self, timeout: impl Into<Timeout>) -> Self {
+        self.timeout = Some(timeout.into());
+        self
+    }
+    /// Applied to every HTTP call, including each call of a batched request.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+    /// The maximum number of calls in flight for [`super::request::Request::execute_batched`]. Defaults to `4`.
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = Some(max_concurrency);
+        self
+    }
+    pub fn build(self) -> Option<ClientConfiguration> {
+        Some(ClientConfiguration {
+            api_url: self.api_url?,
+            api_key: self.api_key?,
+            timeout: self.timeout,
+            retry_policy: self.retry_policy.unwrap_or_default(),
+            max_concurrency: self.max_concurrency.unwrap_or(4).max(1),
+        })
+    }
+}
+
+pub struct ClientConfiguration {
+    pub api_url: URL,
+    pub api_key: ApiKey,
+    pub timeout: Option<Timeout>,
+    pub retry_policy: RetryPolicy,
+    pub max_concurrency: usize,
+}
+
+pub struct URL(pub String);
+
+impl URL {
+    /// Creates an embedding vector representing the input text.
+    pub fn openai_v1_embeddings() -> Self {
+        Self(String::from("https://api.openai.com/v1/embeddings"))
+    }
+}
+
+pub struct ApiKey(pub String);
+
+pub struct Timeout(pub std::time::Duration);
+
+impl From<String> for URL {
+    fn from(value: String) -> Self { Self(value) }
+}
+impl From<String> for ApiKey {
+    fn from(value: String) -> Self { Self(value) }
+}
+impl From<&str> for URL {
+    fn from(value: &str) -> Self { Self(value.to_string()) }
+}
+impl From<&str> for ApiKey {
+    fn from(value: &str) -> Self { Self(value.to_string()) }
+}
+impl From<std::time::Duration> for Timeout {
+    fn from(value: std::time::Duration) -> Self { Self(value) }
+}
+
+impl super::request::Request {
+    /// The most inputs OpenAI accepts in a single call.
+    pub const MAX_INPUTS_PER_CALL: usize = 2048;
+
+    pub async fn execute(
+        self,
+        client_configuration: &ClientConfiguration
+    ) -> Result<super::response::Response, Box<dyn std::error::Error>> {
+        let client = http_client(client_configuration);
+        client_configuration.retry_policy
+            .run(|| execute_once(&client, client_configuration, &self))
+            .await
+    }
+    /// Splits the input list into calls of at most `batch_size` inputs (capped
+    /// at [`Self::MAX_INPUTS_PER_CALL`]), runs them with at most
+    /// `max_concurrency` in flight, and merges the results in input order.
+    pub async fn execute_batched(
+        self,
+        client_configuration: &ClientConfiguration,
+        batch_size: usize,
+    ) -> Result<super::response::Response, Box<dyn std::error::Error>> {
+        let client = http_client(client_configuration);
+        let batch_size = batch_size.min(Self::MAX_INPUTS_PER_CALL);
+        let requests = self.input
+            .clone()
+            .chunks(batch_size)
+            .into_iter()
+            .map(|input| super::request::Request { input, ..self.clone() })
+            .collect::<Vec<_>>();
+        let responses = futures::stream::iter(requests.iter())
+            .map(|request| {
+                client_configuration.retry_policy.run(|| execute_once(&client, client_configuration, request))
+            })
+            .buffered(client_configuration.max_concurrency)
+            .try_collect::<Vec<_>>()
+            .await?;
+        Ok(super::response::Response::merge(responses).unwrap())
+    }
+}
+
+fn http_client(client_configuration: &ClientConfiguration) -> reqwest::Client {
+    if let Some(timeout) = client_configuration.timeout.as_ref() {
+        reqwest::ClientBuilder::new()
+            .timeout(timeout.0)
+            .build()
+            .unwrap()
+    } else {
+        reqwest::ClientBuilder::new().build().unwrap()
+    }
+}
+
+async fn execute_once(
+    client: &reqwest::Client,
+    client_configuration: &ClientConfiguration,
+    request: &super::request::Request,
+) -> Result<super::response::Response, Box<dyn std::error::Error>> {
+    let api_url = client_configuration.api_url.0.as_str();
+    let api_key = client_configuration.api_key.0.as_str();
+    let json_data = serde_json::to_string(request).unwrap();
+    let response = client
+        .post(api_url)
+        .header("Authorization", format!("Bearer {}", api_key))
+        .header("Content-Type", "application/json")
+        .body(json_data)
+        .send()
+        .await?;
+    if let Some(error) = ApiError::from_code(response.status().as_u16()) {
+        return Err(Box::new(error))
+    }
+    let result = response.text().await?;
+    let result = serde_json::from_str::<super::response::Response>(&result)?;
+    Ok(result)
+}
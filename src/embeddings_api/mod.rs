@@ -0,0 +1,4 @@
+pub mod request;
+pub mod response;
+pub mod client;
+pub mod vector_store;
\ No newline at end of file
@@ -0,0 +1,182 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Default)]
+pub struct RequestBuilder {
+    /// Input text to embed, encoded as a string or array of tokens. To embed multiple inputs in a single request, pass an array of strings or array of token arrays. The input must not exceed the max input tokens for the model (8192 tokens for `text-embedding-ada-002`), cannot be an empty string, and any array must be 2048 dimensions or less.
+    pub input: Option<Input>,
+    /// ID of the model to use.
+    pub model: Option<Model>,
+    /// The format to return the embeddings in. Can be either `float` or `base64`.
+    pub encoding_format: Option<EncodingFormat>,
+    /// The number of dimensions the resulting output embeddings should have. Only supported in `text-embedding-3` and later models.
+    pub dimensions: Option<i32>,
+    /// A unique identifier representing your end-user, which can help OpenAI to monitor and detect abuse.
+    pub user: Option<String>,
+}
+
+impl RequestBuilder {
+    /// Input text to embed, encoded as a string or array of tokens. To embed multiple inputs in a single request, pass an array of strings or array of token arrays.
+    pub fn with_input(mut self, input: impl Into<Input>) -> Self {
+        self.input = Some(input.into());
+        self
+    }
+    /// ID of the model to use.
+    pub fn with_model(mut self, model: impl Into<Model>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+    /// The format to return the embeddings in. Can be either `float` or `base64`.
+    pub fn with_encoding_format(mut self, encoding_format: impl Into<EncodingFormat>) -> Self {
+        self.encoding_format = Some(encoding_format.into());
+        self
+    }
+    /// The number of dimensions the resulting output embeddings should have. Only supported in `text-embedding-3` and later models.
+    pub fn with_dimensions(mut self, dimensions: i32) -> Self {
+        self.dimensions = Some(dimensions);
+        self
+    }
+    /// A unique identifier representing your end-user, which can help OpenAI to monitor and detect abuse.
+    pub fn with_user(mut self, user: impl Into<String>) -> Self {
+        self.user = Some(user.into());
+        self
+    }
+    pub fn build(self) -> Option<Request> {
+        let input = self.input?;
+        if input.is_empty() {
+            return None
+        }
+        Some(Request {
+            input,
+            model: self.model?,
+            encoding_format: self.encoding_format,
+            dimensions: self.dimensions,
+            user: self.user,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Request {
+    /// Input text to embed, encoded as a string or array of tokens.
+    pub input: Input,
+    /// ID of the model to use.
+    pub model: Model,
+    /// The format to return the embeddings in. Can be either `float` or `base64`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding_format: Option<EncodingFormat>,
+    /// The number of dimensions the resulting output embeddings should have. Only supported in `text-embedding-3` and later models.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dimensions: Option<i32>,
+    /// A unique identifier representing your end-user, which can help OpenAI to monitor and detect abuse.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+}
+
+/// A single string, a list of strings, a single token array, or a list of token arrays.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum Input {
+    String(String),
+    Strings(Vec<String>),
+    Tokens(Vec<u32>),
+    TokenBatches(Vec<Vec<u32>>),
+}
+
+impl Input {
+    /// The number of embeddings this input produces.
+    pub fn len(&self) -> usize {
+        match self {
+            Input::String(_) | Input::Tokens(_) => 1,
+            Input::Strings(xs) => xs.len(),
+            Input::TokenBatches(xs) => xs.len(),
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Input::String(x) => x.is_empty(),
+            Input::Tokens(x) => x.is_empty(),
+            Input::Strings(xs) => xs.is_empty(),
+            Input::TokenBatches(xs) => xs.is_empty(),
+        }
+    }
+    /// Splits a list of inputs into lists of at most `size` inputs each.
+    pub fn chunks(self, size: usize) -> Vec<Input> {
+        let size = size.max(1);
+        match self {
+            Input::Strings(xs) if xs.len() > size => xs
+                .chunks(size)
+                .map(|x| Input::Strings(x.to_vec()))
+                .collect(),
+            Input::TokenBatches(xs) if xs.len() > size => xs
+                .chunks(size)
+                .map(|x| Input::TokenBatches(x.to_vec()))
+                .collect(),
+            input => vec![input],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(transparent)]
+pub struct Model(pub String);
+
+impl Model {
+    pub fn text_embedding_3_small() -> Self {
+        Self(String::from("text-embedding-3-small"))
+    }
+    pub fn text_embedding_3_large() -> Self {
+        Self(String::from("text-embedding-3-large"))
+    }
+    pub fn text_embedding_ada_002() -> Self {
+        Self(String::from("text-embedding-ada-002"))
+    }
+}
+
+/// The format to return the embeddings in. Can be either `float` or `base64`.
+///
+/// `base64` is more compact on the wire; [`super::response::Embedding::vector`] decodes either.
+#[derive(Debug, Clone, Serialize)]
+#[serde(transparent)]
+pub struct EncodingFormat(pub String);
+
+impl EncodingFormat {
+    pub fn float() -> Self {
+        Self(String::from("float"))
+    }
+    pub fn base64() -> Self {
+        Self(String::from("base64"))
+    }
+}
+
+impl From<String> for Input {
+    fn from(value: String) -> Self { Self::String(value) }
+}
+impl From<&str> for Input {
+    fn from(value: &str) -> Self { Self::String(value.to_string()) }
+}
+impl From<Vec<String>> for Input {
+    fn from(value: Vec<String>) -> Self { Self::Strings(value) }
+}
+impl From<Vec<&str>> for Input {
+    fn from(value: Vec<&str>) -> Self { Self::Strings(value.into_iter().map(str::to_string).collect()) }
+}
+impl From<Vec<u32>> for Input {
+    fn from(value: Vec<u32>) -> Self { Self::Tokens(value) }
+}
+impl From<Vec<Vec<u32>>> for Input {
+    fn from(value: Vec<Vec<u32>>) -> Self { Self::TokenBatches(value) }
+}
+
+impl From<String> for Model {
+    fn from(value: String) -> Self { Self(value) }
+}
+impl From<String> for EncodingFormat {
+    fn from(value: String) -> Self { Self(value) }
+}
+
+impl From<&str> for Model {
+    fn from(value: &str) -> Self { Self(value.to_string()) }
+}
+impl From<&str> for EncodingFormat {
+    fn from(value: &str) -> Self { Self(value.to_string()) }
+}
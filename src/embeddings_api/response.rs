@@ -0,0 +1,77 @@
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct Response {
+    pub object: String,
+    pub data: Vec<Embedding>,
+    pub model: String,
+    pub usage: Usage,
+}
+
+impl Response {
+    /// Concatenates the embeddings of several responses, in order, renumbering
+    /// their indices and summing their usage.
+    pub fn merge(responses: impl IntoIterator<Item = Response>) -> Option<Self> {
+        let mut responses = responses.into_iter();
+        let mut merged = responses.next()?;
+        for response in responses {
+            let offset = merged.data.len();
+            merged.data.extend(response.data.into_iter().map(|mut x| {
+                x.index += offset;
+                x
+            }));
+            merged.usage.prompt_tokens += response.usage.prompt_tokens;
+            merged.usage.total_tokens += response.usage.total_tokens;
+        }
+        Some(merged)
+    }
+    /// Decodes every embedding, ordered by index.
+    pub fn vectors(&self) -> Result<Vec<Vec<f32>>, base64::DecodeError> {
+        let mut data = self.data.iter().collect::<Vec<_>>();
+        data.sort_by_key(|x| x.index);
+        data.into_iter().map(Embedding::vector).collect()
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct Embedding {
+    pub object: String,
+    pub index: usize,
+    pub embedding: EmbeddingVector,
+}
+
+impl Embedding {
+    pub fn vector(&self) -> Result<Vec<f32>, base64::DecodeError> {
+        self.embedding.decode()
+    }
+}
+
+/// Either a list of floats, or (for `encoding_format = base64`) the
+/// little-endian `f32` values encoded as base64.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(untagged)]
+pub enum EmbeddingVector {
+    Float(Vec<f32>),
+    Base64(String),
+}
+
+impl EmbeddingVector {
+    pub fn decode(&self) -> Result<Vec<f32>, base64::DecodeError> {
+        use base64::prelude::*;
+        match self {
+            EmbeddingVector::Float(xs) => Ok(xs.clone()),
+            EmbeddingVector::Base64(x) => {
+                let bytes = BASE64_STANDARD.decode(x)?;
+                let floats = bytes
+                    .chunks_exact(4)
+                    .map(|x| f32::from_le_bytes([x[0], x[1], x[2], x[3]]))
+                    .collect();
+                Ok(floats)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct Usage {
+    pub prompt_tokens: isize,
+    pub total_tokens: isize,
+}
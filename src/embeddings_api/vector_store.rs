@@ -0,0 +1,263 @@
+//! A small in-process vector index for retrieval-augmented prompts.
+//!
+//! Meant for a few thousand chunks: search is a linear scan, and the whole
+//! store is persisted as a single JSON file.
+use std::path::Path;
+
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::text_api::request::{Message, RequestBuilder};
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// DOCUMENT
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Document {
+    pub id: String,
+    pub text: String,
+    #[serde(default)]
+    pub metadata: Map<String, Value>,
+    #[serde(default)]
+    pub vector: Vec<f32>,
+}
+
+impl Document {
+    pub fn new(id: impl Into<String>, text: impl Into<String>) -> Self {
+        Self { id: id.into(), text: text.into(), metadata: Map::new(), vector: Vec::new() }
+    }
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+    pub fn with_vector(mut self, vector: Vec<f32>) -> Self {
+        self.vector = vector;
+        self
+    }
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// VECTOR STORE
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VectorStore {
+    documents: Vec<Document>,
+}
+
+impl VectorStore {
+    pub fn new() -> Self { Self::default() }
+    pub fn open(file_path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let source = std::fs::read_to_string(file_path.as_ref())?;
+        let store = serde_json::from_str::<Self>(&source)?;
+        Ok(store)
+    }
+    pub fn save(&self, file_path: impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>> {
+        let file_path = file_path.as_ref();
+        if let Some(parent) = file_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(file_path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+    pub fn documents(&self) -> &[Document] {
+        &self.documents
+    }
+    pub fn get(&self, id: impl AsRef<str>) -> Option<&Document> {
+        let id = id.as_ref();
+        self.documents.iter().find(|x| x.id == id)
+    }
+    /// Adds the document, replacing any document with the same id.
+    pub fn add(&mut self, document: Document) {
+        self.remove(&document.id);
+        self.documents.push(document);
+    }
+    /// Adds documents embedded in one request, i.e. `documents[i]` gets the
+    /// embedding with index `i`. Nothing is added unless there is exactly one
+    /// embedding per document.
+    pub fn add_embedded(
+        &mut self,
+        documents: Vec<Document>,
+        response: &super::response::Response,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let vectors = response.vectors()?;
+        if vectors.len() != documents.len() {
+            return Err(Box::new(EmbeddingCountMismatch { documents: documents.len(), embeddings: vectors.len() }))
+        }
+        for (document, vector) in documents.into_iter().zip(vectors) {
+            self.add(document.with_vector(vector));
+        }
+        Ok(())
+    }
+    pub fn remove(&mut self, id: impl AsRef<str>) -> Option<Document> {
+        let id = id.as_ref();
+        let index = self.documents.iter().position(|x| x.id == id)?;
+        Some(self.documents.remove(index))
+    }
+    /// The `top_k` best scoring documents that match every filter, best first.
+    ///
+    /// Documents whose vector has a different length than the query’s (e.g.
+    /// embedded with another model, or not at all) are skipped.
+    pub fn search(&self, query: &Query) -> Vec<SearchResult<'_>> {
+        let mut results = self.documents
+            .iter()
+            .filter(|document| document.vector.len() == query.vector.len())
+            .filter(|document| query.filters.iter().all(|x| x.matches(&document.metadata)))
+            .map(|document| SearchResult {
+                document,
+                score: query.metric.score(&query.vector, &document.vector),
+            })
+            .collect::<Vec<_>>();
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+        results.truncate(query.top_k);
+        results
+    }
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// QUERY
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+#[derive(Debug, Clone)]
+pub struct Query {
+    pub vector: Vec<f32>,
+    /// Defaults to `4`.
+    pub top_k: usize,
+    pub metric: Metric,
+    pub filters: Vec<Filter>,
+}
+
+impl Query {
+    pub fn new(vector: Vec<f32>) -> Self {
+        Self { vector, top_k: 4, metric: Metric::default(), filters: Vec::new() }
+    }
+    pub fn with_top_k(mut self, top_k: usize) -> Self {
+        self.top_k = top_k;
+        self
+    }
+    pub fn with_metric(mut self, metric: Metric) -> Self {
+        self.metric = metric;
+        self
+    }
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub enum Metric {
+    #[default]
+    Cosine,
+    /// Equivalent to cosine for normalized vectors (e.g. OpenAI embeddings), and cheaper.
+    Dot,
+}
+
+impl Metric {
+    pub(crate) fn score(&self, a: &[f32], b: &[f32]) -> f32 {
+        let dot = a.iter().zip(b).map(|(x, y)| x * y).sum::<f32>();
+        match self {
+            Metric::Dot => dot,
+            Metric::Cosine => {
+                let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+                let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+                if norm_a == 0.0 || norm_b == 0.0 {
+                    return 0.0
+                }
+                dot / (norm_a * norm_b)
+            }
+        }
+    }
+}
+
+/// A predicate on a document's metadata.
+#[derive(Debug, Clone)]
+pub enum Filter {
+    Eq(String, Value),
+    Ne(String, Value),
+    In(String, Vec<Value>),
+    Exists(String),
+}
+
+impl Filter {
+    pub fn eq(key: impl Into<String>, value: impl Into<Value>) -> Self {
+        Filter::Eq(key.into(), value.into())
+    }
+    pub fn ne(key: impl Into<String>, value: impl Into<Value>) -> Self {
+        Filter::Ne(key.into(), value.into())
+    }
+    pub fn is_in(key: impl Into<String>, values: impl IntoIterator<Item = impl Into<Value>>) -> Self {
+        Filter::In(key.into(), values.into_iter().map(Into::into).collect())
+    }
+    pub fn exists(key: impl Into<String>) -> Self {
+        Filter::Exists(key.into())
+    }
+    fn matches(&self, metadata: &Map<String, Value>) -> bool {
+        match self {
+            Filter::Eq(key, value) => metadata.get(key) == Some(value),
+            Filter::Ne(key, value) => metadata.get(key) != Some(value),
+            Filter::In(key, values) => metadata.get(key).map(|x| values.contains(x)).unwrap_or(false),
+            Filter::Exists(key) => metadata.contains_key(key),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchResult<'a> {
+    pub document: &'a Document,
+    pub score: f32,
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// RETRIEVAL
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+/// A system message listing the retrieved passages, numbered in rank order.
+pub fn context_message(results: &[SearchResult]) -> Message {
+    let passages = results
+        .iter()
+        .enumerate()
+        .map(|(ix, result)| format!("[{}] {}", ix + 1, result.document.text.trim()))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    Message::system(format!(
+        "Answer using the following context passages where relevant.\n\n{passages}"
+    ))
+}
+
+/// Inserts the [`context_message`] after the request's leading system messages.
+pub fn with_context(mut request: RequestBuilder, results: &[SearchResult]) -> RequestBuilder {
+    let position = request.messages
+        .iter()
+        .take_while(|x| matches!(x, Message::System { .. }))
+        .count();
+    request.messages.insert(position, context_message(results));
+    request
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// ERRORS
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+#[derive(Debug, Clone)]
+pub struct EmbeddingCountMismatch {
+    pub documents: usize,
+    pub embeddings: usize,
+}
+
+impl std::fmt::Display for EmbeddingCountMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let msg = format!(
+            "Error: Embedding Count Mismatch! {} documents, but {} embeddings.",
+            self.documents,
+            self.embeddings,
+        );
+        let msg = msg.red();
+        write!(f, "{msg}")
+    }
+}
+
+impl std::error::Error for EmbeddingCountMismatch {}
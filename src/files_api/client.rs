@@ -0,0 +1,308 @@
+pub use crate::text_api::client::{ApiError, RetryPolicy};
+
+#[derive(Default)]
+pub struct ClientConfigurationBuilder {
+    pub api_url: Option<URL>,
+    pub api_key: Option<ApiKey>,
+    pub timeout: Option<Timeout>,
+    pub retry_policy: Option<RetryPolicy>,
+}
+
+impl ClientConfigurationBuilder {
+    pub fn with_api_url(mut self, api_url: impl Into<URL>) -> Self {
+        self.api_url = Some(api_url.into());
+        self
+    }
+    pub fn with_api_key(mut self, api_key: impl Into<ApiKey>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+    pub fn with_timeout(mut self, timeout: impl Into<Timeout>) -> Self {
+        self.timeout = Some(timeout.into());
+        self
+    }
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+    pub fn build(self) -> Option<ClientConfiguration> {
+        Some(ClientConfiguration {
+            api_url: self.api_url?,
+            api_key: self.api_key?,
+            timeout: self.timeout,
+            retry_policy: self.retry_policy.unwrap_or_default(),
+        })
+    }
+}
+
+pub struct ClientConfiguration {
+    pub api_url: URL,
+    pub api_key: ApiKey,
+    pub timeout: Option<Timeout>,
+    pub retry_policy: RetryPolicy,
+}
+
+impl ClientConfiguration {
+    pub(crate) fn http_client(&self) -> reqwest::Client {
+        if let Some(timeout) = self.timeout.as_ref() {
+            reqwest::ClientBuilder::new()
+                .timeout(timeout.0)
+                .build()
+                .unwrap()
+        } else {
+            reqwest::ClientBuilder::new().build().unwrap()
+        }
+    }
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}{path}", self.api_url.0.trim_end_matches('/'))
+    }
+}
+
+pub struct URL(pub String);
+
+impl URL {
+    /// Files are uploaded to, and managed under, this endpoint.
+    pub fn openai_v1_files() -> Self {
+        Self(String::from("https://api.openai.com/v1/files"))
+    }
+}
+
+pub struct ApiKey(pub String);
+
+pub struct Timeout(pub std::time::Duration);
+
+impl From<String> for URL {
+    fn from(value: String) -> Self { Self(value) }
+}
+impl From<String> for ApiKey {
+    fn from(value: String) -> Self { Self(value) }
+}
+impl From<&str> for URL {
+    fn from(value: &str) -> Self { Self(value.to_string()) }
+}
+impl From<&str> for ApiKey {
+    fn from(value: &str) -> Self { Self(value.to_string()) }
+}
+impl From<std::time::Duration> for Timeout {
+    fn from(value: std::time::Duration) -> Self { Self(value) }
+}
+
+impl super::request::UploadRequest {
+    /// Uploads the file with `multipart/form-data`, streaming it from disk or
+    /// the reader when given one. Timeouts and dropped connections aren’t
+    /// retried, since the file may have been created already.
+    pub async fn execute(
+        self,
+        client_configuration: &ClientConfiguration
+    ) -> Result<super::response::File, Box<dyn std::error::Error>> {
+        use super::request::Contents;
+        let client = client_configuration.http_client();
+        let super::request::UploadRequest { file_name, purpose, contents } = self;
+        let retry_policy = &client_configuration.retry_policy;
+        let upload = |part: reqwest::multipart::Part| {
+            send_upload(&client, client_configuration, &purpose, part.file_name(file_name.clone()))
+        };
+        match contents {
+            // A reader can only be read once, so it isn't retried.
+            Contents::Reader(reader) => {
+                let body = reqwest::Body::wrap_stream(reader_stream(reader));
+                upload(reqwest::multipart::Part::stream(body)).await
+            }
+            Contents::Bytes(bytes) => {
+                retry_policy.run_once_accepted(|| upload(reqwest::multipart::Part::bytes(bytes.clone()))).await
+            }
+            Contents::Path(file_path) => retry_policy.run_once_accepted(|| async {
+                let file = tokio::fs::File::open(&file_path).await?;
+                let length = file.metadata().await?.len();
+                let body = reqwest::Body::wrap_stream(reader_stream(file));
+                upload(reqwest::multipart::Part::stream_with_length(body, length)).await
+            }).await,
+        }
+    }
+}
+
+async fn send_upload(
+    client: &reqwest::Client,
+    client_configuration: &ClientConfiguration,
+    purpose: &super::request::Purpose,
+    part: reqwest::multipart::Part,
+) -> Result<super::response::File, Box<dyn std::error::Error>> {
+    let api_key = client_configuration.api_key.0.as_str();
+    let form = reqwest::multipart::Form::new()
+        .text("purpose", purpose.0.clone())
+        .part("file", part);
+    let response = client
+        .post(client_configuration.endpoint(""))
+        .header("Authorization", format!("Bearer {}", api_key))
+        .multipart(form)
+        .send()
+        .await?;
+    if let Some(error) = ApiError::from_code(response.status().as_u16()) {
+        return Err(Box::new(error))
+    }
+    let result = response.text().await?;
+    let result = serde_json::from_str::<super::response::File>(&result)?;
+    Ok(result)
+}
+
+/// Reads `reader` in chunks, for streaming request bodies.
+fn reader_stream<R>(reader: R) -> impl futures::Stream<Item = std::io::Result<Vec<u8>>> + Send + Sync + 'static
+where
+    R: tokio::io::AsyncRead + Send + Sync + Unpin + 'static,
+{
+    use tokio::io::AsyncReadExt;
+    futures::stream::unfold(Some(reader), |reader| async move {
+        let mut reader = reader?;
+        let mut buffer = vec![0; 64 * 1024];
+        match reader.read(&mut buffer).await {
+            Ok(0) => None,
+            Ok(length) => {
+                buffer.truncate(length);
+                Some((Ok(buffer), Some(reader)))
+            }
+            Err(error) => Some((Err(error), None)),
+        }
+    })
+}
+
+impl super::request::ListRequest {
+    /// Fetches a single page of files; pass the page’s `last_id` as `after` for the next one.
+    pub async fn execute(
+        self,
+        client_configuration: &ClientConfiguration
+    ) -> Result<super::response::FileList, Box<dyn std::error::Error>> {
+        let client = client_configuration.http_client();
+        get_json(&client, client_configuration, "", &self).await
+    }
+    /// Follows the pagination cursor until every matching file is listed.
+    pub async fn execute_all(
+        self,
+        client_configuration: &ClientConfiguration
+    ) -> Result<Vec<super::response::File>, Box<dyn std::error::Error>> {
+        let client = client_configuration.http_client();
+        let mut request = self;
+        let mut files = Vec::new();
+        loop {
+            let mut page = get_json::<super::response::FileList>(&client, client_configuration, "", &request).await?;
+            let next = page.last_id.clone().or_else(|| page.data.last().map(|x| x.id.clone()));
+            files.append(&mut page.data);
+            match next {
+                Some(next) if page.has_more => request.after = Some(next),
+                _ => return Ok(files),
+            }
+        }
+    }
+}
+
+/// Returns information about a specific file.
+pub async fn retrieve(
+    client_configuration: &ClientConfiguration,
+    file_id: impl AsRef<str>,
+) -> Result<super::response::File, Box<dyn std::error::Error>> {
+    let client = client_configuration.http_client();
+    let path = format!("/{}", file_id.as_ref());
+    get_json(&client, client_configuration, &path, &[("", ""); 0]).await
+}
+
+/// Deletes a file.
+pub async fn delete(
+    client_configuration: &ClientConfiguration,
+    file_id: impl AsRef<str>,
+) -> Result<super::response::DeletedFile, Box<dyn std::error::Error>> {
+    let client = client_configuration.http_client();
+    let api_key = client_configuration.api_key.0.as_str();
+    let api_url = client_configuration.endpoint(&format!("/{}", file_id.as_ref()));
+    client_configuration.retry_policy.run(|| async {
+        let response = client
+            .delete(api_url.as_str())
+            .header("Authorization", format!("Bearer {}", api_key))
+            .send()
+            .await?;
+        if let Some(error) = ApiError::from_code(response.status().as_u16()) {
+            return Err(Box::new(error) as Box<dyn std::error::Error>)
+        }
+        let result = response.text().await?;
+        let result = serde_json::from_str::<super::response::DeletedFile>(&result)?;
+        Ok(result)
+    }).await
+}
+
+/// Returns the contents of the specified file.
+pub async fn retrieve_content(
+    client_configuration: &ClientConfiguration,
+    file_id: impl AsRef<str>,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let client = client_configuration.http_client();
+    client_configuration.retry_policy.run(|| async {
+        let response = get_content(&client, client_configuration, file_id.as_ref()).await?;
+        let result = response.bytes().await?.to_vec();
+        Ok(result)
+    }).await
+}
+
+/// Streams the contents of the specified file to `file_path`, without holding it in memory.
+pub async fn download(
+    client_configuration: &ClientConfiguration,
+    file_id: impl AsRef<str>,
+    file_path: impl AsRef<std::path::Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use futures::StreamExt;
+    use tokio::io::AsyncWriteExt;
+    let file_path = file_path.as_ref();
+    if let Some(parent) = file_path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let client = client_configuration.http_client();
+    client_configuration.retry_policy.run(|| async {
+        let response = get_content(&client, client_configuration, file_id.as_ref()).await?;
+        let mut file = tokio::fs::File::create(file_path).await?;
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            file.write_all(&chunk?).await?;
+        }
+        file.flush().await?;
+        Ok(())
+    }).await
+}
+
+async fn get_content(
+    client: &reqwest::Client,
+    client_configuration: &ClientConfiguration,
+    file_id: &str,
+) -> Result<reqwest::Response, Box<dyn std::error::Error>> {
+    let api_key = client_configuration.api_key.0.as_str();
+    let api_url = client_configuration.endpoint(&format!("/{file_id}/content"));
+    let response = client
+        .get(api_url)
+        .header("Authorization", format!("Bearer {}", api_key))
+        .send()
+        .await?;
+    if let Some(error) = ApiError::from_code(response.status().as_u16()) {
+        return Err(Box::new(error))
+    }
+    Ok(response)
+}
+
+async fn get_json<T: serde::de::DeserializeOwned>(
+    client: &reqwest::Client,
+    client_configuration: &ClientConfiguration,
+    path: &str,
+    query: &(impl serde::Serialize + ?Sized),
+) -> Result<T, Box<dyn std::error::Error>> {
+    let api_key = client_configuration.api_key.0.as_str();
+    let api_url = client_configuration.endpoint(path);
+    client_configuration.retry_policy.run(|| async {
+        let response = client
+            .get(api_url.as_str())
+            .header("Authorization", format!("Bearer {}", api_key))
+            .query(query)
+            .send()
+            .await?;
+        if let Some(error) = ApiError::from_code(response.status().as_u16()) {
+            return Err(Box::new(error) as Box<dyn std::error::Error>)
+        }
+        let result = response.text().await?;
+        let result = serde_json::from_str::<T>(&result)?;
+        Ok(result)
+    }).await
+}
@@ -0,0 +1,167 @@
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Default)]
+pub struct UploadRequestBuilder {
+    /// The name the file is stored under. Defaults to the file name of the path, if uploading from a path.
+    pub file_name: Option<String>,
+    /// The intended purpose of the uploaded file.
+    pub purpose: Option<Purpose>,
+    /// The file contents.
+    pub contents: Option<Contents>,
+}
+
+impl UploadRequestBuilder {
+    /// The name the file is stored under. Defaults to the file name of the path, if uploading from a path.
+    pub fn with_file_name(mut self, file_name: impl Into<String>) -> Self {
+        self.file_name = Some(file_name.into());
+        self
+    }
+    /// The intended purpose of the uploaded file.
+    pub fn with_purpose(mut self, purpose: impl Into<Purpose>) -> Self {
+        self.purpose = Some(purpose.into());
+        self
+    }
+    /// The file contents, held in memory.
+    pub fn with_contents(mut self, contents: impl Into<Vec<u8>>) -> Self {
+        self.contents = Some(Contents::Bytes(contents.into()));
+        self
+    }
+    /// Streams the file from disk rather than reading it into memory.
+    pub fn with_file_path(mut self, file_path: impl AsRef<Path>) -> Self {
+        self.contents = Some(Contents::Path(file_path.as_ref().to_path_buf()));
+        self
+    }
+    /// Streams the contents from a reader. Uploads from a reader can’t be retried.
+    pub fn with_reader(mut self, reader: impl tokio::io::AsyncRead + Send + Sync + Unpin + 'static) -> Self {
+        self.contents = Some(Contents::Reader(Box::new(reader)));
+        self
+    }
+    pub fn build(self) -> Option<UploadRequest> {
+        let contents = self.contents?;
+        let file_name = match (self.file_name, &contents) {
+            (Some(file_name), _) => file_name,
+            (None, Contents::Path(file_path)) => file_path.file_name()?.to_string_lossy().to_string(),
+            (None, _) => return None,
+        };
+        Some(UploadRequest {
+            file_name,
+            purpose: self.purpose?,
+            contents,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct UploadRequest {
+    pub file_name: String,
+    pub purpose: Purpose,
+    pub contents: Contents,
+}
+
+/// Where the uploaded bytes come from.
+pub enum Contents {
+    Bytes(Vec<u8>),
+    Path(PathBuf),
+    Reader(Box<dyn tokio::io::AsyncRead + Send + Sync + Unpin>),
+}
+
+impl std::fmt::Debug for Contents {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Contents::Bytes(bytes) => write!(f, "Bytes({} bytes)", bytes.len()),
+            Contents::Path(file_path) => write!(f, "Path({file_path:?})"),
+            Contents::Reader(_) => write!(f, "Reader"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ListRequestBuilder {
+    /// Only return files with the given purpose.
+    pub purpose: Option<Purpose>,
+    /// A limit on the number of objects to be returned per page. Limit can range between 1 and 10,000, and the default is 10,000.
+    pub limit: Option<usize>,
+    /// Sort order by the `created_at` timestamp of the objects. `asc` for ascending order and `desc` for descending order.
+    pub order: Option<String>,
+    /// A cursor for use in pagination. `after` is an object ID that defines your place in the list.
+    pub after: Option<String>,
+}
+
+impl ListRequestBuilder {
+    /// Only return files with the given purpose.
+    pub fn with_purpose(mut self, purpose: impl Into<Purpose>) -> Self {
+        self.purpose = Some(purpose.into());
+        self
+    }
+    /// A limit on the number of objects to be returned per page. Limit can range between 1 and 10,000, and the default is 10,000.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+    /// Sort order by the `created_at` timestamp of the objects. `asc` for ascending order and `desc` for descending order.
+    pub fn with_order(mut self, order: impl Into<String>) -> Self {
+        self.order = Some(order.into());
+        self
+    }
+    /// A cursor for use in pagination. `after` is an object ID that defines your place in the list.
+    pub fn with_after(mut self, after: impl Into<String>) -> Self {
+        self.after = Some(after.into());
+        self
+    }
+    pub fn build(self) -> ListRequest {
+        ListRequest {
+            purpose: self.purpose,
+            limit: self.limit,
+            order: self.order,
+            after: self.after,
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ListRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purpose: Option<Purpose>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+}
+
+/// The intended purpose of an uploaded file.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Purpose(pub String);
+
+impl Purpose {
+    /// Input for the Batch API.
+    pub fn batch() -> Self {
+        Self(String::from("batch"))
+    }
+    /// Training data for fine-tuning.
+    pub fn fine_tune() -> Self {
+        Self(String::from("fine-tune"))
+    }
+    /// Files for Assistants and Message files.
+    pub fn assistants() -> Self {
+        Self(String::from("assistants"))
+    }
+    /// Images used for vision fine-tuning.
+    pub fn vision() -> Self {
+        Self(String::from("vision"))
+    }
+    /// Flexible file type for any purpose.
+    pub fn user_data() -> Self {
+        Self(String::from("user_data"))
+    }
+}
+
+impl From<String> for Purpose {
+    fn from(value: String) -> Self { Self(value) }
+}
+impl From<&str> for Purpose {
+    fn from(value: &str) -> Self { Self(value.to_string()) }
+}
+
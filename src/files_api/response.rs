@@ -0,0 +1,40 @@
+/// The `File` object, representing a document that has been uploaded to OpenAI.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct File {
+    /// The file identifier, which can be referenced in the API endpoints.
+    pub id: String,
+    /// The object type, which is always `file`.
+    pub object: String,
+    /// The size of the file, in bytes.
+    pub bytes: isize,
+    /// The Unix timestamp (in seconds) for when the file was created.
+    pub created_at: isize,
+    /// The name of the file.
+    pub filename: String,
+    /// The intended purpose of the file.
+    pub purpose: super::request::Purpose,
+    /// Deprecated. The current status of the file, which can be either `uploaded`, `processed`, or `error`.
+    #[serde(default)]
+    pub status: Option<String>,
+}
+
+/// A page of [`File`]s.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FileList {
+    pub object: String,
+    pub data: Vec<File>,
+    /// Whether there are more files after the last one in `data`.
+    #[serde(default)]
+    pub has_more: bool,
+    #[serde(default)]
+    pub first_id: Option<String>,
+    #[serde(default)]
+    pub last_id: Option<String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct DeletedFile {
+    pub id: String,
+    pub object: String,
+    pub deleted: bool,
+}
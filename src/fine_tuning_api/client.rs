@@ -0,0 +1,234 @@
+use colored::Colorize;
+
+use super::response::{Job, JobEvent, JobEventList, JobStatus};
+
+pub use crate::text_api::client::{ApiError, RetryPolicy};
+
+#[derive(Default)]
+pub struct ClientConfigurationBuilder {
+    pub api_url: Option<URL>,
+    pub api_key: Option<ApiKey>,
+    pub timeout: Option<Timeout>,
+    pub retry_policy: Option<RetryPolicy>,
+    pub poll_interval: Option<std::time::Duration>,
+}
+
+impl ClientConfigurationBuilder {
+    pub fn with_api_url(mut self, api_url: impl Into<URL>) -> Self {
+        self.api_url = Some(api_url.into());
+        self
+    }
+    pub fn with_api_key(mut self, api_key: impl Into<ApiKey>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+    pub fn with_timeout(mut self, timeout: impl Into<Timeout>) -> Self {
+        self.timeout = Some(timeout.into());
+        self
+    }
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+    /// How often [`Job::watch`] checks for new events. Defaults to 15 seconds.
+    pub fn with_poll_interval(mut self, poll_interval: std::time::Duration) -> Self {
+        self.poll_interval = Some(poll_interval);
+        self
+    }
+    pub fn build(self) -> Option<ClientConfiguration> {
+        Some(ClientConfiguration {
+            api_url: self.api_url?,
+            api_key: self.api_key?,
+            timeout: self.timeout,
+            retry_policy: self.retry_policy.unwrap_or_default(),
+            poll_interval: self.poll_interval.unwrap_or(std::time::Duration::from_secs(15)),
+        })
+    }
+}
+
+pub struct ClientConfiguration {
+    pub api_url: URL,
+    pub api_key: ApiKey,
+    pub timeout: Option<Timeout>,
+    pub retry_policy: RetryPolicy,
+    pub poll_interval: std::time::Duration,
+}
+
+impl ClientConfiguration {
+    async fn send<T: serde::de::DeserializeOwned>(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<T, Box<dyn std::error::Error>> {
+        let client = {
+            if let Some(timeout) = self.timeout.as_ref() {
+                reqwest::ClientBuilder::new()
+                    .timeout(timeout.0)
+                    .build()
+                    .unwrap()
+            } else {
+                reqwest::ClientBuilder::new().build().unwrap()
+            }
+        };
+        let api_url = format!("{}{path}", self.api_url.0.trim_end_matches('/'));
+        let api_key = self.api_key.0.as_str();
+        self.retry_policy.run(|| async {
+            let mut request = client
+                .request(method.clone(), api_url.as_str())
+                .header("Authorization", format!("Bearer {}", api_key));
+            if let Some(body) = body.as_ref() {
+                request = request.json(body);
+            }
+            let response = request.send().await?;
+            if let Some(error) = ApiError::from_code(response.status().as_u16()) {
+                return Err(Box::new(error) as Box<dyn std::error::Error>)
+            }
+            let result = response.text().await?;
+            let result = serde_json::from_str::<T>(&result)?;
+            Ok(result)
+        }).await
+    }
+}
+
+pub struct URL(pub String);
+
+impl URL {
+    /// Creates and manages fine-tuning jobs.
+    pub fn openai_v1_fine_tuning_jobs() -> Self {
+        Self(String::from("https://api.openai.com/v1/fine_tuning/jobs"))
+    }
+}
+
+pub struct ApiKey(pub String);
+
+pub struct Timeout(pub std::time::Duration);
+
+impl From<String> for URL {
+    fn from(value: String) -> Self { Self(value) }
+}
+impl From<String> for ApiKey {
+    fn from(value: String) -> Self { Self(value) }
+}
+impl From<&str> for URL {
+    fn from(value: &str) -> Self { Self(value.to_string()) }
+}
+impl From<&str> for ApiKey {
+    fn from(value: &str) -> Self { Self(value.to_string()) }
+}
+impl From<std::time::Duration> for Timeout {
+    fn from(value: std::time::Duration) -> Self { Self(value) }
+}
+
+impl super::request::JobRequest {
+    /// Creates the fine-tuning job, without waiting for it.
+    pub async fn execute(
+        self,
+        client_configuration: &ClientConfiguration
+    ) -> Result<Job, Box<dyn std::error::Error>> {
+        let body = serde_json::to_value(&self)?;
+        client_configuration.send(reqwest::Method::POST, "", Some(body)).await
+    }
+}
+
+impl Job {
+    /// Fetches the current state of the job.
+    pub async fn refresh(
+        &self,
+        client_configuration: &ClientConfiguration
+    ) -> Result<Job, Box<dyn std::error::Error>> {
+        client_configuration.send(reqwest::Method::GET, &format!("/{}", self.id), None).await
+    }
+    /// Immediately cancels the job.
+    pub async fn cancel(
+        &self,
+        client_configuration: &ClientConfiguration
+    ) -> Result<Job, Box<dyn std::error::Error>> {
+        client_configuration.send(reqwest::Method::POST, &format!("/{}/cancel", self.id), None).await
+    }
+    /// A page of the job’s most recent events, newest first.
+    pub async fn events(
+        &self,
+        client_configuration: &ClientConfiguration,
+        limit: usize,
+    ) -> Result<JobEventList, Box<dyn std::error::Error>> {
+        let path = format!("/{}/events?limit={limit}", self.id);
+        client_configuration.send(reqwest::Method::GET, &path, None).await
+    }
+    /// A page of the events older than the event `after`, newest first.
+    pub async fn events_after(
+        &self,
+        client_configuration: &ClientConfiguration,
+        after: &str,
+        limit: usize,
+    ) -> Result<JobEventList, Box<dyn std::error::Error>> {
+        let path = format!("/{}/events?limit={limit}&after={after}", self.id);
+        client_configuration.send(reqwest::Method::GET, &path, None).await
+    }
+    /// Polls the job every `poll_interval` until it finishes, passing each new
+    /// event to `on_event` in chronological order. Fails with [`JobFailed`] if
+    /// the job fails.
+    pub async fn watch(
+        self,
+        client_configuration: &ClientConfiguration,
+        mut on_event: impl FnMut(&JobEvent),
+    ) -> Result<Job, Box<dyn std::error::Error>> {
+        let mut job = self;
+        let mut last_event_id: Option<String> = None;
+        loop {
+            let new_events = job.events_since(client_configuration, last_event_id.as_deref()).await?;
+            if let Some(newest) = new_events.first() {
+                last_event_id = Some(newest.id.clone());
+            }
+            new_events.iter().rev().for_each(&mut on_event);
+            if job.status.is_terminal() {
+                break
+            }
+            tokio::time::sleep(client_configuration.poll_interval).await;
+            job = job.refresh(client_configuration).await?;
+        }
+        if job.status == JobStatus::Failed {
+            return Err(Box::new(JobFailed(job)))
+        }
+        Ok(job)
+    }
+    /// The events newer than `last_event_id` (all of them without one),
+    /// newest first, paging back until it’s reached.
+    async fn events_since(
+        &self,
+        client_configuration: &ClientConfiguration,
+        last_event_id: Option<&str>,
+    ) -> Result<Vec<JobEvent>, Box<dyn std::error::Error>> {
+        let mut events = Vec::new();
+        let mut page = self.events(client_configuration, 100).await?;
+        loop {
+            let (count, before) = (page.data.len(), events.len());
+            events.extend(page.data.into_iter().take_while(|x| Some(x.id.as_str()) != last_event_id));
+            let reached = events.len() - before < count;
+            let oldest = events.last().map(|x| x.id.clone());
+            match oldest {
+                Some(after) if page.has_more && !reached && count > 0 => {
+                    page = self.events_after(client_configuration, &after, 100).await?;
+                }
+                _ => return Ok(events),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct JobFailed(pub Job);
+
+impl std::fmt::Display for JobFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reason = self.0.error
+            .as_ref()
+            .and_then(|x| x.message.clone())
+            .unwrap_or_default();
+        let msg = format!("Error: Fine-Tuning Job Failed! {:?}: {reason}", self.0.id);
+        let msg = msg.red();
+        write!(f, "{msg}")
+    }
+}
+
+impl std::error::Error for JobFailed {}
@@ -0,0 +1,4 @@
+pub mod training_data;
+pub mod request;
+pub mod response;
+pub mod client;
\ No newline at end of file
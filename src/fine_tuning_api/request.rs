@@ -0,0 +1,121 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Default)]
+pub struct JobRequestBuilder {
+    /// The name of the model to fine-tune.
+    pub model: Option<String>,
+    /// The ID of an uploaded file that contains training data.
+    pub training_file: Option<String>,
+    /// The ID of an uploaded file that contains validation data.
+    pub validation_file: Option<String>,
+    /// The hyperparameters used for the fine-tuning job.
+    pub hyperparameters: Option<Hyperparameters>,
+    /// A string of up to 64 characters that will be added to your fine-tuned model name.
+    pub suffix: Option<String>,
+    /// The seed controls the reproducibility of the job.
+    pub seed: Option<isize>,
+}
+
+impl JobRequestBuilder {
+    /// The name of the model to fine-tune.
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+    /// The ID of an uploaded file that contains training data.
+    pub fn with_training_file(mut self, training_file: impl Into<String>) -> Self {
+        self.training_file = Some(training_file.into());
+        self
+    }
+    /// The ID of an uploaded file that contains validation data.
+    pub fn with_validation_file(mut self, validation_file: impl Into<String>) -> Self {
+        self.validation_file = Some(validation_file.into());
+        self
+    }
+    /// The hyperparameters used for the fine-tuning job.
+    pub fn with_hyperparameters(mut self, hyperparameters: Hyperparameters) -> Self {
+        self.hyperparameters = Some(hyperparameters);
+        self
+    }
+    /// A string of up to 64 characters that will be added to your fine-tuned model name.
+    pub fn with_suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.suffix = Some(suffix.into());
+        self
+    }
+    /// The seed controls the reproducibility of the job.
+    pub fn with_seed(mut self, seed: isize) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+    pub fn build(self) -> Option<JobRequest> {
+        Some(JobRequest {
+            model: self.model?,
+            training_file: self.training_file?,
+            validation_file: self.validation_file,
+            hyperparameters: self.hyperparameters,
+            suffix: self.suffix,
+            seed: self.seed,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JobRequest {
+    pub model: String,
+    pub training_file: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validation_file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hyperparameters: Option<Hyperparameters>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suffix: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<isize>,
+}
+
+/// Unset values are chosen automatically by the API.
+#[derive(Debug, Clone, Default, Serialize, serde::Deserialize)]
+pub struct Hyperparameters {
+    /// The number of epochs to train the model for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n_epochs: Option<Auto<isize>>,
+    /// Number of examples in each batch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batch_size: Option<Auto<isize>>,
+    /// Scaling factor for the learning rate.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub learning_rate_multiplier: Option<Auto<f32>>,
+}
+
+impl Hyperparameters {
+    pub fn with_n_epochs(mut self, n_epochs: isize) -> Self {
+        self.n_epochs = Some(Auto::Value(n_epochs));
+        self
+    }
+    pub fn with_batch_size(mut self, batch_size: isize) -> Self {
+        self.batch_size = Some(Auto::Value(batch_size));
+        self
+    }
+    pub fn with_learning_rate_multiplier(mut self, learning_rate_multiplier: f32) -> Self {
+        self.learning_rate_multiplier = Some(Auto::Value(learning_rate_multiplier));
+        self
+    }
+}
+
+/// Either `"auto"` or a value.
+#[derive(Debug, Clone, Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum Auto<T> {
+    Value(T),
+    Auto(internal::AutoLiteral),
+}
+
+pub mod internal {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    pub enum AutoLiteral {
+        Auto,
+    }
+}
@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+
+/// The `fine_tuning.job` object.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: String,
+    pub object: String,
+    pub created_at: isize,
+    /// For jobs that have failed, contains more information on the cause of the failure.
+    pub error: Option<JobError>,
+    /// The name of the fine-tuned model that is being created. `None` if the job is still running.
+    pub fine_tuned_model: Option<String>,
+    pub finished_at: Option<isize>,
+    pub hyperparameters: Option<super::request::Hyperparameters>,
+    /// The base model that is being fine-tuned.
+    pub model: String,
+    pub organization_id: Option<String>,
+    /// The compiled results file ID(s) for the fine-tuning job.
+    #[serde(default)]
+    pub result_files: Vec<String>,
+    pub status: JobStatus,
+    /// The total number of billable tokens processed by this job. `None` if the job is still running.
+    pub trained_tokens: Option<isize>,
+    pub training_file: String,
+    pub validation_file: Option<String>,
+    pub seed: Option<isize>,
+    pub estimated_finish: Option<isize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    ValidatingFiles,
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    /// Whether the job will no longer change.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, JobStatus::Succeeded | JobStatus::Failed | JobStatus::Cancelled)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobError {
+    pub code: Option<String>,
+    pub message: Option<String>,
+    pub param: Option<String>,
+}
+
+/// A `fine_tuning.job.event` object.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobEvent {
+    pub id: String,
+    pub object: String,
+    pub created_at: isize,
+    /// One of `info`, `warn` or `error`.
+    pub level: String,
+    pub message: String,
+    /// One of `message` or `metrics`.
+    #[serde(default)]
+    pub r#type: Option<String>,
+    /// Metrics (e.g. `step`, `train_loss`) for `metrics` events.
+    #[serde(default)]
+    pub data: Option<serde_json::Value>,
+}
+
+/// A page of [`JobEvent`]s, newest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobEventList {
+    pub object: String,
+    pub data: Vec<JobEvent>,
+    #[serde(default)]
+    pub has_more: bool,
+}
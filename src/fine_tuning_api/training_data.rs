@@ -0,0 +1,187 @@
+//! Conversations serialized into the chat fine-tuning JSONL format.
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::text_api::request::Message;
+use crate::text_api::tokens::TokenCounter;
+
+#[derive(Debug, Clone, Default)]
+pub struct TrainingDataBuilder {
+    pub conversations: Vec<Vec<Message>>,
+    /// The most tokens a single example may have. Defaults to `65536`.
+    pub max_tokens_per_example: Option<usize>,
+    /// The model to be fine-tuned, whose tokenizer is used to count tokens.
+    /// Defaults to `cl100k_base`.
+    pub model: Option<String>,
+}
+
+impl TrainingDataBuilder {
+    pub fn with_conversation(mut self, messages: Vec<Message>) -> Self {
+        self.conversations.push(messages);
+        self
+    }
+    pub fn with_conversations(mut self, conversations: impl IntoIterator<Item = Vec<Message>>) -> Self {
+        self.conversations.extend(conversations);
+        self
+    }
+    /// The most tokens a single example may have. Defaults to `65536`.
+    pub fn with_max_tokens_per_example(mut self, max_tokens_per_example: usize) -> Self {
+        self.max_tokens_per_example = Some(max_tokens_per_example);
+        self
+    }
+    /// The model to be fine-tuned, whose tokenizer is used to count tokens.
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+    /// Checks every conversation, reporting all problems at once.
+    ///
+    /// Each conversation must be non-empty, only use the `system`, `user` and
+    /// `assistant` roles, contain at least one assistant message, have no
+    /// empty messages, and fit in `max_tokens_per_example`.
+    pub fn build(self) -> Result<TrainingData, InvalidTrainingData> {
+        let max_tokens = self.max_tokens_per_example.unwrap_or(65536);
+        let counter = self.model
+            .as_deref()
+            .map(TokenCounter::for_model)
+            .unwrap_or_default();
+        let mut issues = Vec::new();
+        if self.conversations.is_empty() {
+            issues.push(Issue { example: 0, message: None, kind: IssueKind::NoExamples });
+        }
+        for (example, messages) in self.conversations.iter().enumerate() {
+            if messages.is_empty() {
+                issues.push(Issue { example, message: None, kind: IssueKind::EmptyConversation });
+                continue
+            }
+            for (index, message) in messages.iter().enumerate() {
+                let role = message.role();
+                if !matches!(role, "system" | "user" | "assistant") {
+                    issues.push(Issue { example, message: Some(index), kind: IssueKind::UnsupportedRole(role) });
+                }
+                if message.content().trim().is_empty() {
+                    issues.push(Issue { example, message: Some(index), kind: IssueKind::EmptyContent });
+                }
+            }
+            if !messages.iter().any(|x| matches!(x, Message::Assistant { .. })) {
+                issues.push(Issue { example, message: None, kind: IssueKind::MissingAssistantMessage });
+            }
+            let tokens = counter.count_messages(messages);
+            if tokens > max_tokens {
+                issues.push(Issue { example, message: None, kind: IssueKind::TooManyTokens { max: max_tokens, given: tokens } });
+            }
+        }
+        if !issues.is_empty() {
+            return Err(InvalidTrainingData { issues })
+        }
+        let examples = self.conversations
+            .into_iter()
+            .map(|messages| Example { messages })
+            .collect();
+        Ok(TrainingData { examples })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TrainingData {
+    pub examples: Vec<Example>,
+}
+
+/// A line of the training file.
+#[derive(Debug, Clone, Serialize)]
+pub struct Example {
+    pub messages: Vec<Message>,
+}
+
+impl TrainingData {
+    pub fn to_jsonl(&self) -> String {
+        self.examples
+            .iter()
+            .map(|x| serde_json::to_string(x).unwrap())
+            .map(|x| format!("{x}\n"))
+            .collect()
+    }
+    pub fn save(&self, file_path: impl AsRef<std::path::Path>) -> Result<(), std::io::Error> {
+        let file_path = file_path.as_ref();
+        if let Some(parent) = file_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(file_path, self.to_jsonl())
+    }
+    /// Uploads the training data with the `fine-tune` purpose.
+    pub async fn upload(
+        &self,
+        file_name: impl Into<String>,
+        client_configuration: &crate::files_api::client::ClientConfiguration,
+    ) -> Result<crate::files_api::response::File, Box<dyn std::error::Error>> {
+        crate::files_api::request::UploadRequestBuilder::default()
+            .with_file_name(file_name)
+            .with_purpose(crate::files_api::request::Purpose::fine_tune())
+            .with_contents(self.to_jsonl())
+            .build()
+            .unwrap()
+            .execute(client_configuration)
+            .await
+    }
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// ERRORS
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+#[derive(Debug, Clone)]
+pub struct InvalidTrainingData {
+    pub issues: Vec<Issue>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Issue {
+    /// The index of the conversation.
+    pub example: usize,
+    /// The index of the message within the conversation, if the issue is with a single message.
+    pub message: Option<usize>,
+    pub kind: IssueKind,
+}
+
+#[derive(Debug, Clone)]
+pub enum IssueKind {
+    NoExamples,
+    EmptyConversation,
+    EmptyContent,
+    UnsupportedRole(&'static str),
+    MissingAssistantMessage,
+    TooManyTokens { max: usize, given: usize },
+}
+
+impl std::fmt::Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.message {
+            Some(message) => write!(f, "example {}, message {}: ", self.example, message)?,
+            None => write!(f, "example {}: ", self.example)?,
+        }
+        match &self.kind {
+            IssueKind::NoExamples => write!(f, "no examples given"),
+            IssueKind::EmptyConversation => write!(f, "conversation has no messages"),
+            IssueKind::EmptyContent => write!(f, "message has no content"),
+            IssueKind::UnsupportedRole(role) => write!(f, "role '{role}' is not supported"),
+            IssueKind::MissingAssistantMessage => write!(f, "conversation has no assistant message"),
+            IssueKind::TooManyTokens { max, given } => {
+                write!(f, "conversation has {given} tokens, the limit is {max}")
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for InvalidTrainingData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let issues = self.issues
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("; ");
+        let msg = format!("Error: Invalid Training Data! {issues}.");
+        let msg = msg.red();
+        write!(f, "{msg}")
+    }
+}
+
+impl std::error::Error for InvalidTrainingData {}
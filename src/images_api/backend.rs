@@ -0,0 +1,173 @@
+//! The HTTP APIs the images client can talk to.
+//!
+//! Every backend accepts the same [`super::request::Request`] and produces the
+//! same [`super::response::Response`]; only the wire format differs.
+use serde_json::json;
+
+use super::request::{Model, Request};
+use super::response::{Image, Response, B64};
+
+/// The API spoken by the server at [`super::client::ClientConfiguration::api_url`].
+///
+/// Requests are built for a backend (see [`super::request::RequestBuilder::build`]);
+/// OpenAI's per-model constraints are only applied to OpenAI. For other
+/// backends `model` is optional: the Stability engine id, or the
+/// Automatic1111 checkpoint. These backends always return base64 encoded
+/// images regardless of `response_format`.
+#[derive(Debug, Clone, Default)]
+pub enum Backend {
+    /// `POST /v1/images/generations`
+    #[default]
+    OpenAi,
+    /// Stability AI's REST API, `POST /v1/generation/{engine_id}/text-to-image`.
+    ///
+    /// The engine id is taken from the request's `model`, falling back to
+    /// [`Backend::STABILITY_DEFAULT_ENGINE`]. The request's `style` is sent as
+    /// the `style_preset` when it's one of [`Backend::STABILITY_STYLE_PRESETS`]
+    /// (e.g. `photographic`); others, like OpenAI's `vivid`, are left out.
+    Stability,
+    /// Automatic1111's `POST /sdapi/v1/txt2img`, also served by compatible
+    /// local servers (Forge, SD.Next and ComfyUI bridges).
+    ///
+    /// The request's `model` is sent as the `sd_model_checkpoint` override.
+    Automatic1111,
+}
+
+impl Backend {
+    pub const STABILITY_DEFAULT_ENGINE: &'static str = "stable-diffusion-xl-1024-v1-0";
+    pub const STABILITY_STYLE_PRESETS: &'static [&'static str] = &[
+        "3d-model", "analog-film", "anime", "cinematic", "comic-book", "digital-art", "enhance",
+        "fantasy-art", "isometric", "line-art", "low-poly", "modeling-compound", "neon-punk",
+        "origami", "photographic", "pixel-art", "tile-texture",
+    ];
+    /// Whether the backend can be used without an API key.
+    pub(crate) fn requires_api_key(&self) -> bool {
+        !matches!(self, Backend::Automatic1111)
+    }
+    /// The model the request is billed as, if any: OpenAI defaults to
+    /// `dall-e-2`, Stability to its default engine, and Automatic1111 runs
+    /// locally.
+    pub(crate) fn priced_model(&self, request: &Request) -> Option<String> {
+        match self {
+            Backend::OpenAi => Some(request.model.clone().unwrap_or_else(|| String::from(Model::DALL_E2))),
+            Backend::Stability => Some(request.model.clone().unwrap_or_else(|| String::from(Self::STABILITY_DEFAULT_ENGINE))),
+            Backend::Automatic1111 => None,
+        }
+    }
+    /// The variant the model's images are priced by: the size, and for
+    /// `dall-e-3` the quality too, e.g. `hd 1024x1792`.
+    pub(crate) fn priced_variant(&self, request: &Request) -> Option<String> {
+        let Backend::OpenAi = self else { return None };
+        let size = request.size.as_deref().unwrap_or("1024x1024");
+        match request.model.as_deref() {
+            Some(Model::DALL_E3) => {
+                let quality = request.quality.as_deref().unwrap_or("standard");
+                Some(format!("{quality} {size}"))
+            }
+            _ => Some(size.to_string()),
+        }
+    }
+    pub(crate) fn endpoint(&self, api_url: &str, request: &Request) -> String {
+        match self {
+            Backend::OpenAi | Backend::Automatic1111 => api_url.to_string(),
+            Backend::Stability => {
+                let engine = request.model.as_deref().unwrap_or(Self::STABILITY_DEFAULT_ENGINE);
+                format!("{}/{engine}/text-to-image", api_url.trim_end_matches('/'))
+            }
+        }
+    }
+    /// The JSON body sent for `request`.
+    pub fn encode(&self, request: &Request) -> serde_json::Value {
+        match self {
+            Backend::OpenAi => serde_json::to_value(request).unwrap(),
+            Backend::Stability => {
+                let (width, height) = dimensions(request);
+                let mut body = json!({
+                    "text_prompts": [{ "text": request.prompt, "weight": 1.0 }],
+                    "width": width,
+                    "height": height,
+                    "samples": request.n.unwrap_or(1),
+                });
+                let preset = request.style
+                    .as_deref()
+                    .filter(|x| Self::STABILITY_STYLE_PRESETS.contains(x));
+                if let Some(style) = preset {
+                    body["style_preset"] = json!(style);
+                }
+                body
+            }
+            Backend::Automatic1111 => {
+                let (width, height) = dimensions(request);
+                let mut body = json!({
+                    "prompt": request.prompt,
+                    "width": width,
+                    "height": height,
+                    "batch_size": request.n.unwrap_or(1),
+                    "n_iter": 1,
+                });
+                if let Some(model) = request.model.as_ref() {
+                    body["override_settings"] = json!({ "sd_model_checkpoint": model });
+                }
+                body
+            }
+        }
+    }
+    pub(crate) fn decode(&self, body: &str) -> Result<Response, serde_json::Error> {
+        match self {
+            Backend::OpenAi => serde_json::from_str::<Response>(body),
+            Backend::Stability => {
+                let body = serde_json::from_str::<internal::StabilityResponse>(body)?;
+                let data = body.artifacts
+                    .into_iter()
+                    .map(|x| b64_image(x.base64))
+                    .collect::<Vec<_>>();
+                Ok(Response { created: now(), data })
+            }
+            Backend::Automatic1111 => {
+                let body = serde_json::from_str::<internal::Automatic1111Response>(body)?;
+                let data = body.images
+                    .into_iter()
+                    .map(b64_image)
+                    .collect::<Vec<_>>();
+                Ok(Response { created: now(), data })
+            }
+        }
+    }
+}
+
+/// Parses the `WIDTHxHEIGHT` size, defaulting to `1024x1024`.
+fn dimensions(request: &Request) -> (u32, u32) {
+    request.size
+        .as_deref()
+        .and_then(|size| size.split_once('x'))
+        .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+        .unwrap_or((1024, 1024))
+}
+
+fn b64_image(base64: String) -> Image {
+    Image { b64_json: Some(B64(base64)), url: None, revised_prompt: None }
+}
+
+fn now() -> isize {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|x| x.as_secs() as isize)
+        .unwrap_or(0)
+}
+
+mod internal {
+    #[derive(Debug, Clone, serde::Deserialize)]
+    pub struct StabilityResponse {
+        pub artifacts: Vec<StabilityArtifact>,
+    }
+
+    #[derive(Debug, Clone, serde::Deserialize)]
+    pub struct StabilityArtifact {
+        pub base64: String,
+    }
+
+    #[derive(Debug, Clone, serde::Deserialize)]
+    pub struct Automatic1111Response {
+        pub images: Vec<String>,
+    }
+}
@@ -1,10 +1,18 @@
-pub use crate::text_api::client::ApiError;
+use futures::StreamExt;
+
+pub use crate::text_api::client::{ApiError, RetryPolicy};
+pub use super::backend::Backend;
+use crate::cost::CostAccountant;
 
 #[derive(Default)]
 pub struct ClientConfigurationBuilder {
     pub api_url: Option<URL>,
     pub api_key: Option<ApiKey>,
     pub timeout: Option<Timeout>,
+    pub retry_policy: Option<RetryPolicy>,
+    pub max_concurrency: Option<usize>,
+    pub backend: Option<Backend>,
+    pub cost_accountant: Option<CostAccountant>,
 }
 
 impl ClientConfigurationBuilder {
@@ -20,11 +28,42 @@ impl ClientConfigurationBuilder {
         self.timeout = Some(timeout.into());
         self
     }
+    /// Applied to every HTTP call, including each call of a fanned-out request.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+    /// The maximum number of calls in flight when a request is fanned out. Defaults to `4`.
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = Some(max_concurrency);
+        self
+    }
+    /// The API spoken by the server at `api_url`. Defaults to OpenAI.
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+    /// Records the cost of each request by image, and refuses to send once the budget is spent.
+    pub fn with_cost_accountant(mut self, cost_accountant: CostAccountant) -> Self {
+        self.cost_accountant = Some(cost_accountant);
+        self
+    }
+    /// The API key may only be omitted for backends that don’t need one (i.e. a local Automatic1111 server).
     pub fn build(self) -> Option<ClientConfiguration> {
+        let backend = self.backend.unwrap_or_default();
+        let api_key = match self.api_key {
+            Some(api_key) => api_key,
+            None if !backend.requires_api_key() => ApiKey(String::new()),
+            None => return None,
+        };
         Some(ClientConfiguration {
             api_url: self.api_url?,
-            api_key: self.api_key?,
-            timeout: self.timeout
+            api_key,
+            timeout: self.timeout,
+            retry_policy: self.retry_policy.unwrap_or_default(),
+            max_concurrency: self.max_concurrency.unwrap_or(4).max(1),
+            backend,
+            cost_accountant: self.cost_accountant,
         })
     }
 }
@@ -33,6 +72,10 @@ pub struct ClientConfiguration {
     pub api_url: URL,
     pub api_key: ApiKey,
     pub timeout: Option<Timeout>,
+    pub retry_policy: RetryPolicy,
+    pub max_concurrency: usize,
+    pub backend: Backend,
+    pub cost_accountant: Option<CostAccountant>,
 }
 
 pub struct URL(pub String);
@@ -42,6 +85,14 @@ impl URL {
     pub fn openai_v1_images_generations() -> Self {
         Self(String::from("https://api.openai.com/v1/images/generations"))
     }
+    /// Stability AI’s generation endpoints; use with [`Backend::Stability`].
+    pub fn stability_v1_generation() -> Self {
+        Self(String::from("https://api.stability.ai/v1/generation"))
+    }
+    /// A local Automatic1111 (or compatible) server on its default port; use with [`Backend::Automatic1111`].
+    pub fn local_automatic1111_txt2img() -> Self {
+        Self(String::from("http://127.0.0.1:7860/sdapi/v1/txt2img"))
+    }
 }
 
 pub struct ApiKey(pub String);
@@ -65,36 +116,89 @@ impl From<std::time::Duration> for Timeout {
 }
 
 impl super::request::Request {
+    /// Requests for more images than the model accepts per call (e.g. `n > 1`
+    /// for `dall-e-3`) are split into parallel calls, at most
+    /// `max_concurrency` at a time, and merged back into a single response.
     pub async fn execute(
         self,
         client_configuration: &ClientConfiguration
     ) -> Result<super::response::Response, Box<dyn std::error::Error>> {
-        let api_url = client_configuration.api_url.0.as_str();
-        let api_key = client_configuration.api_key.0.as_str();
         let client = {
             if let Some(timeout) = client_configuration.timeout.as_ref() {
                 reqwest::ClientBuilder::new()
-                    .timeout(timeout.0.clone())
+                    .timeout(timeout.0)
                     .build()
                     .unwrap()
             } else {
                 reqwest::ClientBuilder::new().build().unwrap()
             }
         };
-        let json_data = serde_json::to_string(&self).unwrap();
-        let response = client
-            .post(api_url)
-            .header("Authorization", format!("Bearer {}", api_key))
-            .header("Content-Type", "application/json")
-            // .json(&self.client.request_body)
-            .body(json_data)
-            .send()
-            .await?;
-        if let Some(error) = ApiError::from_code(response.status().as_u16()) {
-            return Err(Box::new(error))
+        if let Some(cost_accountant) = client_configuration.cost_accountant.as_ref() {
+            cost_accountant.check_budget()?;
+        }
+        let model = client_configuration.backend.priced_model(&self);
+        let variant = client_configuration.backend.priced_variant(&self);
+        let requests = self.split(&client_configuration.backend);
+        let results = futures::stream::iter(requests.iter())
+            .map(|request| execute_with_retry(&client, client_configuration, request))
+            .buffered(client_configuration.max_concurrency)
+            .collect::<Vec<_>>()
+            .await;
+        // Every call that succeeded was billed, even if another one failed.
+        if let Some(cost_accountant) = client_configuration.cost_accountant.as_ref() {
+            let model = model.filter(|x| cost_accountant.has_price(x));
+            for response in results.iter().flatten() {
+                let units = response.data.len() as f64;
+                match (model.as_ref(), variant.as_deref()) {
+                    (Some(model), Some(variant)) => {
+                        cost_accountant.record_variant_units(model, variant, units);
+                    }
+                    (Some(model), None) => {
+                        cost_accountant.record_units(model, units);
+                    }
+                    (None, _) => {}
+                }
+            }
         }
-        let result = response.text().await?;
-        let result = serde_json::from_str::<super::response::Response>(&result)?;
-        Ok(result)
+        let responses = results.into_iter().collect::<Result<Vec<_>, _>>()?;
+        Ok(super::response::Response::merge(responses))
+    }
+}
+
+async fn execute_with_retry(
+    client: &reqwest::Client,
+    client_configuration: &ClientConfiguration,
+    request: &super::request::Request,
+) -> Result<super::response::Response, Box<dyn std::error::Error>> {
+    client_configuration.retry_policy
+        .run(|| execute_once(client, client_configuration, request))
+        .await
+}
+
+async fn execute_once(
+    client: &reqwest::Client,
+    client_configuration: &ClientConfiguration,
+    request: &super::request::Request,
+) -> Result<super::response::Response, Box<dyn std::error::Error>> {
+    let backend = &client_configuration.backend;
+    let api_url = backend.endpoint(&client_configuration.api_url.0, request);
+    let api_key = client_configuration.api_key.0.as_str();
+    let json_data = serde_json::to_string(&backend.encode(request)).unwrap();
+    let mut http_request = client
+        .post(api_url)
+        .header("Content-Type", "application/json")
+        .header("Accept", "application/json");
+    if !api_key.is_empty() {
+        http_request = http_request.header("Authorization", format!("Bearer {}", api_key));
     }
-}
\ No newline at end of file
+    let response = http_request
+        .body(json_data)
+        .send()
+        .await?;
+    if let Some(error) = ApiError::from_code(response.status().as_u16()) {
+        return Err(Box::new(error))
+    }
+    let result = response.text().await?;
+    let result = backend.decode(&result)?;
+    Ok(result)
+}
@@ -1,3 +1,4 @@
 pub mod request;
 pub mod response;
-pub mod client;
\ No newline at end of file
+pub mod client;
+pub mod backend;
\ No newline at end of file
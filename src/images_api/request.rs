@@ -1,12 +1,15 @@
+use colored::Colorize;
 use serde::Serialize;
 
+use super::backend::Backend;
+
 #[derive(Debug, Clone, Default)]
 pub struct RequestBuilder {
     /// A text description of the desired image(s). The maximum length is 1000 characters for `dall-e-2` and 4000 characters for `dall-e-3`.
     pub prompt: Option<String>,
     /// The model to use for image generation.
     pub model: Option<Model>,
-    /// The number of images to generate. Must be between 1 and 10. For `dall-e-3`, only `n=1` is supported.
+    /// The number of images to generate. Must be between 1 and 10. For `dall-e-3`, which only supports `n=1`, the client splits the request into `n` parallel calls.
     pub n: Option<i32>,
     /// The quality of the image that will be generated.
     ///
@@ -22,6 +25,10 @@ pub struct RequestBuilder {
     /// * Must be one of `256x256`, `512x512`, or `1024x1024` for `dall-e-2`.
     /// * Must be one of `1024x1024`, `1792x1024`, or `1024x1792` for `dall-e-3` models.
     pub size: Option<Size>,
+    /// The style of the generated images.
+    ///
+    /// Must be one of `vivid` or `natural`. Vivid causes the model to lean towards generating hyper-real and dramatic images. Natural causes the model to produce more natural, less hyper-real looking images. This param is only supported for `dall-e-3`.
+    pub style: Option<Style>,
     /// A unique identifier representing your end-user, which can help OpenAI to monitor and detect abuse.
     pub user: Option<String>,
 }
@@ -37,7 +44,7 @@ impl RequestBuilder {
         self.model = Some(model.into());
         self
     }
-    /// The number of images to generate. Must be between 1 and 10. For `dall-e-3`, only `n=1` is supported.
+    /// The number of images to generate. Must be between 1 and 10. For `dall-e-3`, which only supports `n=1`, the client splits the request into `n` parallel calls.
     pub fn with_n(mut self, n: i32) -> Self {
         self.n = Some(n);
         self
@@ -65,19 +72,51 @@ impl RequestBuilder {
         self.size = Some(size.into());
         self
     }
+    /// The style of the generated images.
+    ///
+    /// Must be one of `vivid` or `natural`. Vivid causes the model to lean towards generating hyper-real and dramatic images. Natural causes the model to produce more natural, less hyper-real looking images. This param is only supported for `dall-e-3`.
+    pub fn with_style(mut self, style: impl Into<Style>) -> Self {
+        self.style = Some(style.into());
+        self
+    }
     /// A unique identifier representing your end-user, which can help OpenAI to monitor and detect abuse.
     pub fn with_user(mut self, user: impl Into<String>) -> Self {
         self.user = Some(user.into());
         self
     }
-    pub fn build(self) -> Option<Request> {
-        Some(Request {
-            prompt: self.prompt?,
+    /// Validates the request for the backend it will be sent to. For OpenAI,
+    /// that’s the constraints of the chosen model (`dall-e-2` when unset).
+    ///
+    /// Other OpenAI models, and every other backend, are passed through unchecked.
+    pub fn build(self, backend: &Backend) -> Result<Request, InvalidRequest> {
+        let prompt = self.prompt.ok_or(InvalidRequest::MissingPrompt)?;
+        let constraints = match backend {
+            Backend::OpenAi => {
+                let model_name = self.model
+                    .as_ref()
+                    .map(|x| x.0.as_str())
+                    .unwrap_or(Model::DALL_E2);
+                ModelConstraints::lookup(model_name)
+            }
+            Backend::Stability | Backend::Automatic1111 => None,
+        };
+        if let Some(constraints) = constraints {
+            constraints.validate(
+                &prompt,
+                self.n,
+                self.quality.as_ref(),
+                self.size.as_ref(),
+                self.style.as_ref(),
+            )?;
+        }
+        Ok(Request {
+            prompt,
             model: self.model.map(|x| x.0),
             n: self.n,
             quality: self.quality.map(|x| x.0),
             response_format: self.response_format.map(|x| x.0),
             size: self.size.map(|x| x.0),
+            style: self.style.map(|x| x.0),
             user: self.user,
         })
     }
@@ -90,7 +129,7 @@ pub struct Request {
     /// The model to use for image generation.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub model: Option<String>,
-    /// The number of images to generate. Must be between 1 and 10. For `dall-e-3`, only `n=1` is supported.
+    /// The number of images to generate. Must be between 1 and 10. For `dall-e-3`, which only supports `n=1`, the client splits the request into `n` parallel calls.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub n: Option<i32>,
     /// The quality of the image that will be generated.
@@ -110,21 +149,53 @@ pub struct Request {
     /// * Must be one of `1024x1024`, `1792x1024`, or `1024x1792` for `dall-e-3` models.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub size: Option<String>,
+    /// The style of the generated images.
+    ///
+    /// Must be one of `vivid` or `natural`. This param is only supported for `dall-e-3`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
     /// A unique identifier representing your end-user, which can help OpenAI to monitor and detect abuse.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user: Option<String>,
 }
 
+impl Request {
+    /// Splits the request into calls the model accepts, each asking for at
+    /// most as many images as the model generates per call. Only OpenAI
+    /// models are split.
+    pub fn split(self, backend: &Backend) -> Vec<Request> {
+        let model = self.model.as_deref().unwrap_or(Model::DALL_E2);
+        let max_n_per_call = match backend {
+            Backend::OpenAi => ModelConstraints::lookup(model).map(|x| x.max_n_per_call),
+            Backend::Stability | Backend::Automatic1111 => None,
+        };
+        let max_n_per_call = max_n_per_call.unwrap_or(i32::MAX);
+        let mut remaining = self.n.unwrap_or(1);
+        if remaining <= max_n_per_call {
+            return vec![self]
+        }
+        let mut requests = Vec::new();
+        while remaining > 0 {
+            let n = remaining.min(max_n_per_call);
+            requests.push(Request { n: Some(n), ..self.clone() });
+            remaining -= n;
+        }
+        requests
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(transparent)]
 pub struct Model(pub String);
 
 impl Model {
+    pub(crate) const DALL_E2: &'static str = "dall-e-2";
+    pub(crate) const DALL_E3: &'static str = "dall-e-3";
     pub fn dall_e2() -> Self {
-        Self(String::from("dall-e-2"))
+        Self(String::from(Self::DALL_E2))
     }
     pub fn dall_e3() -> Self {
-        Self(String::from("dall-e-3"))
+        Self(String::from(Self::DALL_E3))
     }
 }
 
@@ -190,6 +261,22 @@ impl Size {
     }
 }
 
+/// The style of the generated images.
+///
+/// Must be one of `vivid` or `natural`. This param is only supported for `dall-e-3`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(transparent)]
+pub struct Style(pub String);
+
+impl Style {
+    pub fn vivid() -> Self {
+        Self(String::from("vivid"))
+    }
+    pub fn natural() -> Self {
+        Self(String::from("natural"))
+    }
+}
+
 impl From<String> for Model {
     fn from(value: String) -> Self { Self(value) }
 }
@@ -202,6 +289,9 @@ impl From<String> for ResponseFormat {
 impl From<String> for Size {
     fn from(value: String) -> Self { Self(value) }
 }
+impl From<String> for Style {
+    fn from(value: String) -> Self { Self(value) }
+}
 
 impl From<&str> for Model {
     fn from(value: &str) -> Self { Self(value.to_string()) }
@@ -214,4 +304,133 @@ impl From<&str> for ResponseFormat {
 }
 impl From<&str> for Size {
     fn from(value: &str) -> Self { Self(value.to_string()) }
-}
\ No newline at end of file
+}
+impl From<&str> for Style {
+    fn from(value: &str) -> Self { Self(value.to_string()) }
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// VALIDATION
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+/// What a given OpenAI image model accepts.
+#[derive(Debug, Clone, Copy)]
+struct ModelConstraints {
+    model: &'static str,
+    max_prompt_length: usize,
+    max_n: i32,
+    max_n_per_call: i32,
+    sizes: &'static [&'static str],
+    qualities: &'static [&'static str],
+    styles: &'static [&'static str],
+}
+
+impl ModelConstraints {
+    const DALL_E2: Self = ModelConstraints {
+        model: Model::DALL_E2,
+        max_prompt_length: 1000,
+        max_n: 10,
+        max_n_per_call: 10,
+        sizes: &["256x256", "512x512", "1024x1024"],
+        qualities: &["standard"],
+        styles: &[],
+    };
+    const DALL_E3: Self = ModelConstraints {
+        model: Model::DALL_E3,
+        max_prompt_length: 4000,
+        max_n: 10,
+        max_n_per_call: 1,
+        sizes: &["1024x1024", "1792x1024", "1024x1792"],
+        qualities: &["standard", "hd"],
+        styles: &["vivid", "natural"],
+    };
+    fn lookup(model: &str) -> Option<Self> {
+        match model {
+            Model::DALL_E2 => Some(Self::DALL_E2),
+            Model::DALL_E3 => Some(Self::DALL_E3),
+            _ => None,
+        }
+    }
+    fn validate(
+        &self,
+        prompt: &str,
+        n: Option<i32>,
+        quality: Option<&Quality>,
+        size: Option<&Size>,
+        style: Option<&Style>,
+    ) -> Result<(), InvalidRequest> {
+        let model = self.model.to_string();
+        let prompt_length = prompt.chars().count();
+        if prompt_length > self.max_prompt_length {
+            return Err(InvalidRequest::PromptTooLong { model, max: self.max_prompt_length, given: prompt_length })
+        }
+        if let Some(n) = n {
+            if n < 1 || n > self.max_n {
+                return Err(InvalidRequest::UnsupportedN { model, max: self.max_n, given: n })
+            }
+        }
+        if let Some(Size(size)) = size {
+            if !self.sizes.contains(&size.as_str()) {
+                return Err(InvalidRequest::UnsupportedSize { model, supported: self.sizes, given: size.clone() })
+            }
+        }
+        if let Some(Quality(quality)) = quality {
+            if !self.qualities.contains(&quality.as_str()) {
+                return Err(InvalidRequest::UnsupportedQuality { model, supported: self.qualities, given: quality.clone() })
+            }
+        }
+        if let Some(Style(style)) = style {
+            if !self.styles.contains(&style.as_str()) {
+                return Err(InvalidRequest::UnsupportedStyle { model, supported: self.styles, given: style.clone() })
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum InvalidRequest {
+    MissingPrompt,
+    PromptTooLong { model: String, max: usize, given: usize },
+    UnsupportedN { model: String, max: i32, given: i32 },
+    UnsupportedSize { model: String, supported: &'static [&'static str], given: String },
+    UnsupportedQuality { model: String, supported: &'static [&'static str], given: String },
+    UnsupportedStyle { model: String, supported: &'static [&'static str], given: String },
+}
+
+impl std::fmt::Display for InvalidRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn one_of(supported: &[&str]) -> String {
+            if supported.is_empty() {
+                return String::from("this parameter is not supported")
+            }
+            format!("must be one of {}", supported.join(", "))
+        }
+        let msg = match self {
+            InvalidRequest::MissingPrompt => {
+                String::from("Error: Invalid Request! A prompt is required.")
+            }
+            InvalidRequest::PromptTooLong { model, max, given } => format!(
+                "Error: Invalid Request! The prompt for '{model}' is limited to {max} characters, given {given}."
+            ),
+            InvalidRequest::UnsupportedN { model, max, given } => format!(
+                "Error: Invalid Request! 'n' for '{model}' must be between 1 and {max}, given {given}."
+            ),
+            InvalidRequest::UnsupportedSize { model, supported, given } => format!(
+                "Error: Invalid Request! Size '{given}' is not supported by '{model}' ({}).",
+                one_of(supported)
+            ),
+            InvalidRequest::UnsupportedQuality { model, supported, given } => format!(
+                "Error: Invalid Request! Quality '{given}' is not supported by '{model}' ({}).",
+                one_of(supported)
+            ),
+            InvalidRequest::UnsupportedStyle { model, supported, given } => format!(
+                "Error: Invalid Request! Style '{given}' is not supported by '{model}' ({}).",
+                one_of(supported)
+            ),
+        };
+        let msg = msg.red();
+        write!(f, "{msg}")
+    }
+}
+
+impl std::error::Error for InvalidRequest {}
\ No newline at end of file
@@ -4,6 +4,18 @@ pub struct Response {
     pub data: Vec<Image>,
 }
 
+impl Response {
+    /// Concatenates the images of several responses, e.g. the parts of a fanned-out request.
+    pub fn merge(responses: impl IntoIterator<Item = Response>) -> Self {
+        let mut merged = Response { created: 0, data: Vec::new() };
+        for mut response in responses {
+            merged.created = merged.created.max(response.created);
+            merged.data.append(&mut response.data);
+        }
+        merged
+    }
+}
+
 #[derive(Debug, Clone, serde::Deserialize)]
 pub struct Image {
     pub b64_json: Option<B64>,
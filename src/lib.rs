@@ -1,3 +1,10 @@
 pub mod text_api;
 pub mod images_api;
-pub mod audio_api;
\ No newline at end of file
+pub mod audio_api;
+pub mod embeddings_api;
+pub mod moderation_api;
+pub mod files_api;
+pub mod batch_api;
+pub mod fine_tuning_api;
+pub mod models_api;
+pub mod cost;
\ No newline at end of file
@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use colored::Colorize;
+
+use super::response::{Model, ModelList};
+use crate::text_api;
+
+pub use crate::text_api::client::{ApiError, RetryPolicy};
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// CONFIGURATION
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+#[derive(Default)]
+pub struct ClientConfigurationBuilder {
+    pub providers: Vec<Provider>,
+    pub timeout: Option<Timeout>,
+    pub retry_policy: Option<RetryPolicy>,
+    pub cache_ttl: Option<Duration>,
+}
+
+impl ClientConfigurationBuilder {
+    /// Adds a provider whose model list endpoint is queried, e.g.
+    /// `with_provider("open-ai", URL::openai_v1_models(), api_key)`.
+    pub fn with_provider(
+        mut self,
+        name: impl Into<String>,
+        api_url: impl Into<URL>,
+        api_key: impl Into<ApiKey>,
+    ) -> Self {
+        self.providers.push(Provider { name: name.into(), api_url: api_url.into(), api_key: api_key.into() });
+        self
+    }
+    pub fn with_timeout(mut self, timeout: impl Into<Timeout>) -> Self {
+        self.timeout = Some(timeout.into());
+        self
+    }
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+    /// How long a provider’s model list is reused before it’s fetched again. Defaults to one hour.
+    pub fn with_cache_ttl(mut self, cache_ttl: Duration) -> Self {
+        self.cache_ttl = Some(cache_ttl);
+        self
+    }
+    pub fn build(self) -> Option<ModelCatalog> {
+        if self.providers.is_empty() {
+            return None
+        }
+        Some(ModelCatalog {
+            providers: self.providers,
+            timeout: self.timeout,
+            retry_policy: self.retry_policy.unwrap_or_default(),
+            cache_ttl: self.cache_ttl.unwrap_or(Duration::from_secs(60 * 60)),
+            cache: Mutex::new(HashMap::new()),
+        })
+    }
+}
+
+pub struct Provider {
+    pub name: String,
+    pub api_url: URL,
+    pub api_key: ApiKey,
+}
+
+pub struct URL(pub String);
+
+impl URL {
+    /// Lists the currently available models.
+    pub fn openai_v1_models() -> Self {
+        Self(String::from("https://api.openai.com/v1/models"))
+    }
+    pub fn octo_ai_v1_models() -> Self {
+        Self(String::from("https://text.octoai.run/v1/models"))
+    }
+    pub fn mistral_ai_v1_models() -> Self {
+        Self(String::from("https://api.mistral.ai/v1/models"))
+    }
+}
+
+pub struct ApiKey(pub String);
+
+pub struct Timeout(pub std::time::Duration);
+
+impl From<String> for URL {
+    fn from(value: String) -> Self { Self(value) }
+}
+impl From<String> for ApiKey {
+    fn from(value: String) -> Self { Self(value) }
+}
+impl From<&str> for URL {
+    fn from(value: &str) -> Self { Self(value.to_string()) }
+}
+impl From<&str> for ApiKey {
+    fn from(value: &str) -> Self { Self(value.to_string()) }
+}
+impl From<std::time::Duration> for Timeout {
+    fn from(value: std::time::Duration) -> Self { Self(value) }
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// MODEL CATALOG
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+/// The models offered by every configured provider, fetched on demand and cached.
+pub struct ModelCatalog {
+    providers: Vec<Provider>,
+    timeout: Option<Timeout>,
+    retry_policy: RetryPolicy,
+    cache_ttl: Duration,
+    cache: Mutex<HashMap<String, (Instant, Vec<Model>)>>,
+}
+
+impl ModelCatalog {
+    /// The models of the named provider, from the cache when fresh.
+    pub async fn list(&self, provider: impl AsRef<str>) -> Result<Vec<Model>, Box<dyn std::error::Error>> {
+        let provider = provider.as_ref();
+        if let Some((fetched_at, models)) = self.cache.lock().unwrap().get(provider) {
+            if fetched_at.elapsed() < self.cache_ttl {
+                return Ok(models.clone())
+            }
+        }
+        let provider = self.providers
+            .iter()
+            .find(|x| x.name == provider)
+            .ok_or_else(|| Box::new(UnknownProvider(provider.to_string())))?;
+        let models = self.fetch(provider).await?;
+        self.cache.lock().unwrap().insert(provider.name.clone(), (Instant::now(), models.clone()));
+        Ok(models)
+    }
+    /// The models of every provider, keyed by provider name.
+    pub async fn list_all(&self) -> Result<HashMap<String, Vec<Model>>, Box<dyn std::error::Error>> {
+        let mut result = HashMap::new();
+        for provider in self.providers.iter() {
+            result.insert(provider.name.clone(), self.list(&provider.name).await?);
+        }
+        Ok(result)
+    }
+    /// Drops every cached model list.
+    pub fn invalidate(&self) {
+        self.cache.lock().unwrap().clear();
+    }
+    /// The first provider (in configuration order) offering `model_id`, and its model.
+    ///
+    /// Providers that can’t be reached are skipped; it only fails, with the
+    /// first provider’s error, when none of them answers.
+    pub async fn find(&self, model_id: impl AsRef<str>) -> Result<Option<(String, Model)>, Box<dyn std::error::Error>> {
+        let model_id = model_id.as_ref();
+        let mut error = None;
+        let mut answered = false;
+        for provider in self.providers.iter() {
+            match self.list(&provider.name).await {
+                Ok(models) => {
+                    answered = true;
+                    if let Some(model) = models.into_iter().find(|x| x.id == model_id) {
+                        return Ok(Some((provider.name.clone(), model)))
+                    }
+                }
+                Err(x) => {
+                    error.get_or_insert(x);
+                }
+            }
+        }
+        match error {
+            Some(error) if !answered => Err(error),
+            _ => Ok(None),
+        }
+    }
+    /// Checks that the request names a model offered by one of the providers.
+    pub async fn validate_request(
+        &self,
+        request: &text_api::request::RequestBuilder,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.validate_model(None, request.model.as_deref()).await
+    }
+    /// Checks the prompt’s `model` attribute, so typos surface before the call is sent.
+    pub async fn validate_prompt(
+        &self,
+        prompt: &text_api::xml_dsl::Prompt,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.validate_model(prompt.name.clone(), prompt.request.model.as_deref()).await
+    }
+    async fn validate_model(
+        &self,
+        prompt: Option<String>,
+        model: Option<&str>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(model) = model else {
+            return Err(Box::new(InvalidModel::Missing { prompt }))
+        };
+        if self.find(model).await?.is_some() {
+            return Ok(())
+        }
+        // Providers that didn’t answer just don’t suggest anything.
+        let mut models = Vec::new();
+        for provider in self.providers.iter() {
+            models.extend(self.list(&provider.name).await.unwrap_or_default());
+        }
+        let mut suggestions = models
+            .into_iter()
+            .map(|x| x.id)
+            .filter(|x| x.contains(model) || model.contains(x.as_str()))
+            .collect::<Vec<_>>();
+        suggestions.sort();
+        suggestions.dedup();
+        suggestions.truncate(5);
+        Err(Box::new(InvalidModel::Unknown { prompt, model: model.to_string(), suggestions }))
+    }
+    async fn fetch(&self, provider: &Provider) -> Result<Vec<Model>, Box<dyn std::error::Error>> {
+        let client = {
+            if let Some(timeout) = self.timeout.as_ref() {
+                reqwest::ClientBuilder::new()
+                    .timeout(timeout.0)
+                    .build()
+                    .unwrap()
+            } else {
+                reqwest::ClientBuilder::new().build().unwrap()
+            }
+        };
+        let api_url = provider.api_url.0.as_str();
+        let api_key = provider.api_key.0.as_str();
+        self.retry_policy.run(|| async {
+            let response = client
+                .get(api_url)
+                .header("Authorization", format!("Bearer {}", api_key))
+                .send()
+                .await?;
+            if let Some(error) = ApiError::from_code(response.status().as_u16()) {
+                return Err(Box::new(error) as Box<dyn std::error::Error>)
+            }
+            let result = response.text().await?;
+            let result = serde_json::from_str::<ModelList>(&result)?;
+            Ok(result.data)
+        }).await
+    }
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// ERRORS
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+#[derive(Debug, Clone)]
+pub struct UnknownProvider(pub String);
+
+impl std::fmt::Display for UnknownProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let msg = format!("Error: Unknown Provider! {:?} isn’t configured.", self.0);
+        let msg = msg.red();
+        write!(f, "{msg}")
+    }
+}
+
+impl std::error::Error for UnknownProvider {}
+
+#[derive(Debug, Clone)]
+pub enum InvalidModel {
+    Missing { prompt: Option<String> },
+    Unknown { prompt: Option<String>, model: String, suggestions: Vec<String> },
+}
+
+impl std::fmt::Display for InvalidModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let msg = match self {
+            InvalidModel::Missing { prompt: Some(prompt) } => {
+                format!("Error: Invalid Model! Prompt {prompt:?} doesn’t specify a model.")
+            }
+            InvalidModel::Missing { prompt: None } => {
+                String::from("Error: Invalid Model! The request doesn’t specify a model.")
+            }
+            InvalidModel::Unknown { prompt, model, suggestions } => {
+                let mut msg = format!("Error: Invalid Model! No provider offers {model:?}");
+                if let Some(prompt) = prompt {
+                    msg.push_str(&format!(" (used by prompt {prompt:?})"));
+                }
+                msg.push('.');
+                if !suggestions.is_empty() {
+                    msg.push_str(&format!(" Did you mean one of: {}?", suggestions.join(", ")));
+                }
+                msg
+            }
+        };
+        let msg = msg.red();
+        write!(f, "{msg}")
+    }
+}
+
+impl std::error::Error for InvalidModel {}
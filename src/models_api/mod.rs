@@ -0,0 +1,3 @@
+pub mod response;
+pub mod client;
+pub mod registry;
\ No newline at end of file
@@ -0,0 +1,291 @@
+//! What each model can do and what it costs, from a table embedded in the
+//! crate that can be overridden or extended with a TOML or JSON file.
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+use crate::text_api::request::{OctoAiModels, OpenAiModels, RequestBuilder};
+
+const EMBEDDED: &str = include_str!("registry.json");
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// MODEL INFO
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelInfo {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// The most tokens, prompt and completion combined, the model can attend to.
+    /// Zero for models that aren’t priced by token.
+    #[serde(default)]
+    pub context_window: usize,
+    /// The most tokens the model will generate, when lower than the context window.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<usize>,
+    #[serde(default)]
+    pub tools: bool,
+    /// Whether the model accepts images. Chat messages here are text only,
+    /// so no request is checked against it.
+    #[serde(default)]
+    pub vision: bool,
+    #[serde(default)]
+    pub json_mode: bool,
+    #[serde(default)]
+    pub logprobs: bool,
+    /// USD per million prompt tokens.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_price: Option<f64>,
+    /// USD per million completion tokens.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_price: Option<f64>,
+    /// What image and audio models are billed by, e.g. `image` or `character`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    /// USD per `unit`, or the cheapest price when it varies (see `unit_prices`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit_price: Option<f64>,
+    /// USD per `unit` by variant, e.g. `hd 1024x1792` for an image's quality
+    /// and size, overriding `unit_price`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub unit_prices: HashMap<String, f64>,
+}
+
+impl ModelInfo {
+    pub fn supports(&self, feature: Feature) -> bool {
+        match feature {
+            Feature::Tools => self.tools,
+            Feature::JsonMode => self.json_mode,
+            Feature::Logprobs => self.logprobs,
+        }
+    }
+    /// The most tokens a completion can have.
+    pub fn max_output(&self) -> usize {
+        self.max_output_tokens.unwrap_or(self.context_window)
+    }
+    /// USD per `unit` of `variant`, falling back to `unit_price`.
+    pub fn unit_price_of(&self, variant: Option<&str>) -> Option<f64> {
+        variant
+            .and_then(|x| self.unit_prices.get(x).copied())
+            .or(self.unit_price)
+    }
+    /// USD per prompt token.
+    pub fn input_price_per_token(&self) -> Option<f64> {
+        self.input_price.map(|x| x / 1_000_000.0)
+    }
+    /// USD per completion token.
+    pub fn output_price_per_token(&self) -> Option<f64> {
+        self.output_price.map(|x| x / 1_000_000.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feature {
+    Tools,
+    JsonMode,
+    Logprobs,
+}
+
+impl std::fmt::Display for Feature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Feature::Tools => write!(f, "tools"),
+            Feature::JsonMode => write!(f, "JSON mode"),
+            Feature::Logprobs => write!(f, "logprobs"),
+        }
+    }
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// REGISTRY
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+/// Defaults to the embedded table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelRegistry {
+    models: Vec<ModelInfo>,
+}
+
+/// The layout of registry files: a `models` array in JSON, or `[[models]]`
+/// tables in TOML. Entries are kept as plain values so overrides can be partial.
+#[derive(Deserialize)]
+struct RegistryFile {
+    models: Vec<serde_json::Value>,
+}
+
+impl Default for ModelRegistry {
+    fn default() -> Self {
+        Self::embedded().clone()
+    }
+}
+
+impl ModelRegistry {
+    /// The table that ships with the crate.
+    pub fn embedded() -> &'static ModelRegistry {
+        static REGISTRY: OnceLock<ModelRegistry> = OnceLock::new();
+        REGISTRY.get_or_init(|| serde_json::from_str(EMBEDDED).unwrap())
+    }
+    /// Loads a registry from a `.toml` or `.json` file, without the embedded table.
+    pub fn open(file_path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let models = read_file(file_path.as_ref())?
+            .into_iter()
+            .map(serde_json::from_value)
+            .collect::<Result<Vec<ModelInfo>, _>>()?;
+        Ok(Self { models })
+    }
+    /// Applies a `.toml` or `.json` file on top of this registry. Entries whose
+    /// `id` is already known only replace the fields they set, e.g. just the
+    /// prices; other entries are added and must be complete.
+    pub fn with_overrides_from(mut self, file_path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        for entry in read_file(file_path.as_ref())? {
+            let id = entry.get("id").and_then(|x| x.as_str()).unwrap_or_default();
+            match self.models.iter_mut().find(|x| x.id == id) {
+                Some(existing) => {
+                    let mut merged = serde_json::to_value(&*existing)?;
+                    if let (Some(merged), Some(entry)) = (merged.as_object_mut(), entry.as_object()) {
+                        merged.extend(entry.clone());
+                    }
+                    *existing = serde_json::from_value(merged)?;
+                }
+                None => self.models.push(serde_json::from_value(entry)?),
+            }
+        }
+        Ok(self)
+    }
+    /// Adds the model, replacing any with the same `id`.
+    pub fn with_model(mut self, model: ModelInfo) -> Self {
+        self.models.retain(|x| x.id != model.id);
+        self.models.push(model);
+        self
+    }
+    pub fn models(&self) -> &[ModelInfo] {
+        &self.models
+    }
+    /// Looks up a model by id. Fine-tuned models (`ft:<base>:...`) resolve to
+    /// their base model.
+    pub fn get(&self, model: impl AsRef<str>) -> Option<&ModelInfo> {
+        let model = model.as_ref();
+        if let Some(model) = self.models.iter().find(|x| x.id == model) {
+            return Some(model)
+        }
+        let base = model.strip_prefix("ft:")?.split(':').next()?;
+        self.models.iter().find(|x| x.id == base)
+    }
+}
+
+fn read_file(file_path: &Path) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error>> {
+    let source = std::fs::read_to_string(file_path)?;
+    let file = match file_path.extension().and_then(|x| x.to_str()) {
+        Some("toml") => toml::from_str::<RegistryFile>(&source)?,
+        _ => serde_json::from_str::<RegistryFile>(&source)?,
+    };
+    Ok(file.models)
+}
+
+impl OpenAiModels {
+    /// The model’s entry in the embedded registry.
+    pub fn info(&self) -> Option<&'static ModelInfo> {
+        ModelRegistry::embedded().get(self)
+    }
+}
+
+impl OctoAiModels {
+    /// The model’s entry in the embedded registry.
+    pub fn info(&self) -> Option<&'static ModelInfo> {
+        ModelRegistry::embedded().get(self)
+    }
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// REQUEST CHECKS
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+#[derive(Debug, Clone, PartialEq)]
+pub enum Unsupported {
+    Feature(Feature),
+    MaxTokens { max: usize, given: usize },
+    /// The prompt, plus `max_tokens` when set, doesn’t fit in the context window.
+    ContextWindow { max: usize, given: usize },
+}
+
+impl std::fmt::Display for Unsupported {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Unsupported::Feature(feature) => write!(f, "{feature} is not supported"),
+            Unsupported::MaxTokens { max, given } => {
+                write!(f, "max_tokens is {given}, the model generates at most {max}")
+            }
+            Unsupported::ContextWindow { max, given } => {
+                write!(f, "the request needs {given} tokens, the context window is {max}")
+            }
+        }
+    }
+}
+
+impl RequestBuilder {
+    /// Everything the request asks for that its model doesn’t offer, for
+    /// callers that only want to warn. Requests whose model is missing or
+    /// not in the registry have nothing to check.
+    pub fn check_capabilities(&self, registry: &ModelRegistry) -> Vec<Unsupported> {
+        let Some(info) = self.model.as_deref().and_then(|x| registry.get(x)) else {
+            return Vec::new()
+        };
+        let mut issues = Vec::new();
+        if self.tools.as_ref().is_some_and(|x| !x.is_empty()) && !info.tools {
+            issues.push(Unsupported::Feature(Feature::Tools));
+        }
+        if self.response_format.as_ref().is_some_and(|x| x.is_json_object()) && !info.json_mode {
+            issues.push(Unsupported::Feature(Feature::JsonMode));
+        }
+        if (self.logprobs == Some(true) || self.top_logprobs.is_some()) && !info.logprobs {
+            issues.push(Unsupported::Feature(Feature::Logprobs));
+        }
+        if let Some(given) = self.max_tokens.and_then(|x| usize::try_from(x).ok()) {
+            if given > info.max_output() {
+                issues.push(Unsupported::MaxTokens { max: info.max_output(), given });
+            }
+        }
+        if info.context_window > 0 {
+            let max_tokens = self.max_tokens.and_then(|x| usize::try_from(x).ok()).unwrap_or_default();
+            let given = self.count_tokens() + max_tokens;
+            if given > info.context_window {
+                issues.push(Unsupported::ContextWindow { max: info.context_window, given });
+            }
+        }
+        issues
+    }
+    /// Like [`RequestBuilder::check_capabilities`], but fails when anything is unsupported.
+    pub fn require_capabilities(&self, registry: &ModelRegistry) -> Result<(), UnsupportedCapabilities> {
+        let issues = self.check_capabilities(registry);
+        if issues.is_empty() {
+            return Ok(())
+        }
+        let model = self.model.clone().unwrap_or_default();
+        Err(UnsupportedCapabilities { model, issues })
+    }
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// ERRORS
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+#[derive(Debug, Clone)]
+pub struct UnsupportedCapabilities {
+    pub model: String,
+    pub issues: Vec<Unsupported>,
+}
+
+impl std::fmt::Display for UnsupportedCapabilities {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let issues = self.issues
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("; ");
+        let msg = format!("Error: Unsupported Capabilities! {:?}: {issues}.", self.model);
+        let msg = msg.red();
+        write!(f, "{msg}")
+    }
+}
+
+impl std::error::Error for UnsupportedCapabilities {}
@@ -0,0 +1,22 @@
+/// Describes a model offering that can be used with the API.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Model {
+    /// The model identifier, which can be referenced in the API endpoints.
+    pub id: String,
+    /// The object type, which is always `model`.
+    #[serde(default)]
+    pub object: String,
+    /// The Unix timestamp (in seconds) when the model was created.
+    #[serde(default)]
+    pub created: Option<isize>,
+    /// The organization that owns the model.
+    #[serde(default)]
+    pub owned_by: Option<String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ModelList {
+    #[serde(default)]
+    pub object: String,
+    pub data: Vec<Model>,
+}
@@ -0,0 +1,154 @@
+//! Pre-flight screening of request messages, see
+//! [`crate::text_api::client::ApiCallBuilder::with_moderation`].
+use std::collections::HashMap;
+
+use colored::Colorize;
+
+use crate::text_api::request::Message;
+use super::client::ClientConfiguration;
+use super::request::{Model, RequestBuilder};
+use super::response::Response;
+
+/// Runs every message except the assistant's own replies (system, user,
+/// tool and function messages) through the moderation endpoint and rejects
+/// the call when a category score exceeds its threshold.
+///
+/// Without thresholds, the call is rejected whenever the API flags an input.
+pub struct ModerationGuard {
+    pub client_configuration: ClientConfiguration,
+    pub model: Option<Model>,
+    /// Category name (e.g. `harassment`, `self-harm/intent`) → the score above which the call is rejected.
+    pub thresholds: HashMap<String, f32>,
+}
+
+impl ModerationGuard {
+    pub fn new(client_configuration: ClientConfiguration) -> Self {
+        Self { client_configuration, model: None, thresholds: HashMap::new() }
+    }
+    pub fn with_model(mut self, model: impl Into<Model>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+    pub fn with_threshold(mut self, category: impl Into<String>, threshold: f32) -> Self {
+        self.thresholds.insert(category.into(), threshold);
+        self
+    }
+    /// Screens every non-assistant message, failing with [`ModerationRejected`]
+    /// if any exceeds a threshold.
+    pub async fn screen(&self, messages: &[Message]) -> Result<(), Box<dyn std::error::Error>> {
+        let (indices, inputs): (Vec<usize>, Vec<String>) = messages
+            .iter()
+            .enumerate()
+            .filter(|(_, message)| !matches!(message, Message::Assistant { .. }))
+            .map(|(index, message)| (index, message.content().to_string()))
+            .unzip();
+        if inputs.is_empty() {
+            return Ok(())
+        }
+        let mut request = RequestBuilder::default().with_input(inputs);
+        request.model = self.model.clone();
+        let response = request
+            .build()
+            .unwrap()
+            .execute(&self.client_configuration)
+            .await?;
+        let mut violations = self.violations(&response)?;
+        for violation in violations.iter_mut() {
+            violation.message_index = indices[violation.message_index];
+        }
+        if violations.is_empty() {
+            return Ok(())
+        }
+        Err(Box::new(ModerationRejected { violations }))
+    }
+    /// The categories of `response` that reject the call, with
+    /// `message_index` being the index of the input. Fails when a threshold
+    /// names a category the response doesn’t score, which would otherwise
+    /// never fire.
+    pub fn violations(&self, response: &Response) -> Result<Vec<Violation>, UnknownCategory> {
+        let mut thresholds = self.thresholds.iter().collect::<Vec<_>>();
+        thresholds.sort_by(|a, b| a.0.cmp(b.0));
+        let mut violations = Vec::new();
+        for (message_index, result) in response.results.iter().enumerate() {
+            if self.thresholds.is_empty() {
+                violations.extend(result.flagged_categories().into_iter().map(|category| Violation {
+                    message_index,
+                    category: category.to_string(),
+                    score: result.category_scores.get(category).copied().unwrap_or(1.0),
+                    threshold: None,
+                }));
+                continue
+            }
+            for (category, threshold) in thresholds.iter() {
+                let Some(score) = result.category_scores.get(*category).copied() else {
+                    let mut known = result.category_scores.keys().cloned().collect::<Vec<_>>();
+                    known.sort();
+                    return Err(UnknownCategory { category: category.to_string(), known })
+                };
+                if score > **threshold {
+                    violations.push(Violation {
+                        message_index,
+                        category: category.to_string(),
+                        score,
+                        threshold: Some(**threshold),
+                    });
+                }
+            }
+        }
+        Ok(violations)
+    }
+}
+
+/// A category that caused a [`ModerationRejected`] error.
+#[derive(Debug, Clone)]
+pub struct Violation {
+    /// The index among the request's messages.
+    pub message_index: usize,
+    pub category: String,
+    pub score: f32,
+    /// `None` when rejected because the API flagged the category.
+    pub threshold: Option<f32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ModerationRejected {
+    pub violations: Vec<Violation>,
+}
+
+impl std::fmt::Display for ModerationRejected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let categories = self.violations
+            .iter()
+            .map(|x| format!("'{}' ({:.3}) in message {}", x.category, x.score, x.message_index))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let msg = format!("Error: Moderation Rejected! {categories}.");
+        let msg = msg.red();
+        write!(f, "{msg}")
+    }
+}
+
+impl std::error::Error for ModerationRejected {}
+
+/// A threshold names a category the moderation model doesn’t score, e.g.
+/// `self_harm` for `self-harm`.
+#[derive(Debug, Clone)]
+pub struct UnknownCategory {
+    pub category: String,
+    /// The categories the response does score.
+    pub known: Vec<String>,
+}
+
+impl std::fmt::Display for UnknownCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let msg = format!(
+            "Error: Unknown Moderation Category! '{}' is not one of {}.",
+            self.category,
+            self.known.join(", "),
+        );
+        let msg = msg.red();
+        write!(f, "{msg}")
+    }
+}
+
+impl std::error::Error for UnknownCategory {}
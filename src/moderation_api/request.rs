@@ -0,0 +1,115 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Default)]
+pub struct RequestBuilder {
+    /// Input (or inputs) to classify. Can be a single string, an array of strings, or an array of multi-modal input objects similar to other models.
+    pub input: Option<Input>,
+    /// The content moderation model you would like to use. Defaults to `omni-moderation-latest`.
+    pub model: Option<Model>,
+}
+
+impl RequestBuilder {
+    /// Input (or inputs) to classify. Can be a single string, an array of strings, or an array of multi-modal input objects similar to other models.
+    pub fn with_input(mut self, input: impl Into<Input>) -> Self {
+        self.input = Some(input.into());
+        self
+    }
+    /// The content moderation model you would like to use. Defaults to `omni-moderation-latest`.
+    pub fn with_model(mut self, model: impl Into<Model>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+    pub fn build(self) -> Option<Request> {
+        Some(Request {
+            input: self.input?,
+            model: self.model,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Request {
+    /// Input (or inputs) to classify.
+    pub input: Input,
+    /// The content moderation model you would like to use.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<Model>,
+}
+
+/// A single string, a list of strings, or a list of text and image parts
+/// (images are only supported by the `omni-moderation` models).
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum Input {
+    String(String),
+    Strings(Vec<String>),
+    Parts(Vec<InputPart>),
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type")]
+#[serde(rename_all = "snake_case")]
+pub enum InputPart {
+    Text { text: String },
+    ImageUrl { image_url: ImageUrl },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImageUrl {
+    /// Either a URL of the image or the base64 encoded image data (as a `data:` URL).
+    pub url: String,
+}
+
+impl InputPart {
+    pub fn text(text: impl Into<String>) -> Self {
+        InputPart::Text { text: text.into() }
+    }
+    pub fn image_url(url: impl Into<String>) -> Self {
+        InputPart::ImageUrl { image_url: ImageUrl { url: url.into() } }
+    }
+    /// Encodes raw image bytes as a `data:` URL.
+    pub fn image_bytes(mime_type: impl AsRef<str>, bytes: impl AsRef<[u8]>) -> Self {
+        use base64::prelude::*;
+        let data = BASE64_STANDARD.encode(bytes);
+        Self::image_url(format!("data:{};base64,{data}", mime_type.as_ref()))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(transparent)]
+pub struct Model(pub String);
+
+impl Model {
+    pub fn omni_moderation_latest() -> Self {
+        Self(String::from("omni-moderation-latest"))
+    }
+    pub fn text_moderation_latest() -> Self {
+        Self(String::from("text-moderation-latest"))
+    }
+    pub fn text_moderation_stable() -> Self {
+        Self(String::from("text-moderation-stable"))
+    }
+}
+
+impl From<String> for Input {
+    fn from(value: String) -> Self { Self::String(value) }
+}
+impl From<&str> for Input {
+    fn from(value: &str) -> Self { Self::String(value.to_string()) }
+}
+impl From<Vec<String>> for Input {
+    fn from(value: Vec<String>) -> Self { Self::Strings(value) }
+}
+impl From<Vec<&str>> for Input {
+    fn from(value: Vec<&str>) -> Self { Self::Strings(value.into_iter().map(str::to_string).collect()) }
+}
+impl From<Vec<InputPart>> for Input {
+    fn from(value: Vec<InputPart>) -> Self { Self::Parts(value) }
+}
+
+impl From<String> for Model {
+    fn from(value: String) -> Self { Self(value) }
+}
+impl From<&str> for Model {
+    fn from(value: &str) -> Self { Self(value.to_string()) }
+}
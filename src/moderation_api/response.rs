@@ -0,0 +1,41 @@
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct Response {
+    pub id: String,
+    pub model: String,
+    /// One result per input.
+    pub results: Vec<ModerationResult>,
+}
+
+impl Response {
+    pub fn flagged(&self) -> bool {
+        self.results.iter().any(|x| x.flagged)
+    }
+}
+
+/// Categories are keyed by their API names, e.g. `harassment`, `hate/threatening` or `self-harm/intent`.
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct ModerationResult {
+    /// Whether any of the categories are flagged.
+    pub flagged: bool,
+    pub categories: HashMap<String, bool>,
+    /// The model's confidence in each category, between `0` and `1`.
+    pub category_scores: HashMap<String, f32>,
+    /// Which input types (`text`, `image`) each category was applied to.
+    #[serde(default)]
+    pub category_applied_input_types: Option<HashMap<String, Vec<String>>>,
+}
+
+impl ModerationResult {
+    /// The names of the flagged categories.
+    pub fn flagged_categories(&self) -> Vec<&str> {
+        let mut categories = self.categories
+            .iter()
+            .filter(|(_, flagged)| **flagged)
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        categories.sort_unstable();
+        categories
+    }
+}
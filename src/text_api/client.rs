@@ -2,7 +2,10 @@ use std::{cell::RefCell, path::Path};
 use colored::Colorize;
 use futures::{StreamExt, TryFutureExt};
 
+use super::context::ContextStrategy;
 use super::response;
+use crate::cost::CostAccountant;
+use crate::moderation_api::guard::ModerationGuard;
 
 thread_local! {
     static RUNTIME: RefCell<tokio::runtime::Runtime> = RefCell::new(tokio::runtime::Runtime::new().unwrap());
@@ -19,6 +22,10 @@ pub struct ApiCallBuilder {
     pub request_body: Option<super::request::RequestBuilder>,
     pub timeout: Option<std::time::Duration>,
     pub logger: Option<Box<dyn Logger>>,
+    pub retry_policy: Option<RetryPolicy>,
+    pub moderation: Option<ModerationGuard>,
+    pub cost_accountant: Option<CostAccountant>,
+    pub context_strategy: Option<ContextStrategy>,
 }
 
 impl ApiCallBuilder {
@@ -43,13 +50,50 @@ impl ApiCallBuilder {
         self.logger = Some(logger);
         self
     }
+    /// Retries transient failures of batch calls. Off by default, since a
+    /// completion that timed out may still be billed. Streaming calls are not
+    /// retried.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+    /// Screens the messages, except the assistant's, with the moderation API
+    /// before each call.
+    pub fn with_moderation(mut self, moderation: ModerationGuard) -> Self {
+        self.moderation = Some(moderation);
+        self
+    }
+    /// Records the cost of each call, and refuses to send once the accountant’s budget is spent.
+    pub fn with_cost_accountant(mut self, cost_accountant: CostAccountant) -> Self {
+        self.cost_accountant = Some(cost_accountant);
+        self
+    }
+    /// Trims the message history to fit the model’s context window before each call.
+    pub fn with_context_strategy(mut self, context_strategy: ContextStrategy) -> Self {
+        self.context_strategy = Some(context_strategy);
+        self
+    }
     fn build(self) -> Option<IApiCall> {
         let api_url = self.api_url?;
         let api_key = self.api_key?;
         let request_body = self.request_body?.build()?;
         let timeout = self.timeout;
         let logger: Option<Box<dyn Logger>> = self.logger;
-        let client = IApiCall { api_url, api_key, request_body, timeout, logger };
+        let retry_policy = self.retry_policy.unwrap_or_else(RetryPolicy::none);
+        let moderation = self.moderation;
+        let cost_accountant = self.cost_accountant;
+        let context_strategy = self.context_strategy;
+        let client = IApiCall {
+            api_url,
+            api_key,
+            request_body,
+            timeout,
+            logger,
+            retry_policy,
+            moderation,
+            cost_accountant,
+            context_strategy,
+        };
         Some(client)
     }
     pub fn build_batch_api_call(self) -> Option<BatchApiCall> {
@@ -134,6 +178,30 @@ struct IApiCall {
     pub request_body: super::request::Request,
     pub timeout: Option<std::time::Duration>,
     pub logger: Option<Box<dyn Logger>>,
+    pub retry_policy: RetryPolicy,
+    pub moderation: Option<ModerationGuard>,
+    pub cost_accountant: Option<CostAccountant>,
+    pub context_strategy: Option<ContextStrategy>,
+}
+
+impl IApiCall {
+    /// Fits the messages to the context window, then checks the budget and
+    /// screens the messages.
+    async fn prepare(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(context_strategy) = self.context_strategy.as_ref() {
+            context_strategy.apply(&mut self.request_body).await?;
+        }
+        self.screen().await
+    }
+    async fn screen(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(cost_accountant) = self.cost_accountant.as_ref() {
+            cost_accountant.check_budget()?;
+        }
+        if let Some(moderation) = self.moderation.as_ref() {
+            moderation.screen(&self.request_body.messages).await?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -168,7 +236,8 @@ pub struct BatchApiCall {
 
 impl BatchApiCall {
     /// This calls the streaming client internally.
-    pub async fn execute(self) -> Result<response::batch::Response, Box<dyn std::error::Error>> {
+    pub async fn execute(mut self) -> Result<response::batch::Response, Box<dyn std::error::Error>> {
+        self.client.prepare().await?;
         let api_url = self.client.api_url.0;
         let api_key = self.client.api_key.as_str();
         let client = {
@@ -186,20 +255,26 @@ impl BatchApiCall {
             return Err(Box::new(InvalidConfiguration::StreamFlag { should_be: false, given: true }));
         }
         let json_data = serde_json::to_string(&self.client.request_body).unwrap();
-        let response = client
-            .post(api_url)
-            .header("Authorization", format!("Bearer {}", api_key))
-            .header("Content-Type", "application/json")
-            // .json(&self.client.request_body)
-            .body(json_data)
-            .send()
-            .await?;
-        if let Some(error) = ApiError::from_code(response.status().as_u16()) {
-            return Err(Box::new(error))
+        let result = self.client.retry_policy.run(|| async {
+            let response = client
+                .post(api_url)
+                .header("Authorization", format!("Bearer {}", api_key))
+                .header("Content-Type", "application/json")
+                // .json(&self.client.request_body)
+                .body(json_data.clone())
+                .send()
+                .await?;
+            if let Some(error) = ApiError::from_code(response.status().as_u16()) {
+                return Err(Box::new(error) as Box<dyn std::error::Error>)
+            }
+            let result = response.text().await?;
+            let result = serde_json::from_str::<response::batch::Response>(&result)?;
+            // let result = response.json::<response::batch::Response>().await?;
+            Ok(result)
+        }).await?;
+        if let Some(cost_accountant) = self.client.cost_accountant.as_ref() {
+            cost_accountant.record_usage(&self.client.request_body.model, &result.usage);
         }
-        let result = response.text().await?;
-        let result = serde_json::from_str::<response::batch::Response>(&result)?;
-        // let result = response.json::<response::batch::Response>().await?;
         Ok(result)
     }
 }
@@ -219,7 +294,8 @@ pub struct ServerSentEvent {
 
 
 impl StreamingApiCall {
-    pub async fn execute(self) -> Result<ResponseChunkCollection, Box<dyn std::error::Error>> {
+    pub async fn execute(mut self) -> Result<ResponseChunkCollection, Box<dyn std::error::Error>> {
+        self.client.prepare().await?;
         let api_url = self.client.api_url.0;
         let api_key = self.client.api_key.as_str();
         let client = {
@@ -280,10 +356,33 @@ impl StreamingApiCall {
         if let Some(logger) = logger.as_ref() {
             logger.log("\n");
         }
-        Ok(ResponseChunkCollection(outputs))
+        let outputs = ResponseChunkCollection(outputs);
+        if let Some(cost_accountant) = self.client.cost_accountant.as_ref() {
+            let usage = outputs
+                .usage()
+                .unwrap_or_else(|| estimate_usage(&self.client.request_body, &outputs));
+            cost_accountant.record_usage(&self.client.request_body.model, &usage);
+        }
+        Ok(outputs)
     }
 }
 
+/// The usage counted locally, for servers that don’t report it on streams.
+fn estimate_usage(
+    request: &super::request::Request,
+    outputs: &ResponseChunkCollection,
+) -> response::batch::Usage {
+    let counter = super::tokens::TokenCounter::for_model(&request.model);
+    let prompt_tokens = counter.count_messages(&request.messages);
+    let completion_tokens = (0..request.n.unwrap_or(1).max(1) as usize)
+        .filter_map(|x| outputs.content(x))
+        .map(|x| counter.count_text(x))
+        .sum::<usize>();
+    let prompt_tokens = prompt_tokens as super::common::Integer;
+    let completion_tokens = completion_tokens as super::common::Integer;
+    response::batch::Usage { prompt_tokens, completion_tokens, total_tokens: prompt_tokens + completion_tokens }
+}
+
 #[derive(Debug, Clone)]
 pub struct ResponseChunkCollection(pub Vec<response::streaming::ResponseChunk>);
 
@@ -299,6 +398,10 @@ impl ResponseChunkCollection {
         }
         Some(output.join(""))
     }
+    /// The usage reported on the final chunk, if the server sent one.
+    pub fn usage(&self) -> Option<response::batch::Usage> {
+        self.0.iter().rev().find_map(|x| x.usage.clone())
+    }
 }
 
 
@@ -352,6 +455,7 @@ impl ApiError {
             409 => Some(ApiError::ConflictError),
             422 => Some(ApiError::UnprocessableEntityError),
             429 => Some(ApiError::RateLimitError),
+            500..=599 => Some(ApiError::InternalServerError),
             _ => None,
         }
     }
@@ -376,3 +480,117 @@ impl std::fmt::Display for ApiError {
 }
 
 impl std::error::Error for ApiError {}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// RETRY POLICY
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+/// How transient failures (rate limits, server errors, timeouts and dropped
+/// connections) are retried. Shared by every client. The text client only
+/// retries when given a policy; the others use the default (3 retries,
+/// backing off from 500ms to at most 30s) unless given another.
+///
+/// The delay before each retry doubles, starting at `initial_backoff` and
+/// never exceeding `max_backoff`.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: usize,
+    pub initial_backoff: std::time::Duration,
+    pub max_backoff: std::time::Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: std::time::Duration::from_millis(500),
+            max_backoff: std::time::Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Never retry.
+    pub fn none() -> Self {
+        Self { max_retries: 0, ..Self::default() }
+    }
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+    pub fn with_initial_backoff(mut self, initial_backoff: std::time::Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+    pub fn with_max_backoff(mut self, max_backoff: std::time::Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+    fn is_retryable(error: &(dyn std::error::Error + 'static)) -> bool {
+        if let Some(error) = error.downcast_ref::<ApiError>() {
+            return matches!(
+                error,
+                ApiError::RateLimitError
+                    | ApiError::InternalServerError
+                    | ApiError::APITimeoutError
+                    | ApiError::APIConnectionError
+            )
+        }
+        if let Some(error) = error.downcast_ref::<reqwest::Error>() {
+            return error.is_timeout() || error.is_connect()
+        }
+        false
+    }
+    /// Whether the server answered with an error or was never reached, so the
+    /// call certainly had no effect.
+    fn is_refused(error: &(dyn std::error::Error + 'static)) -> bool {
+        if let Some(error) = error.downcast_ref::<ApiError>() {
+            return matches!(error, ApiError::RateLimitError | ApiError::InternalServerError)
+        }
+        if let Some(error) = error.downcast_ref::<reqwest::Error>() {
+            return error.is_connect()
+        }
+        false
+    }
+    /// Runs `call` until it succeeds, fails with a non-retryable error, or the
+    /// retry budget is spent.
+    pub async fn run<T, F, Fut>(&self, call: F) -> Result<T, Box<dyn std::error::Error>>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = Result<T, Box<dyn std::error::Error>>>,
+    {
+        self.run_while(call, Self::is_retryable).await
+    }
+    /// Like [`RetryPolicy::run`], for calls that create something (a batch, a
+    /// file). Timeouts and dropped connections aren’t retried, since the
+    /// server may have acted on the call already.
+    pub async fn run_once_accepted<T, F, Fut>(&self, call: F) -> Result<T, Box<dyn std::error::Error>>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = Result<T, Box<dyn std::error::Error>>>,
+    {
+        self.run_while(call, Self::is_refused).await
+    }
+    async fn run_while<T, F, Fut>(
+        &self,
+        mut call: F,
+        is_retryable: fn(&(dyn std::error::Error + 'static)) -> bool,
+    ) -> Result<T, Box<dyn std::error::Error>>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = Result<T, Box<dyn std::error::Error>>>,
+    {
+        let mut backoff = self.initial_backoff;
+        let mut attempt = 0;
+        loop {
+            match call().await {
+                Ok(value) => return Ok(value),
+                Err(error) if attempt < self.max_retries && is_retryable(error.as_ref()) => {
+                    tokio::time::sleep(backoff).await;
+                    backoff = std::cmp::min(backoff * 2, self.max_backoff);
+                    attempt += 1;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+}
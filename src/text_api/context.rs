@@ -0,0 +1,316 @@
+//! Keeps a chat’s message list inside the model’s context window.
+//!
+//! System messages are always kept. Everything else is grouped into turns —
+//! a user message and the replies that follow it — and trimmed a whole turn
+//! at a time, oldest first, so tool calls are never separated from their
+//! results. The latest turn is never dropped.
+use std::ops::Range;
+use std::sync::Arc;
+
+use super::client::ApiCallBuilder;
+use super::common::Integer;
+use super::request::{Message, Request, RequestBuilder};
+use super::tokens::TokenCounter;
+use crate::models_api::registry::ModelRegistry;
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// STRATEGIES
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+/// Pass to `ApiCallBuilder::with_context_strategy` to apply it before every call.
+///
+/// Token budgets default to the model’s context window (from the embedded
+/// registry) minus the request’s `max_tokens`; requests for unknown models
+/// are left alone.
+#[derive(Debug, Clone)]
+pub enum ContextStrategy {
+    /// Keep the system messages and the last `n` turns.
+    KeepLastTurns(usize),
+    /// Drop the oldest turns until the prompt fits in `budget` tokens.
+    DropOldest { budget: Option<usize> },
+    /// Like [`ContextStrategy::DropOldest`], but the dropped turns are
+    /// replaced with a summary written by an extra completion call.
+    Summarize { budget: Option<usize>, summarizer: Summarizer },
+}
+
+impl ContextStrategy {
+    pub fn keep_last_turns(n: usize) -> Self {
+        ContextStrategy::KeepLastTurns(n)
+    }
+    pub fn drop_oldest() -> Self {
+        ContextStrategy::DropOldest { budget: None }
+    }
+    pub fn summarize(summarizer: Summarizer) -> Self {
+        ContextStrategy::Summarize { budget: None, summarizer }
+    }
+    /// Overrides the token budget of the `DropOldest` and `Summarize` strategies.
+    pub fn with_budget(self, budget: usize) -> Self {
+        match self {
+            ContextStrategy::KeepLastTurns(n) => ContextStrategy::KeepLastTurns(n),
+            ContextStrategy::DropOldest { .. } => ContextStrategy::DropOldest { budget: Some(budget) },
+            ContextStrategy::Summarize { summarizer, .. } => {
+                ContextStrategy::Summarize { budget: Some(budget), summarizer }
+            }
+        }
+    }
+    /// Trims the request’s messages in place.
+    pub async fn apply(&self, request: &mut Request) -> Result<(), Box<dyn std::error::Error>> {
+        let counter = TokenCounter::for_model(&request.model);
+        match self {
+            ContextStrategy::KeepLastTurns(n) => {
+                request.messages = keep_last_turns(&request.messages, *n);
+            }
+            ContextStrategy::DropOldest { budget } => {
+                let Some(budget) = budget.or_else(|| default_budget(request)) else {
+                    return Ok(())
+                };
+                request.messages = drop_oldest(&request.messages, budget, &counter).0;
+            }
+            ContextStrategy::Summarize { budget, summarizer } => {
+                let Some(budget) = budget.or_else(|| default_budget(request)) else {
+                    return Ok(())
+                };
+                let (kept, dropped) = drop_oldest(&request.messages, budget, &counter);
+                if dropped.is_empty() {
+                    return Ok(())
+                }
+                // Boxed, since the summarization call runs through `ApiCallBuilder` too.
+                let summary = Box::pin(summarizer.summarize(&dropped)).await?;
+                let summary = Message::system(format!("Summary of the earlier conversation:\n{summary}"));
+                let mut messages = kept;
+                let position = messages
+                    .iter()
+                    .position(|x| !matches!(x, Message::System { .. }))
+                    .unwrap_or(messages.len());
+                messages.insert(position, summary);
+                // The summary itself takes up room, so trim again if needed.
+                request.messages = drop_oldest(&messages, budget, &counter).0;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn default_budget(request: &Request) -> Option<usize> {
+    let info = ModelRegistry::embedded().get(&request.model)?;
+    if info.context_window == 0 {
+        return None
+    }
+    let reserved = request.max_tokens.and_then(|x| usize::try_from(x).ok()).unwrap_or_default();
+    Some(info.context_window.saturating_sub(reserved))
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// TURNS
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+/// The index ranges of each turn. A turn starts at a user message (or at the
+/// first non-system message) and runs until the next user message.
+pub(crate) fn turns(messages: &[Message]) -> Vec<Range<usize>> {
+    let mut starts = Vec::new();
+    for (index, message) in messages.iter().enumerate() {
+        let is_start = match message {
+            Message::System { .. } => false,
+            Message::User { .. } => true,
+            _ => starts.is_empty(),
+        };
+        if is_start {
+            starts.push(index);
+        }
+    }
+    starts
+        .iter()
+        .enumerate()
+        .map(|(ix, start)| *start..starts.get(ix + 1).copied().unwrap_or(messages.len()))
+        .collect()
+}
+
+/// Splits `messages` into those outside the first `count` turns (plus every
+/// system message), and the non-system messages of those turns.
+fn split_turns(messages: &[Message], count: usize) -> (Vec<Message>, Vec<Message>) {
+    let turns = turns(messages);
+    let cutoff = turns.get(count).map(|x| x.start).unwrap_or(messages.len());
+    let first = turns.first().map(|x| x.start).unwrap_or(cutoff);
+    let mut kept = Vec::new();
+    let mut dropped = Vec::new();
+    for (index, message) in messages.iter().enumerate() {
+        let is_dropped = (first..cutoff).contains(&index) && !matches!(message, Message::System { .. });
+        if is_dropped {
+            dropped.push(message.clone());
+        } else {
+            kept.push(message.clone());
+        }
+    }
+    (kept, dropped)
+}
+
+/// The system messages and the last `n` turns (at least one).
+pub fn keep_last_turns(messages: &[Message], n: usize) -> Vec<Message> {
+    let total = turns(messages).len();
+    let drop = total.saturating_sub(n.max(1));
+    split_turns(messages, drop).0
+}
+
+/// Drops the oldest turns until `messages` fit in `budget` tokens, returning
+/// the kept and the dropped messages. The latest turn is kept even when it
+/// alone is over budget.
+pub fn drop_oldest(messages: &[Message], budget: usize, counter: &TokenCounter) -> (Vec<Message>, Vec<Message>) {
+    let total = turns(messages).len();
+    let mut drop = 0;
+    loop {
+        let (kept, dropped) = split_turns(messages, drop);
+        if drop + 1 >= total || counter.count_messages(&kept) <= budget {
+            return (kept, dropped)
+        }
+        drop += 1;
+    }
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// SUMMARIZER
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+/// The completion call that condenses dropped turns.
+///
+/// The transcript is sent in chunks that fit the summarizer model’s context
+/// window, whose summaries are then condensed into one.
+#[derive(Clone)]
+pub struct Summarizer {
+    /// Makes a builder with the URL, key and settings (retries, timeout,
+    /// cost accountant, moderation) for each call.
+    api_call: Arc<dyn Fn() -> ApiCallBuilder + Send + Sync>,
+    pub model: String,
+    pub instructions: String,
+    /// The most tokens one call may send. Defaults to the model’s context
+    /// window (from the embedded registry) minus room for the summary; the
+    /// transcript isn’t chunked for unknown models.
+    pub budget: Option<usize>,
+}
+
+impl std::fmt::Debug for Summarizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Summarizer")
+            .field("model", &self.model)
+            .field("instructions", &self.instructions)
+            .field("budget", &self.budget)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The most tokens a summary may have.
+const SUMMARY_TOKENS: usize = 1024;
+
+impl Summarizer {
+    /// Calls `model` with a builder from `api_call` (URL, key, retries and
+    /// so on) for every summary.
+    pub fn new(model: impl AsRef<str>, api_call: impl Fn() -> ApiCallBuilder + Send + Sync + 'static) -> Self {
+        Self {
+            api_call: Arc::new(api_call),
+            model: model.as_ref().to_string(),
+            instructions: String::from(
+                "Summarize the following conversation in a few sentences. Keep names, \
+                 decisions, open questions and any facts the assistant will need later."
+            ),
+            budget: None,
+        }
+    }
+    /// Replaces the default system prompt of the summarization call.
+    pub fn with_instructions(mut self, instructions: impl AsRef<str>) -> Self {
+        self.instructions = instructions.as_ref().to_string();
+        self
+    }
+    /// The most tokens one summarization call may send.
+    pub fn with_budget(mut self, budget: usize) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+    pub async fn summarize(&self, messages: &[Message]) -> Result<String, Box<dyn std::error::Error>> {
+        let lines = messages
+            .iter()
+            .map(|x| format!("{}: {}", x.role(), x.content()))
+            .collect::<Vec<_>>();
+        let mut summaries = Vec::new();
+        for chunk in self.chunks(&lines) {
+            summaries.push(self.complete(chunk).await?);
+        }
+        if summaries.len() == 1 {
+            return Ok(summaries.remove(0))
+        }
+        // Condense the partial summaries, cutting them short if they still don’t fit.
+        let combined = summaries.join("\n\n");
+        let combined = match self.transcript_budget() {
+            Some(budget) => truncate(&combined, budget, &TokenCounter::for_model(&self.model)),
+            None => combined,
+        };
+        self.complete(combined).await
+    }
+    /// The most tokens the transcript of one call may have.
+    fn transcript_budget(&self) -> Option<usize> {
+        let budget = match self.budget {
+            Some(budget) => budget,
+            None => {
+                let info = ModelRegistry::embedded().get(&self.model)?;
+                info.context_window.checked_sub(SUMMARY_TOKENS).filter(|x| *x > 0)?
+            }
+        };
+        let counter = TokenCounter::for_model(&self.model);
+        let overhead = counter.count_messages(&[Message::system(&self.instructions), Message::user("")]);
+        Some(budget.saturating_sub(overhead).max(1))
+    }
+    /// The transcript lines grouped into chunks within the budget. A line
+    /// over the budget on its own is cut short.
+    fn chunks(&self, lines: &[String]) -> Vec<String> {
+        let Some(budget) = self.transcript_budget() else {
+            return vec![lines.join("\n\n")]
+        };
+        let counter = TokenCounter::for_model(&self.model);
+        let mut chunks = Vec::new();
+        let mut chunk = String::new();
+        let mut tokens = 0;
+        for line in lines {
+            let line = truncate(line, budget, &counter);
+            // Plus the blank line that separates it.
+            let line_tokens = counter.count_text(&line) + 1;
+            if !chunk.is_empty() && tokens + line_tokens > budget {
+                chunks.push(std::mem::take(&mut chunk));
+                tokens = 0;
+            }
+            if !chunk.is_empty() {
+                chunk.push_str("\n\n");
+            }
+            chunk.push_str(&line);
+            tokens += line_tokens;
+        }
+        chunks.push(chunk);
+        chunks
+    }
+    async fn complete(&self, transcript: String) -> Result<String, Box<dyn std::error::Error>> {
+        let request_body = RequestBuilder::default()
+            .with_model(&self.model)
+            .with_max_tokens(SUMMARY_TOKENS as Integer)
+            .with_messages(vec![Message::system(&self.instructions), Message::user(transcript)]);
+        let response = (self.api_call)()
+            .with_request_body(request_body)
+            .build_batch_api_call()
+            .ok_or("the summary can’t be requested without an API URL and key")?
+            .execute()
+            .await?;
+        let summary = response.choices
+            .first()
+            .and_then(|x| x.message.content.clone())
+            .unwrap_or_default();
+        Ok(summary)
+    }
+}
+
+/// `text` cut short to at most `budget` tokens.
+fn truncate(text: &str, budget: usize, counter: &TokenCounter) -> String {
+    let mut text = text.to_string();
+    loop {
+        let tokens = counter.count_text(&text);
+        if tokens <= budget {
+            return text
+        }
+        // Shrink in proportion, and by at least one character so it ends.
+        let length = text.chars().count();
+        let keep = (length * budget / tokens).min(length - 1);
+        text = text.chars().take(keep).collect();
+    }
+}
@@ -0,0 +1,127 @@
+//! A chat session: the message history plus the request parameters every
+//! call shares, with replies appended as they arrive.
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::client::{ApiCallBuilder, ResponseChunkCollection};
+use super::request::{Message, RequestBuilder};
+use super::response;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Conversation {
+    /// Every message so far, oldest first.
+    pub messages: Vec<Message>,
+    /// The model, temperature, etc. used for every call. Its own messages are ignored.
+    pub defaults: RequestBuilder,
+}
+
+impl Conversation {
+    /// Starts a conversation with the given defaults; messages already on
+    /// `defaults` become the start of the history.
+    pub fn new(defaults: RequestBuilder) -> Self {
+        let mut defaults = defaults;
+        let messages = std::mem::take(&mut defaults.messages);
+        Self { messages, defaults }
+    }
+    pub fn with_system(mut self, content: impl AsRef<str>) -> Self {
+        self.messages.push(Message::system(content));
+        self
+    }
+    pub fn push(&mut self, message: Message) {
+        self.messages.push(message);
+    }
+    /// The request for the next call: the defaults with the full history.
+    pub fn request(&self) -> RequestBuilder {
+        self.defaults.clone().with_messages(self.messages.clone())
+    }
+    /// The number of exchanges, each starting with a user message.
+    pub fn turns(&self) -> usize {
+        super::context::turns(&self.messages).len()
+    }
+    /// Sends `content` as a user message and appends the reply. On failure
+    /// the history is left as it was.
+    ///
+    /// `api_call` supplies the URL, key and any other call settings; its
+    /// request body is replaced.
+    pub async fn send(
+        &mut self,
+        content: impl AsRef<str>,
+        api_call: ApiCallBuilder,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let message = Message::user(content);
+        let mut request = self.request().with_stream(false);
+        request.messages.push(message.clone());
+        let response = api_call
+            .with_request_body(request)
+            .build_batch_api_call()
+            .ok_or("the conversation can’t be sent without an API URL, key and model")?
+            .execute()
+            .await?;
+        self.messages.push(message);
+        Ok(self.append_response(&response))
+    }
+    /// Like [`Conversation::send`], but streams the reply (e.g. to a logger on `api_call`).
+    pub async fn send_streaming(
+        &mut self,
+        content: impl AsRef<str>,
+        api_call: ApiCallBuilder,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let message = Message::user(content);
+        let mut request = self.request().with_stream(true);
+        request.messages.push(message.clone());
+        let chunks = api_call
+            .with_request_body(request)
+            .build_streaming_api_call()
+            .ok_or("the conversation can’t be sent without an API URL, key and model")?
+            .execute()
+            .await?;
+        self.messages.push(message);
+        Ok(self.append_chunks(&chunks))
+    }
+    /// Appends the first choice of a response obtained elsewhere, returning its content.
+    pub fn append_response(&mut self, response: &response::batch::Response) -> String {
+        let content = response.choices
+            .first()
+            .and_then(|x| x.message.content.clone())
+            .unwrap_or_default();
+        self.messages.push(Message::assistant(&content));
+        content
+    }
+    /// Appends the first choice of a streamed response obtained elsewhere, returning its content.
+    pub fn append_chunks(&mut self, chunks: &ResponseChunkCollection) -> String {
+        let content = chunks.content(0).unwrap_or_default();
+        self.messages.push(Message::assistant(&content));
+        content
+    }
+    /// Removes the last exchange — the last user message and everything
+    /// after it — returning the removed messages.
+    pub fn undo(&mut self) -> Option<Vec<Message>> {
+        let last = super::context::turns(&self.messages).pop()?;
+        Some(self.messages.split_off(last.start))
+    }
+    /// An independent copy to continue in a different direction.
+    pub fn fork(&self) -> Self {
+        self.clone()
+    }
+    /// A copy holding only the first `turn` exchanges (and the system
+    /// messages before them), to retry the conversation from that point.
+    pub fn branch_at(&self, turn: usize) -> Self {
+        let turns = super::context::turns(&self.messages);
+        let end = turns.get(turn).map(|x| x.start).unwrap_or(self.messages.len());
+        Self { messages: self.messages[..end].to_vec(), defaults: self.defaults.clone() }
+    }
+    pub fn open(file_path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let source = std::fs::read_to_string(file_path.as_ref())?;
+        let conversation = serde_json::from_str::<Self>(&source)?;
+        Ok(conversation)
+    }
+    pub fn save(&self, file_path: impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>> {
+        let file_path = file_path.as_ref();
+        if let Some(parent) = file_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(file_path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
@@ -2,4 +2,7 @@ pub mod common;
 pub mod request;
 pub mod response;
 pub mod client;
-pub mod xml_dsl;
\ No newline at end of file
+pub mod xml_dsl;
+pub mod tokens;
+pub mod context;
+pub mod conversation;
\ No newline at end of file
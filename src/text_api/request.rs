@@ -4,7 +4,8 @@ use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use super::common::{Integer, Number};
 
-#[derive(Debug, Clone, Default, Serialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct RequestBuilder {
     /// A list of messages comprising the conversation so far.
     pub messages: Vec<Message>,
@@ -309,16 +310,12 @@ pub struct Request {
     /// Currently, only functions are supported as a tool.
     /// 
     /// Use this to provide a list of functions the model may generate JSON inputs for.
-    /// 
-    /// **Currently we don’t serialize this field. It’s skipped.**
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub tools: Option<Vec<Tool>>,
     /// Controls which (if any) function is called by the model. none means the model will not call a function and instead generates a message. auto means the model can pick between generating a message or calling a function. Specifying a particular function via `{"type": "function", "function": {"name": "my_function"}}` forces the model to call that function.
     ///
     /// `none` is the default when no functions are present. auto is the default if functions are present.
-    /// 
-    /// **Currently we don’t serialize this field. It’s skipped.**
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub tool_choice: Option<ToolChoice>,
@@ -356,6 +353,9 @@ impl ResponseFormat {
     pub const JSON_OBJECT: Self = ResponseFormat {
         r#type: internal::ResponseFormatType::JsonObject
     };
+    pub fn is_json_object(&self) -> bool {
+        matches!(self.r#type, internal::ResponseFormatType::JsonObject)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -454,6 +454,43 @@ impl Message {
         let name = name.as_ref().to_string();
         Message::Function { content, name }
     }
+    /// The serialized `role` of the message.
+    pub fn role(&self) -> &'static str {
+        match self {
+            Message::System { .. } => "system",
+            Message::User { .. } => "user",
+            Message::Assistant { .. } => "assistant",
+            Message::Tool { .. } => "tool",
+            Message::Function { .. } => "function",
+        }
+    }
+    pub fn content(&self) -> &str {
+        match self {
+            Message::System { content, .. } => content,
+            Message::User { content, .. } => content,
+            Message::Assistant { content, .. } => content,
+            Message::Tool { content, .. } => content,
+            Message::Function { content, .. } => content,
+        }
+    }
+    pub fn content_mut(&mut self) -> &mut String {
+        match self {
+            Message::System { content, .. } => content,
+            Message::User { content, .. } => content,
+            Message::Assistant { content, .. } => content,
+            Message::Tool { content, .. } => content,
+            Message::Function { content, .. } => content,
+        }
+    }
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Message::System { name, .. } => name.as_deref(),
+            Message::User { name, .. } => name.as_deref(),
+            Message::Assistant { name, .. } => name.as_deref(),
+            Message::Tool { .. } => None,
+            Message::Function { name, .. } => Some(name),
+        }
+    }
 }
 
 pub mod internal {
@@ -467,22 +504,70 @@ pub mod internal {
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Tool {
     pub r#type: String,
     pub function: Function,
 }
 
-#[derive(Debug, Clone, Serialize)]
+impl Tool {
+    /// A function tool; `parameters` is a JSON Schema object.
+    pub fn function(name: impl AsRef<str>, description: Option<String>, parameters: Option<serde_json::Value>) -> Self {
+        let name = name.as_ref().to_string();
+        Tool {
+            r#type: String::from("function"),
+            function: Function { description, name, parameters },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Function {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
     pub description: Option<String>,
     pub name: String,
-    pub parameters: Option<String>,
+    /// The JSON Schema of the arguments.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub parameters: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+/// Either `"none"`, `"auto"` or `"required"`, or a specific function via
+/// `{"type": "function", "function": {"name": "my_function"}}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
 pub enum ToolChoice {
-    
+    Mode(ToolChoiceMode),
+    Function {
+        r#type: String,
+        function: FunctionName,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolChoiceMode {
+    None,
+    Auto,
+    Required,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FunctionName {
+    pub name: String,
+}
+
+impl ToolChoice {
+    pub const NONE: Self = ToolChoice::Mode(ToolChoiceMode::None);
+    pub const AUTO: Self = ToolChoice::Mode(ToolChoiceMode::Auto);
+    pub const REQUIRED: Self = ToolChoice::Mode(ToolChoiceMode::Required);
+    pub fn function(name: impl AsRef<str>) -> Self {
+        ToolChoice::Function {
+            r#type: String::from("function"),
+            function: FunctionName { name: name.as_ref().to_string() },
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -515,6 +600,19 @@ pub enum OctoAiModels {
 #[derive(Debug, Clone)]
 #[allow(non_camel_case_types)]
 pub enum OpenAiModels {
+    /// # GPT-4o
+    /// # `gpt-4o`
+    /// High-intelligence flagship model for complex, multi-step tasks. Currently points to `gpt-4o-2024-05-13`.
+    gpt_4o,
+    /// # `gpt-4o-mini`
+    /// Affordable and intelligent small model for fast, lightweight tasks.
+    gpt_4o_mini,
+    /// # `gpt-4-turbo`
+    /// The latest GPT-4 Turbo model with vision capabilities. Currently points to `gpt-4-turbo-2024-04-09`.
+    gpt_4_turbo,
+    /// # `gpt-4-turbo-2024-04-09`
+    /// GPT-4 Turbo with Vision model. Vision requests can now use JSON mode and function calling.
+    gpt_4_turbo_2024_04_09,
     /// # GPT-4 Turbo
     /// # `gpt-4-0125-preview`
     /// The latest GPT-4 model intended to reduce cases of “laziness” where the model doesn’t complete a task. Returns a maximum of 4,096 output tokens.
@@ -576,6 +674,10 @@ impl AsRef<str> for OctoAiModels {
 impl AsRef<str> for OpenAiModels {
     fn as_ref(&self) -> &str {
         match self {
+            OpenAiModels::gpt_4o => "gpt-4o",
+            OpenAiModels::gpt_4o_mini => "gpt-4o-mini",
+            OpenAiModels::gpt_4_turbo => "gpt-4-turbo",
+            OpenAiModels::gpt_4_turbo_2024_04_09 => "gpt-4-turbo-2024-04-09",
             OpenAiModels::gpt_4_0125_preview => "gpt-4-0125-preview",
             OpenAiModels::gpt_4_turbo_preview => "gpt-4-turbo-preview",
             OpenAiModels::gpt_4_1106_preview => "gpt-4-1106-preview",
@@ -47,6 +47,7 @@ pub mod batch {
 /// The data model for streaming ChatGPT (and ChatGPT compatible) responses.
 pub mod streaming {
     use super::{FunctionCall, Integer, LogProbability, ToolCall};
+    use super::batch::Usage;
     use serde::{Deserialize, Serialize};
 
     #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -57,6 +58,10 @@ pub mod streaming {
         pub model: String,
         pub system_fingerprint: Option<String>,
         pub object: String,
+        /// Only sent on the final chunk, and only by servers that report
+        /// usage for streams.
+        #[serde(default)]
+        pub usage: Option<Usage>,
     }
 
     #[derive(Debug, Clone, Serialize, Deserialize)]
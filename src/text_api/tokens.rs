@@ -0,0 +1,140 @@
+//! Counts the tokens of messages locally, so requests can be sized before
+//! they’re sent.
+//!
+//! OpenAI models are counted exactly with their BPE tables (`cl100k_base`,
+//! `o200k_base`), which are embedded in the binary, following the chat
+//! format’s per-message overhead. Other providers’ models are estimated.
+use std::sync::OnceLock;
+
+use tiktoken_rs::CoreBPE;
+use tiktoken_rs::tokenizer::Tokenizer;
+
+use super::request::{Message, RequestBuilder};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    /// GPT-4, GPT-4 Turbo and GPT-3.5 Turbo.
+    #[default]
+    Cl100kBase,
+    /// GPT-4o and GPT-4o mini.
+    O200kBase,
+    /// Models with unpublished or non-BPE tokenizers (Llama, Mistral, …),
+    /// estimated from `cl100k_base` with a margin, since those tokenizers
+    /// produce more tokens for the same text.
+    Estimate,
+}
+
+impl Encoding {
+    /// Fine-tuned models (`ft:<base>:...`) use the encoding of their base model.
+    pub fn for_model(model: impl AsRef<str>) -> Self {
+        let model = model.as_ref();
+        let model = model
+            .strip_prefix("ft:")
+            .and_then(|x| x.split(':').next())
+            .unwrap_or(model);
+        match tiktoken_rs::tokenizer::get_tokenizer(model) {
+            Some(Tokenizer::O200kBase) => Encoding::O200kBase,
+            Some(Tokenizer::Cl100kBase) => Encoding::Cl100kBase,
+            _ => Encoding::Estimate,
+        }
+    }
+    /// Whether counts are exact rather than estimated.
+    pub fn is_exact(&self) -> bool {
+        !matches!(self, Encoding::Estimate)
+    }
+    pub fn count(&self, text: &str) -> usize {
+        match self {
+            Encoding::Cl100kBase => cl100k_base().encode_with_special_tokens(text).len(),
+            Encoding::O200kBase => o200k_base().encode_with_special_tokens(text).len(),
+            Encoding::Estimate => {
+                let tokens = cl100k_base().encode_with_special_tokens(text).len();
+                (tokens as f64 * 1.15).ceil() as usize
+            }
+        }
+    }
+}
+
+fn cl100k_base() -> &'static CoreBPE {
+    static BPE: OnceLock<CoreBPE> = OnceLock::new();
+    BPE.get_or_init(|| tiktoken_rs::cl100k_base().unwrap())
+}
+
+fn o200k_base() -> &'static CoreBPE {
+    static BPE: OnceLock<CoreBPE> = OnceLock::new();
+    BPE.get_or_init(|| tiktoken_rs::o200k_base().unwrap())
+}
+
+/// Counts the tokens a list of messages takes up in the prompt. Defaults to
+/// `cl100k_base` with the current chat overhead.
+#[derive(Debug, Clone)]
+pub struct TokenCounter {
+    pub encoding: Encoding,
+    /// Added for every message, for the tokens that delimit it.
+    pub tokens_per_message: usize,
+    /// Added for every named message. `gpt-3.5-turbo-0301` omits the role
+    /// when there’s a name, which is one token less.
+    pub tokens_per_name: isize,
+}
+
+impl Default for TokenCounter {
+    fn default() -> Self {
+        Self::new(Encoding::Cl100kBase)
+    }
+}
+
+impl TokenCounter {
+    pub fn new(encoding: Encoding) -> Self {
+        Self { encoding, tokens_per_message: 3, tokens_per_name: 1 }
+    }
+    /// The encoding and overhead rules of the given model.
+    pub fn for_model(model: impl AsRef<str>) -> Self {
+        let model = model.as_ref();
+        let encoding = Encoding::for_model(model);
+        match encoding {
+            _ if model == "gpt-3.5-turbo-0301" => {
+                Self { encoding, tokens_per_message: 4, tokens_per_name: -1 }
+            }
+            Encoding::Estimate => Self { encoding, tokens_per_message: 4, tokens_per_name: 1 },
+            _ => Self::new(encoding),
+        }
+    }
+    pub fn count_text(&self, text: impl AsRef<str>) -> usize {
+        self.encoding.count(text.as_ref())
+    }
+    /// The prompt tokens of `messages`, including the tokens that prime the reply.
+    pub fn count_messages(&self, messages: &[Message]) -> usize {
+        let mut tokens = 3;
+        for message in messages {
+            tokens += self.tokens_per_message;
+            tokens += self.count_text(message.role());
+            tokens += self.count_text(message.content());
+            if let Some(name) = message.name() {
+                tokens += self.count_text(name);
+                tokens = tokens.saturating_add_signed(self.tokens_per_name);
+            }
+        }
+        tokens
+    }
+    /// The prompt tokens of the request’s messages, plus an estimate for its
+    /// tool definitions, which are counted as their JSON.
+    pub fn count_request(&self, request: &RequestBuilder) -> usize {
+        let tools = request.tools
+            .as_ref()
+            .and_then(|x| serde_json::to_string(x).ok())
+            .map(|x| self.count_text(x))
+            .unwrap_or_default();
+        self.count_messages(&request.messages) + tools
+    }
+}
+
+impl RequestBuilder {
+    /// The prompt tokens of the request, counted with its model’s encoding
+    /// (`cl100k_base` when no model is set).
+    pub fn count_tokens(&self) -> usize {
+        let counter = self.model
+            .as_deref()
+            .map(TokenCounter::for_model)
+            .unwrap_or_default();
+        counter.count_request(self)
+    }
+}
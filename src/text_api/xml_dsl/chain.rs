@@ -0,0 +1,294 @@
+//! Prompts run one after another, each seeing the outputs of those before:
+//!
+//! ```xml
+//! <chain name="support">
+//!     <step prompt="classify" output="label"/>
+//!     <step prompt="extract" output="ticket" format="json">
+//!         <field name="customer" path="customer.name"/>
+//!     </step>
+//!     <parallel>
+//!         <step prompt="reply" output="reply"/>
+//!         <step prompt="summarize" output="summary"/>
+//!     </parallel>
+//!     <step prompt="review"/>
+//! </chain>
+//! ```
+//!
+//! Every step is rendered with the chain’s input variables plus the outputs
+//! so far. `output` names the variable the step’s text is stored in; with
+//! `format="json"` the text is parsed first, and each `<field>` stores the
+//! value at a dotted `path` (`items.0.id`). The steps of a `<parallel>` are
+//! sent together and only see the outputs from before it.
+use colored::Colorize;
+use futures::future::join_all;
+
+use super::super::client::ApiCallBuilder;
+use super::scanner::Element;
+use super::PromptCollection;
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// CHAINS
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chain {
+    pub name: String,
+    pub stages: Vec<Stage>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stage {
+    Step(Step),
+    /// Steps sent at the same time.
+    Parallel(Vec<Step>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    /// The name of the prompt to run.
+    pub prompt: String,
+    /// The variable the output is stored in.
+    pub output: Option<String>,
+    pub format: OutputFormat,
+    pub fields: Vec<Field>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    /// The output is parsed as JSON, optionally inside a Markdown code fence.
+    Json,
+}
+
+/// A value taken from a JSON output into a variable.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub name: String,
+    /// Dot-separated keys and array indices.
+    pub path: String,
+}
+
+impl Chain {
+    pub fn new(name: impl AsRef<str>, stages: Vec<Stage>) -> Self {
+        Self { name: name.as_ref().to_string(), stages }
+    }
+    /// Every step, in order.
+    pub fn steps(&self) -> impl Iterator<Item = &Step> {
+        self.stages.iter().flat_map(|x| match x {
+            Stage::Step(step) => std::slice::from_ref(step),
+            Stage::Parallel(steps) => steps.as_slice(),
+        })
+    }
+    pub(crate) fn from_element(element: &Element) -> Option<Self> {
+        let name = element.attr("name")?.to_string();
+        let stages = element.children
+            .iter()
+            .filter_map(|x| match x.name.as_str() {
+                "step" => Step::from_element(x).map(Stage::Step),
+                "parallel" => {
+                    let steps = x.children.iter().filter(|x| x.name == "step").filter_map(Step::from_element);
+                    Some(Stage::Parallel(steps.collect()))
+                }
+                _ => None,
+            })
+            .collect();
+        Some(Self { name, stages })
+    }
+}
+
+impl Step {
+    pub fn new(prompt: impl AsRef<str>) -> Self {
+        Self { prompt: prompt.as_ref().to_string(), output: None, format: OutputFormat::Text, fields: Vec::new() }
+    }
+    pub fn with_output(mut self, output: impl AsRef<str>) -> Self {
+        self.output = Some(output.as_ref().to_string());
+        self
+    }
+    pub fn with_format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+    pub fn with_field(mut self, name: impl AsRef<str>, path: impl AsRef<str>) -> Self {
+        self.fields.push(Field { name: name.as_ref().to_string(), path: path.as_ref().to_string() });
+        self
+    }
+    fn from_element(element: &Element) -> Option<Self> {
+        let format = match element.attr("format") {
+            Some("json") => OutputFormat::Json,
+            _ => OutputFormat::Text,
+        };
+        let fields = element.children
+            .iter()
+            .filter(|x| x.name == "field")
+            .filter_map(|x| Some(Field { name: x.attr("name")?.to_string(), path: x.attr("path")?.to_string() }))
+            .collect();
+        Some(Self {
+            prompt: element.attr("prompt")?.to_string(),
+            output: element.attr("output").map(str::to_string),
+            format,
+            fields,
+        })
+    }
+    /// The variables the step’s output provides.
+    pub fn variables(&self, output: &str) -> Result<Vec<(String, liquid::model::Value)>, String> {
+        let mut variables = Vec::new();
+        match self.format {
+            OutputFormat::Text => {
+                if let Some(name) = self.output.as_ref() {
+                    variables.push((name.clone(), liquid::model::Value::scalar(output.to_string())));
+                }
+            }
+            OutputFormat::Json => {
+                let json = serde_json::from_str::<serde_json::Value>(json_body(output))
+                    .map_err(|x| format!("the output isn’t JSON: {x}"))?;
+                for field in self.fields.iter() {
+                    let pointer = field.path
+                        .split('.')
+                        .map(|x| format!("/{}", x.replace('~', "~0").replace('/', "~1")))
+                        .collect::<String>();
+                    let value = json
+                        .pointer(&pointer)
+                        .ok_or_else(|| format!("the output has no {:?}", field.path))?;
+                    variables.push((field.name.clone(), to_liquid(value)?));
+                }
+                if let Some(name) = self.output.as_ref() {
+                    variables.push((name.clone(), to_liquid(&json)?));
+                }
+            }
+        }
+        Ok(variables)
+    }
+}
+
+/// The JSON in `output`, without a surrounding Markdown code fence.
+pub(crate) fn json_body(output: &str) -> &str {
+    let output = output.trim();
+    let Some(fenced) = output.strip_prefix("```").and_then(|x| x.strip_suffix("```")) else {
+        return output
+    };
+    // Skip the info string, e.g. `json`.
+    fenced.split_once('\n').map(|x| x.1).unwrap_or(fenced).trim()
+}
+
+fn to_liquid(value: &serde_json::Value) -> Result<liquid::model::Value, String> {
+    liquid::model::to_value(value).map_err(|x| x.to_string())
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// EXECUTOR
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+/// Runs chains against the API.
+pub struct ChainExecutor {
+    /// Makes a builder with the URL, key and settings for each call.
+    api_call: Box<dyn Fn() -> ApiCallBuilder>,
+}
+
+impl std::fmt::Debug for ChainExecutor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChainExecutor").finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChainRun {
+    /// The input variables and every step output.
+    pub vars: liquid::Object,
+    /// The output of every step, in order.
+    pub outputs: Vec<StepOutput>,
+}
+
+#[derive(Debug, Clone)]
+pub struct StepOutput {
+    pub prompt: String,
+    pub text: String,
+}
+
+impl ChainRun {
+    /// The output of the last step.
+    pub fn output(&self) -> Option<&str> {
+        self.outputs.last().map(|x| x.text.as_str())
+    }
+}
+
+impl ChainExecutor {
+    /// Calls the API with a builder from `api_call` (URL, key, timeout and
+    /// so on) for every step.
+    pub fn new(api_call: impl Fn() -> ApiCallBuilder + 'static) -> Self {
+        Self { api_call: Box::new(api_call) }
+    }
+    /// Runs `chain` with the prompts of `collection`, starting from `vars`.
+    pub async fn run(
+        &self,
+        chain: &Chain,
+        collection: &PromptCollection,
+        vars: &dyn liquid::ObjectView,
+    ) -> Result<ChainRun, ChainStepFailed> {
+        let mut run = ChainRun {
+            vars: vars.iter().map(|(name, value)| (name.into_owned(), value.to_value())).collect(),
+            outputs: Vec::new(),
+        };
+        for stage in chain.stages.iter() {
+            let steps = match stage {
+                Stage::Step(step) => std::slice::from_ref(step),
+                Stage::Parallel(steps) => steps.as_slice(),
+            };
+            let outputs = join_all(steps.iter().map(|x| self.complete(x, collection, &run.vars))).await;
+            for (step, output) in steps.iter().zip(outputs) {
+                let fail = |message: String| ChainStepFailed {
+                    chain: chain.name.clone(),
+                    prompt: step.prompt.clone(),
+                    message,
+                };
+                let text = output.map_err(fail)?;
+                for (name, value) in step.variables(&text).map_err(fail)? {
+                    run.vars.insert(name.into(), value);
+                }
+                run.outputs.push(StepOutput { prompt: step.prompt.clone(), text });
+            }
+        }
+        Ok(run)
+    }
+    async fn complete(&self, step: &Step, collection: &PromptCollection, vars: &liquid::Object) -> Result<String, String> {
+        let prompt = collection
+            .get(&step.prompt)
+            .ok_or_else(|| String::from("no prompt has this name"))?;
+        let request = prompt.render(vars).map_err(|x| x.to_string())?.with_stream(false);
+        let response = (self.api_call)()
+            .with_request_body(request)
+            .build_batch_api_call()
+            .ok_or("the step can’t be run without an API URL, key and model")?
+            .execute()
+            .await
+            .map_err(|x| x.to_string())?;
+        let output = response.choices
+            .first()
+            .and_then(|x| x.message.content.clone())
+            .unwrap_or_default();
+        Ok(output)
+    }
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// ERRORS
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+#[derive(Debug, Clone)]
+pub struct ChainStepFailed {
+    pub chain: String,
+    pub prompt: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for ChainStepFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let msg = format!(
+            "Error: Chain Step Failed! Step {:?} of chain {:?}: {}",
+            self.prompt,
+            self.chain,
+            self.message,
+        );
+        let msg = msg.red();
+        write!(f, "{msg}")
+    }
+}
+
+impl std::error::Error for ChainStepFailed {}
@@ -0,0 +1,609 @@
+//! Errors and warnings about prompt files, with the place they occur.
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use colored::Colorize;
+
+use super::scanner::{self, Element};
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// DIAGNOSTICS
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mode {
+    /// Problems that can be worked around are collected as warnings.
+    #[default]
+    Lenient,
+    /// Any problem fails the parse.
+    Strict,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub kind: DiagnosticKind,
+    pub file: Option<PathBuf>,
+    /// The 1-based line and column, when known.
+    pub location: Option<(usize, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiagnosticKind {
+    /// The file isn’t well-formed.
+    Syntax(String),
+    /// The Liquid template failed to parse or render.
+    Template(String),
+    /// An `<include>`d file couldn’t be read.
+    Include { src: String, message: String },
+    UnknownElement { element: String, parent: String },
+    UnknownAttribute { element: String, attribute: String },
+    MissingAttribute { element: String, attribute: String },
+    BadValue { attribute: String, value: String, expected: &'static str },
+    UnknownRole(String),
+    /// A `<parameters>` body or a JSON attribute isn’t valid JSON.
+    InvalidJson { element: String, message: String },
+    /// An `<expect>` whose value can’t be used.
+    BadExpectation { attribute: String, message: String },
+    DuplicatePromptName(String),
+    EmptyPrompt(Option<String>),
+    MissingModel(Option<String>),
+    UnknownParent { prompt: Option<String>, parent: String },
+    InheritanceCycle(String),
+    /// An `<example>` without an `<input>` or `<output>`.
+    IncompleteExample,
+    EmptyChain(Option<String>),
+    /// A chain `<step>` names a prompt the file doesn’t define.
+    UnknownStepPrompt { chain: String, prompt: String },
+    /// Two steps of a `<parallel>` store into the same variable.
+    DuplicateParallelOutput(String),
+}
+
+impl DiagnosticKind {
+    /// Whether the prompts can still be built despite the problem.
+    fn is_recoverable(&self) -> bool {
+        !matches!(
+            self,
+            DiagnosticKind::Syntax(_) | DiagnosticKind::Template(_) | DiagnosticKind::Include { .. }
+        )
+    }
+}
+
+impl std::fmt::Display for DiagnosticKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let prompt = |name: &Option<String>| match name {
+            Some(name) => format!("prompt {name:?}"),
+            None => String::from("unnamed prompt"),
+        };
+        match self {
+            DiagnosticKind::Syntax(message) => write!(f, "{message}"),
+            DiagnosticKind::Template(message) => write!(f, "template error: {message}"),
+            DiagnosticKind::Include { src, message } => write!(f, "cannot include {src:?}: {message}"),
+            DiagnosticKind::UnknownElement { element, parent } => {
+                write!(f, "unknown element <{element}> in <{parent}>")
+            }
+            DiagnosticKind::UnknownAttribute { element, attribute } => {
+                write!(f, "unknown attribute {attribute:?} on <{element}>")
+            }
+            DiagnosticKind::MissingAttribute { element, attribute } => {
+                write!(f, "<{element}> needs a {attribute:?} attribute")
+            }
+            DiagnosticKind::BadValue { attribute, value, expected } => {
+                write!(f, "{attribute}={value:?} is not {expected}")
+            }
+            DiagnosticKind::UnknownRole(role) => {
+                write!(f, "unknown role {role:?}; expected system, user, assistant, tool or function")
+            }
+            DiagnosticKind::InvalidJson { element, message } => {
+                write!(f, "<{element}> isn’t valid JSON: {message}")
+            }
+            DiagnosticKind::BadExpectation { attribute, message } => {
+                write!(f, "bad {attribute} expectation: {message}")
+            }
+            DiagnosticKind::DuplicatePromptName(name) => {
+                write!(f, "prompt name {name:?} is already used; the first one wins")
+            }
+            DiagnosticKind::EmptyPrompt(name) => write!(f, "{} has no messages", prompt(name)),
+            DiagnosticKind::MissingModel(name) => write!(f, "{} doesn’t specify a model", prompt(name)),
+            DiagnosticKind::UnknownParent { prompt: name, parent } => {
+                write!(f, "{} extends unknown prompt {parent:?}", prompt(name))
+            }
+            DiagnosticKind::InheritanceCycle(name) => {
+                write!(f, "prompt {name:?} extends itself, directly or indirectly")
+            }
+            DiagnosticKind::IncompleteExample => write!(f, "<example> needs an <input> and an <output>"),
+            DiagnosticKind::EmptyChain(Some(name)) => write!(f, "chain {name:?} has no steps"),
+            DiagnosticKind::EmptyChain(None) => write!(f, "unnamed chain has no steps"),
+            DiagnosticKind::UnknownStepPrompt { chain, prompt } => {
+                write!(f, "chain {chain:?} runs unknown prompt {prompt:?}")
+            }
+            DiagnosticKind::DuplicateParallelOutput(name) => {
+                write!(f, "more than one step of the <parallel> stores {name:?}")
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let file = self.file
+            .as_ref()
+            .map(|x| x.display().to_string())
+            .unwrap_or_else(|| String::from("<input>"));
+        match self.location {
+            Some((line, column)) => write!(f, "{file}:{line}:{column}: ")?,
+            None => write!(f, "{file}: ")?,
+        }
+        match self.severity {
+            Severity::Warning => write!(f, "warning: {}", self.kind),
+            Severity::Error => write!(f, "error: {}", self.kind),
+        }
+    }
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// COLLECTOR
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+pub(crate) struct Diagnostics<'a> {
+    pub mode: Mode,
+    pub file: Option<PathBuf>,
+    pub source: &'a str,
+    pub items: Vec<Diagnostic>,
+}
+
+impl<'a> Diagnostics<'a> {
+    pub fn new(mode: Mode, file: Option<PathBuf>, source: &'a str) -> Self {
+        Self { mode, file, source, items: Vec::new() }
+    }
+    /// Reports `kind` at byte `offset` of the source, or without a location.
+    pub fn report(&mut self, offset: Option<usize>, kind: DiagnosticKind) {
+        let location = offset.map(|x| scanner::line_column(self.source, x));
+        self.report_at(location, kind);
+    }
+    /// Reports `kind` at a line and column.
+    pub fn report_at(&mut self, location: Option<(usize, usize)>, kind: DiagnosticKind) {
+        let severity = match self.mode {
+            Mode::Lenient if kind.is_recoverable() => Severity::Warning,
+            _ => Severity::Error,
+        };
+        self.items.push(Diagnostic { severity, kind, file: self.file.clone(), location });
+    }
+    pub fn has_errors(&self) -> bool {
+        self.items.iter().any(|x| x.severity == Severity::Error)
+    }
+    /// Fails with every diagnostic if any is an error, otherwise returns the warnings.
+    pub fn finish(self) -> Result<Vec<Diagnostic>, InvalidPrompts> {
+        if self.has_errors() {
+            return Err(InvalidPrompts { diagnostics: self.items })
+        }
+        Ok(self.items)
+    }
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// VALIDATION
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+enum Expect {
+    Text,
+    Bool,
+    Integer,
+    Number,
+    /// `true`, `false`, or no value, which means `true`.
+    Flag,
+    OneOf(&'static [&'static str]),
+}
+
+const PROMPT_ATTRIBUTES: &[(&str, Expect)] = &[
+    ("name", Expect::Text),
+    ("extends", Expect::Text),
+    ("model", Expect::Text),
+    ("stream", Expect::Bool),
+    ("temperature", Expect::Number),
+    ("n", Expect::Integer),
+    ("max-tokens", Expect::Integer),
+    ("top-p", Expect::Number),
+    ("frequency-penalty", Expect::Number),
+    ("presence-penalty", Expect::Number),
+    ("logprobs", Expect::Bool),
+    ("top-logprobs", Expect::Integer),
+    ("response-format", Expect::OneOf(&["json-object", "json_object", "text"])),
+    ("seed", Expect::Integer),
+    ("stop", Expect::Text),
+    ("user", Expect::Text),
+    // `none`, `auto`, `required`, or the name of a tool.
+    ("tool-choice", Expect::Text),
+];
+
+const MESSAGE_ATTRIBUTES: &[(&str, Expect)] = &[
+    ("role", Expect::Text),
+    ("name", Expect::Text),
+    ("tool-call-id", Expect::Text),
+];
+
+const LOGIT_BIAS_ATTRIBUTES: &[(&str, Expect)] = &[
+    ("token", Expect::Integer),
+    ("value", Expect::Integer),
+];
+
+const TOOL_ATTRIBUTES: &[(&str, Expect)] = &[
+    ("name", Expect::Text),
+    ("description", Expect::Text),
+];
+
+const TEST_ATTRIBUTES: &[(&str, Expect)] = &[
+    ("name", Expect::Text),
+    ("vars", Expect::Text),
+];
+
+const EXPECT_ATTRIBUTES: &[(&str, Expect)] = &[
+    ("contains", Expect::Text),
+    ("regex", Expect::Text),
+    ("json-schema", Expect::Text),
+    ("equals", Expect::Text),
+];
+
+const EXAMPLES_ATTRIBUTES: &[(&str, Expect)] = &[
+    ("select", Expect::OneOf(&["first", "random", "similar"])),
+    ("count", Expect::Integer),
+    ("seed", Expect::Integer),
+    ("max-tokens", Expect::Integer),
+];
+
+const CHAIN_ATTRIBUTES: &[(&str, Expect)] = &[
+    ("name", Expect::Text),
+];
+
+const STEP_ATTRIBUTES: &[(&str, Expect)] = &[
+    ("prompt", Expect::Text),
+    ("output", Expect::Text),
+    ("format", Expect::OneOf(&["text", "json"])),
+];
+
+const FIELD_ATTRIBUTES: &[(&str, Expect)] = &[
+    ("name", Expect::Text),
+    ("path", Expect::Text),
+];
+
+const INCLUDE_ATTRIBUTES: &[(&str, Expect)] = &[
+    ("src", Expect::Text),
+];
+
+const VAR_ATTRIBUTES: &[(&str, Expect)] = &[
+    ("name", Expect::Text),
+    ("type", Expect::OneOf(super::variables::VariableType::NAMES)),
+    ("required", Expect::Flag),
+];
+
+/// Checks the elements of a prompt file (`parent` is `"file"`), or of a
+/// fragment included in a `<prompt>` (`"prompt"`), and every `<prompt>` in
+/// them at any depth.
+pub(crate) fn validate(elements: &[Element], parent: &str, diagnostics: &mut Diagnostics) {
+    let mut names = HashSet::new();
+    for element in elements {
+        match (parent, element.name.as_str()) {
+            (_, "include") => validate_include(element, diagnostics),
+            ("file", "prompt") => {}
+            ("file", "chain") => validate_chain(element, diagnostics),
+            ("prompt", _) => {
+                validate_prompt_child(element, diagnostics);
+            }
+            _ => diagnostics.report(Some(element.start), DiagnosticKind::UnknownElement {
+                element: element.name.clone(),
+                parent: String::from(parent),
+            }),
+        }
+        for prompt in element.descendants().into_iter().filter(|x| x.name == "prompt") {
+            validate_prompt(prompt, &mut names, diagnostics);
+        }
+    }
+}
+
+fn validate_prompt(prompt: &Element, names: &mut HashSet<String>, diagnostics: &mut Diagnostics) {
+    validate_attributes(prompt, PROMPT_ATTRIBUTES, diagnostics);
+    let name = prompt.attr("name").map(str::to_string);
+    if let Some(name) = name.as_ref() {
+        if !names.insert(name.clone()) {
+            diagnostics.report(Some(prompt.start), DiagnosticKind::DuplicatePromptName(name.clone()));
+        }
+    }
+    // Inherited and included parts are checked once they’re resolved.
+    let extends = prompt.attr("extends").is_some();
+    if prompt.attr("model").is_none() && !extends {
+        diagnostics.report(Some(prompt.start), DiagnosticKind::MissingModel(name.clone()));
+    }
+    let mut has_messages = false;
+    for child in prompt.children.iter() {
+        has_messages |= validate_prompt_child(child, diagnostics);
+    }
+    if !has_messages && !extends {
+        diagnostics.report(Some(prompt.start), DiagnosticKind::EmptyPrompt(name));
+    }
+}
+
+/// Returns whether the child may provide messages.
+fn validate_prompt_child(child: &Element, diagnostics: &mut Diagnostics) -> bool {
+    match child.name.as_str() {
+        "message" => {
+            validate_message(child, diagnostics);
+            true
+        }
+        "include" => {
+            validate_include(child, diagnostics);
+            true
+        }
+        "var" => {
+            validate_var(child, diagnostics);
+            false
+        }
+        "stop" => {
+            validate_attributes(child, &[], diagnostics);
+            false
+        }
+        "logit-bias" => {
+            validate_attributes(child, LOGIT_BIAS_ATTRIBUTES, diagnostics);
+            require_attribute(child, "token", diagnostics);
+            require_attribute(child, "value", diagnostics);
+            false
+        }
+        "tool" => {
+            validate_tool(child, diagnostics);
+            false
+        }
+        "test" => {
+            validate_test(child, diagnostics);
+            false
+        }
+        "examples" => {
+            validate_examples(child, diagnostics);
+            false
+        }
+        "prompt" => false,
+        _ => {
+            diagnostics.report(Some(child.start), DiagnosticKind::UnknownElement {
+                element: child.name.clone(),
+                parent: String::from("prompt"),
+            });
+            false
+        }
+    }
+}
+
+fn validate_tool(tool: &Element, diagnostics: &mut Diagnostics) {
+    validate_attributes(tool, TOOL_ATTRIBUTES, diagnostics);
+    require_attribute(tool, "name", diagnostics);
+    for child in tool.children.iter() {
+        if child.name != "parameters" {
+            diagnostics.report(Some(child.start), DiagnosticKind::UnknownElement {
+                element: child.name.clone(),
+                parent: String::from("tool"),
+            });
+            continue
+        }
+        validate_attributes(child, &[], diagnostics);
+        let text = child.text.as_deref().unwrap_or_default();
+        if let Err(error) = serde_json::from_str::<serde_json::Value>(text) {
+            diagnostics.report(Some(child.start), DiagnosticKind::InvalidJson {
+                element: child.name.clone(),
+                message: error.to_string(),
+            });
+        }
+    }
+}
+
+fn validate_test(test: &Element, diagnostics: &mut Diagnostics) {
+    validate_attributes(test, TEST_ATTRIBUTES, diagnostics);
+    require_attribute(test, "name", diagnostics);
+    if let Some(vars) = test.attribute("vars") {
+        let is_object = serde_json::from_str::<serde_json::Value>(vars.value.as_deref().unwrap_or_default())
+            .map_err(|x| x.to_string())
+            .and_then(|x| x.is_object().then_some(()).ok_or_else(|| String::from("expected an object")));
+        if let Err(message) = is_object {
+            diagnostics.report(Some(vars.start), DiagnosticKind::InvalidJson {
+                element: String::from("test vars"),
+                message,
+            });
+        }
+    }
+    let directory = diagnostics.file.as_deref().and_then(std::path::Path::parent).map(|x| x.to_path_buf());
+    for child in test.children.iter() {
+        if child.name != "expect" {
+            diagnostics.report(Some(child.start), DiagnosticKind::UnknownElement {
+                element: child.name.clone(),
+                parent: String::from("test"),
+            });
+            continue
+        }
+        validate_attributes(child, EXPECT_ATTRIBUTES, diagnostics);
+        if child.attributes.is_empty() {
+            require_attribute(child, "contains", diagnostics);
+        }
+        for attribute in child.attributes.iter() {
+            let Some(value) = attribute.value.as_deref() else { continue };
+            let expectation = super::testing::Expectation::from_attribute(&attribute.name, value, directory.as_deref());
+            if let (true, Err(message)) = (EXPECT_ATTRIBUTES.iter().any(|(x, _)| *x == attribute.name), expectation) {
+                diagnostics.report(Some(attribute.start), DiagnosticKind::BadExpectation {
+                    attribute: attribute.name.clone(),
+                    message,
+                });
+            }
+        }
+    }
+}
+
+fn validate_examples(examples: &Element, diagnostics: &mut Diagnostics) {
+    validate_attributes(examples, EXAMPLES_ATTRIBUTES, diagnostics);
+    // Without a seed every run would silently pick the same examples.
+    if examples.attr("select") == Some("random") {
+        require_attribute(examples, "seed", diagnostics);
+    }
+    for example in examples.children.iter() {
+        if example.name != "example" {
+            report_unknown(example, "examples", diagnostics);
+            continue
+        }
+        validate_attributes(example, &[], diagnostics);
+        for child in example.children.iter() {
+            match child.name.as_str() {
+                "input" | "output" => validate_attributes(child, &[], diagnostics),
+                _ => report_unknown(child, "example", diagnostics),
+            }
+        }
+        let has = |name: &str| example.children.iter().any(|x| x.name == name);
+        if !has("input") || !has("output") {
+            diagnostics.report(Some(example.start), DiagnosticKind::IncompleteExample);
+        }
+    }
+}
+
+fn validate_chain(chain: &Element, diagnostics: &mut Diagnostics) {
+    validate_attributes(chain, CHAIN_ATTRIBUTES, diagnostics);
+    require_attribute(chain, "name", diagnostics);
+    let mut has_steps = false;
+    for child in chain.children.iter() {
+        match child.name.as_str() {
+            "step" => validate_step(child, diagnostics),
+            "parallel" => {
+                validate_attributes(child, &[], diagnostics);
+                let mut outputs = Vec::new();
+                for step in child.children.iter() {
+                    match step.name.as_str() {
+                        "step" => validate_step(step, diagnostics),
+                        _ => report_unknown(step, "parallel", diagnostics),
+                    }
+                    // Which step’s value would win depends on the order the
+                    // results are stored in.
+                    let Some(output) = step.attribute("output") else { continue };
+                    let name = output.value.clone().unwrap_or_default();
+                    if outputs.contains(&name) {
+                        diagnostics.report(Some(output.start), DiagnosticKind::DuplicateParallelOutput(name));
+                    } else {
+                        outputs.push(name);
+                    }
+                }
+            }
+            _ => {
+                report_unknown(child, "chain", diagnostics);
+                continue
+            }
+        }
+        has_steps = true;
+    }
+    if !has_steps {
+        diagnostics.report(Some(chain.start), DiagnosticKind::EmptyChain(chain.attr("name").map(str::to_string)));
+    }
+}
+
+fn validate_step(step: &Element, diagnostics: &mut Diagnostics) {
+    validate_attributes(step, STEP_ATTRIBUTES, diagnostics);
+    require_attribute(step, "prompt", diagnostics);
+    for child in step.children.iter() {
+        if child.name != "field" {
+            report_unknown(child, "step", diagnostics);
+            continue
+        }
+        validate_attributes(child, FIELD_ATTRIBUTES, diagnostics);
+        require_attribute(child, "name", diagnostics);
+        require_attribute(child, "path", diagnostics);
+        if step.attr("format") != Some("json") {
+            diagnostics.report(Some(child.start), DiagnosticKind::MissingAttribute {
+                element: String::from("step"),
+                attribute: String::from("format=\"json\""),
+            });
+        }
+    }
+}
+
+fn report_unknown(element: &Element, parent: &str, diagnostics: &mut Diagnostics) {
+    diagnostics.report(Some(element.start), DiagnosticKind::UnknownElement {
+        element: element.name.clone(),
+        parent: String::from(parent),
+    });
+}
+
+fn validate_include(include: &Element, diagnostics: &mut Diagnostics) {
+    validate_attributes(include, INCLUDE_ATTRIBUTES, diagnostics);
+    require_attribute(include, "src", diagnostics);
+}
+
+fn validate_message(message: &Element, diagnostics: &mut Diagnostics) {
+    validate_attributes(message, MESSAGE_ATTRIBUTES, diagnostics);
+    if let Some(role) = message.attribute("role") {
+        let value = role.value.clone().unwrap_or_default();
+        if !matches!(value.as_str(), "system" | "user" | "assistant" | "tool" | "function") {
+            diagnostics.report(Some(role.start), DiagnosticKind::UnknownRole(value));
+        }
+    }
+}
+
+fn validate_var(var: &Element, diagnostics: &mut Diagnostics) {
+    validate_attributes(var, VAR_ATTRIBUTES, diagnostics);
+    require_attribute(var, "name", diagnostics);
+}
+
+fn require_attribute(element: &Element, attribute: &str, diagnostics: &mut Diagnostics) {
+    if element.attr(attribute).is_none() {
+        diagnostics.report(Some(element.start), DiagnosticKind::MissingAttribute {
+            element: element.name.clone(),
+            attribute: String::from(attribute),
+        });
+    }
+}
+
+fn validate_attributes(element: &Element, known: &[(&str, Expect)], diagnostics: &mut Diagnostics) {
+    for attribute in element.attributes.iter() {
+        let Some((_, expect)) = known.iter().find(|(name, _)| *name == attribute.name) else {
+            diagnostics.report(Some(attribute.start), DiagnosticKind::UnknownAttribute {
+                element: element.name.clone(),
+                attribute: attribute.name.clone(),
+            });
+            continue
+        };
+        let value = attribute.value.as_deref().unwrap_or_default();
+        let expected = match expect {
+            Expect::Text => None,
+            Expect::Bool => value.parse::<bool>().err().map(|_| "true or false"),
+            Expect::Flag if attribute.value.is_none() => None,
+            Expect::Flag => value.parse::<bool>().err().map(|_| "true or false"),
+            Expect::Integer => value.parse::<super::super::common::Integer>().err().map(|_| "an integer"),
+            Expect::Number => value.parse::<super::super::common::Number>().err().map(|_| "a number"),
+            Expect::OneOf(values) => {
+                let is_known = values.contains(&value.to_lowercase().as_str());
+                (!is_known).then_some("one of the supported values")
+            }
+        };
+        if let Some(expected) = expected {
+            diagnostics.report(Some(attribute.start), DiagnosticKind::BadValue {
+                attribute: attribute.name.clone(),
+                value: value.to_string(),
+                expected,
+            });
+        }
+    }
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// ERRORS
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+#[derive(Debug, Clone)]
+pub struct InvalidPrompts {
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl std::fmt::Display for InvalidPrompts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let diagnostics = self.diagnostics
+            .iter()
+            .map(|x| format!("\n  {x}"))
+            .collect::<String>();
+        let msg = format!("Error: Invalid Prompts!{diagnostics}");
+        let msg = msg.red();
+        write!(f, "{msg}")
+    }
+}
+
+impl std::error::Error for InvalidPrompts {}
@@ -0,0 +1,212 @@
+//! Few-shot examples, of which a selection is sent with each call:
+//!
+//! ```xml
+//! <prompt name="classify" model="gpt-4o-mini">
+//!     <message role="system">Label the ticket.</message>
+//!     <examples select="similar" count="3" max-tokens="400">
+//!         <example>
+//!             <input>I was charged twice</input>
+//!             <output>billing</output>
+//!         </example>
+//!         …
+//!     </examples>
+//!     <message role="user">{{ ticket }}</message>
+//! </prompt>
+//! ```
+//!
+//! `select` is `first` (the default), `random` (which needs a `seed`), or
+//! `similar`: the examples whose input is closest to the last user message,
+//! by embedding. At most `count` are chosen, and examples that would take
+//! the total over `max-tokens` are skipped. The chosen examples become
+//! user/assistant pairs, in file order, after the leading system messages.
+//! They are sent as written, without rendering.
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use super::super::request::{Message, RequestBuilder};
+use super::super::tokens::TokenCounter;
+use crate::embeddings_api::client::ClientConfiguration;
+use crate::embeddings_api::request::{Model, RequestBuilder as EmbeddingRequestBuilder};
+use crate::embeddings_api::vector_store::{EmbeddingCountMismatch, Metric};
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// EXAMPLES
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+#[derive(Debug, Clone, PartialEq)]
+pub struct Examples {
+    pub examples: Vec<Example>,
+    pub selection: Selection,
+    /// The most examples to send; all of them if `None`.
+    pub count: Option<usize>,
+    /// The most tokens the examples may take up.
+    pub max_tokens: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Example {
+    pub input: String,
+    pub output: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Selection {
+    #[default]
+    First,
+    /// The same seed picks the same examples.
+    Random { seed: u64 },
+    /// Needs embeddings; see [`super::Prompt::render_with_embeddings`].
+    Similar,
+}
+
+impl Example {
+    pub fn new(input: impl AsRef<str>, output: impl AsRef<str>) -> Self {
+        Self { input: input.as_ref().to_string(), output: output.as_ref().to_string() }
+    }
+    /// The user and assistant messages.
+    pub fn messages(&self) -> [Message; 2] {
+        [Message::user(self.input.clone()), Message::assistant(self.output.clone())]
+    }
+}
+
+impl Examples {
+    pub fn new(examples: Vec<Example>) -> Self {
+        Self { examples, selection: Selection::First, count: None, max_tokens: None }
+    }
+    pub fn with_selection(mut self, selection: Selection) -> Self {
+        self.selection = selection;
+        self
+    }
+    pub fn with_count(mut self, count: usize) -> Self {
+        self.count = Some(count);
+        self
+    }
+    pub fn with_max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+    /// The chosen examples, in file order. `similarities` holds a score for
+    /// each example, and is only used by [`Selection::Similar`].
+    pub fn select(&self, counter: &TokenCounter, similarities: Option<&[f32]>) -> Vec<&Example> {
+        let mut order = (0..self.examples.len()).collect::<Vec<_>>();
+        match (self.selection, similarities) {
+            (Selection::First, _) | (Selection::Similar, None) => {}
+            (Selection::Random { seed }, _) => shuffle(&mut order, seed),
+            (Selection::Similar, Some(similarities)) => {
+                let score = |x: &usize| similarities.get(*x).copied().unwrap_or(f32::MIN);
+                order.sort_by(|a, b| score(b).total_cmp(&score(a)));
+            }
+        }
+        let mut chosen = Vec::new();
+        let mut tokens = 0;
+        for index in order {
+            if chosen.len() == self.count.unwrap_or(usize::MAX) {
+                break
+            }
+            // Without the 3 tokens that prime the reply, which are counted once.
+            let example_tokens = counter.count_messages(&self.examples[index].messages()) - 3;
+            if self.max_tokens.map(|x| tokens + example_tokens > x).unwrap_or(false) {
+                continue
+            }
+            tokens += example_tokens;
+            chosen.push(index);
+        }
+        chosen.sort();
+        chosen.into_iter().map(|x| &self.examples[x]).collect()
+    }
+    /// Inserts the chosen examples after the leading system messages.
+    pub(crate) fn apply(&self, request: &mut RequestBuilder, similarities: Option<&[f32]>) {
+        let counter = request.model
+            .as_deref()
+            .map(TokenCounter::for_model)
+            .unwrap_or_default();
+        let messages = self.select(&counter, similarities)
+            .into_iter()
+            .flat_map(Example::messages)
+            .collect::<Vec<_>>();
+        let position = request.messages
+            .iter()
+            .take_while(|x| matches!(x, Message::System { .. }))
+            .count();
+        request.messages.splice(position..position, messages);
+    }
+}
+
+/// A Fisher–Yates shuffle driven by SplitMix64, so that a seed gives the same
+/// order on every platform and version.
+fn shuffle(items: &mut [usize], seed: u64) {
+    let mut state = seed;
+    let mut next = || {
+        state = state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    };
+    for i in (1..items.len()).rev() {
+        let j = (next() % (i as u64 + 1)) as usize;
+        items.swap(i, j);
+    }
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// EMBEDDINGS
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+/// Embeds texts for [`Selection::Similar`], keeping the examples’ vectors so
+/// that each example is only embedded once.
+pub struct ExampleEmbedder {
+    configuration: ClientConfiguration,
+    model: String,
+    cache: Mutex<HashMap<String, Vec<f32>>>,
+}
+
+impl std::fmt::Debug for ExampleEmbedder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExampleEmbedder")
+            .field("model", &self.model)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ExampleEmbedder {
+    pub fn new(configuration: ClientConfiguration, model: Model) -> Self {
+        Self { configuration, model: model.0, cache: Mutex::new(HashMap::new()) }
+    }
+    /// The cosine similarity of each example’s input to `query`. The query is
+    /// embedded on every call; only the examples’ vectors are kept.
+    pub async fn similarities(&self, examples: &Examples, query: &str) -> Result<Vec<f32>, Box<dyn std::error::Error>> {
+        let mut missing = examples.examples
+            .iter()
+            .map(|x| x.input.clone())
+            .filter(|x| !self.cache.lock().unwrap().contains_key(x.as_str()))
+            .collect::<Vec<_>>();
+        missing.sort();
+        missing.dedup();
+        let mut texts = missing.clone();
+        texts.push(query.to_string());
+        let mut vectors = self.embed(texts).await?;
+        let query = vectors.pop().unwrap_or_default();
+        let mut cache = self.cache.lock().unwrap();
+        cache.extend(missing.into_iter().zip(vectors));
+        let similarities = examples.examples
+            .iter()
+            .map(|x| cache.get(&x.input).map(|x| Metric::Cosine.score(&query, x)).unwrap_or_default())
+            .collect();
+        Ok(similarities)
+    }
+    /// The vectors of `texts`, in order.
+    async fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, Box<dyn std::error::Error>> {
+        let documents = texts.len();
+        let response = EmbeddingRequestBuilder::default()
+            .with_input(texts)
+            .with_model(self.model.as_str())
+            .build()
+            .ok_or("nothing to embed")?
+            .execute_batched(&self.configuration, 256)
+            .await?;
+        let vectors = response.vectors()?;
+        if vectors.len() != documents {
+            return Err(Box::new(EmbeddingCountMismatch { documents, embeddings: vectors.len() }))
+        }
+        Ok(vectors)
+    }
+}
@@ -0,0 +1,294 @@
+//! Writes prompts back to the DSL, so that parsing the output (without
+//! globals) gives the same prompts again.
+//!
+//! Message bodies are written as plain text, with markup wrapped in CDATA,
+//! when the parser would read them back unchanged, and as CDATA alone
+//! otherwise, which is also how verbatim bodies are kept from rendering.
+//! Inheritance and includes aren’t reconstructed; every prompt is written
+//! out in full.
+use std::fmt::Write;
+
+use super::super::request::{Message, RequestBuilder, ToolChoice, ToolChoiceMode};
+use super::chain::{Chain, OutputFormat, Stage, Step};
+use super::examples::{Examples, Selection};
+use super::{Prompt, PromptCollection};
+
+const INDENT: &str = "    ";
+
+impl Prompt {
+    /// The prompt as a `<prompt>` element.
+    pub fn to_dsl(&self) -> String {
+        let mut out = String::new();
+        write_prompt(&mut out, self);
+        out
+    }
+}
+
+impl PromptCollection {
+    /// Every prompt and then every chain, separated by blank lines.
+    pub fn to_dsl(&self) -> String {
+        let chains = self.chains.iter().map(|chain| {
+            let mut out = String::new();
+            write_chain(&mut out, chain);
+            out
+        });
+        self.prompts
+            .iter()
+            .map(Prompt::to_dsl)
+            .chain(chains)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+    pub fn save(&self, file_path: impl AsRef<std::path::Path>) -> Result<(), Box<dyn std::error::Error>> {
+        let file_path = file_path.as_ref();
+        if let Some(parent) = file_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(file_path, self.to_dsl())?;
+        Ok(())
+    }
+}
+
+fn write_prompt(out: &mut String, prompt: &Prompt) {
+    let request = &prompt.request;
+    out.push_str("<prompt");
+    if let Some(name) = prompt.name.as_ref() {
+        write_attribute(out, "name", name);
+    }
+    for (name, value) in attributes(request) {
+        write_attribute(out, name, &value);
+    }
+    out.push_str(">\n");
+    for variable in prompt.variables.iter() {
+        let _ = write!(out, "{INDENT}<var");
+        write_attribute(out, "name", &variable.name);
+        write_attribute(out, "type", variable.kind.name());
+        if variable.required {
+            out.push_str(" required");
+        }
+        out.push_str("/>\n");
+    }
+    for stop in request.stop.iter().flatten() {
+        let _ = writeln!(out, "{INDENT}<stop>{}</stop>", raw_text("stop", stop));
+    }
+    let mut logit_bias = request.logit_bias.iter().flatten().collect::<Vec<_>>();
+    logit_bias.sort();
+    for (token, value) in logit_bias {
+        let _ = write!(out, "{INDENT}<logit-bias");
+        write_attribute(out, "token", token);
+        write_attribute(out, "value", &value.to_string());
+        out.push_str("/>\n");
+    }
+    for tool in request.tools.iter().flatten() {
+        let _ = write!(out, "{INDENT}<tool");
+        write_attribute(out, "name", &tool.function.name);
+        if let Some(description) = tool.function.description.as_ref() {
+            write_attribute(out, "description", description);
+        }
+        match tool.function.parameters.as_ref() {
+            Some(parameters) => {
+                let json = serde_json::to_string(parameters).unwrap_or_default();
+                let _ = write!(out, ">\n{INDENT}{INDENT}<parameters>{}</parameters>\n{INDENT}</tool>\n", raw_text("parameters", &json));
+            }
+            None => out.push_str("/>\n"),
+        }
+    }
+    for (index, message) in request.messages.iter().enumerate() {
+        let verbatim = !prompt.templated || prompt.verbatim_messages.contains(&index);
+        write_message(out, message, verbatim);
+    }
+    if let Some(examples) = prompt.examples.as_ref() {
+        write_examples(out, examples);
+    }
+    for test in prompt.tests.iter() {
+        let _ = write!(out, "{INDENT}<test");
+        write_attribute(out, "name", &test.name);
+        if !test.vars.is_empty() {
+            write_attribute(out, "vars", &serde_json::Value::Object(test.vars.clone()).to_string());
+        }
+        out.push_str(">\n");
+        for expectation in test.expectations.iter() {
+            let _ = write!(out, "{INDENT}{INDENT}<expect");
+            write_attribute(out, expectation.attribute(), &expectation.value());
+            out.push_str("/>\n");
+        }
+        let _ = writeln!(out, "{INDENT}</test>");
+    }
+    out.push_str("</prompt>\n");
+}
+
+fn write_chain(out: &mut String, chain: &Chain) {
+    out.push_str("<chain");
+    write_attribute(out, "name", &chain.name);
+    out.push_str(">\n");
+    for stage in chain.stages.iter() {
+        match stage {
+            Stage::Step(step) => write_step(out, step, INDENT),
+            Stage::Parallel(steps) => {
+                let _ = writeln!(out, "{INDENT}<parallel>");
+                for step in steps {
+                    write_step(out, step, &INDENT.repeat(2));
+                }
+                let _ = writeln!(out, "{INDENT}</parallel>");
+            }
+        }
+    }
+    out.push_str("</chain>\n");
+}
+
+fn write_step(out: &mut String, step: &Step, indent: &str) {
+    let _ = write!(out, "{indent}<step");
+    write_attribute(out, "prompt", &step.prompt);
+    if let Some(output) = step.output.as_ref() {
+        write_attribute(out, "output", output);
+    }
+    if step.format == OutputFormat::Json {
+        write_attribute(out, "format", "json");
+    }
+    if step.fields.is_empty() {
+        out.push_str("/>\n");
+        return
+    }
+    out.push_str(">\n");
+    for field in step.fields.iter() {
+        let _ = write!(out, "{indent}{INDENT}<field");
+        write_attribute(out, "name", &field.name);
+        write_attribute(out, "path", &field.path);
+        out.push_str("/>\n");
+    }
+    let _ = writeln!(out, "{indent}</step>");
+}
+
+/// The request’s attributes in the order the parser lists them.
+fn attributes(request: &RequestBuilder) -> Vec<(&'static str, String)> {
+    let tool_choice = request.tool_choice.as_ref().map(|x| match x {
+        ToolChoice::Mode(ToolChoiceMode::None) => String::from("none"),
+        ToolChoice::Mode(ToolChoiceMode::Auto) => String::from("auto"),
+        ToolChoice::Mode(ToolChoiceMode::Required) => String::from("required"),
+        ToolChoice::Function { function, .. } => function.name.clone(),
+    });
+    let response_format = request.response_format.as_ref().map(|x| match x.is_json_object() {
+        true => String::from("json-object"),
+        false => String::from("text"),
+    });
+    [
+        ("model", request.model.clone()),
+        ("stream", request.stream.map(|x| x.to_string())),
+        ("temperature", request.temperature.map(|x| x.to_string())),
+        ("n", request.n.map(|x| x.to_string())),
+        ("max-tokens", request.max_tokens.map(|x| x.to_string())),
+        ("top-p", request.top_p.map(|x| x.to_string())),
+        ("frequency-penalty", request.frequency_penalty.map(|x| x.to_string())),
+        ("presence-penalty", request.presence_penalty.map(|x| x.to_string())),
+        ("logprobs", request.logprobs.map(|x| x.to_string())),
+        ("top-logprobs", request.top_logprobs.map(|x| x.to_string())),
+        ("response-format", response_format),
+        ("seed", request.seed.map(|x| x.to_string())),
+        ("user", request.user.clone()),
+        ("tool-choice", tool_choice),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| Some((name, value?)))
+    .collect()
+}
+
+/// `verbatim` messages are written as CDATA alone, so they aren’t rendered.
+fn write_message(out: &mut String, message: &Message, verbatim: bool) {
+    let _ = write!(out, "{INDENT}<message");
+    write_attribute(out, "role", message.role());
+    match message {
+        Message::Tool { tool_call_id, .. } => write_attribute(out, "tool-call-id", tool_call_id),
+        _ => {
+            if let Some(name) = message.name() {
+                write_attribute(out, "name", name);
+            }
+        }
+    }
+    out.push('>');
+    match verbatim {
+        true => out.push_str(&cdata(message.content())),
+        false => write_body(out, "message", message.content(), INDENT),
+    }
+    out.push_str("</message>\n");
+}
+
+fn write_examples(out: &mut String, examples: &Examples) {
+    let _ = write!(out, "{INDENT}<examples");
+    match examples.selection {
+        Selection::First => {}
+        Selection::Random { seed } => {
+            write_attribute(out, "select", "random");
+            write_attribute(out, "seed", &seed.to_string());
+        }
+        Selection::Similar => write_attribute(out, "select", "similar"),
+    }
+    if let Some(count) = examples.count {
+        write_attribute(out, "count", &count.to_string());
+    }
+    if let Some(max_tokens) = examples.max_tokens {
+        write_attribute(out, "max-tokens", &max_tokens.to_string());
+    }
+    out.push_str(">\n");
+    let indent = INDENT.repeat(2);
+    let body_indent = INDENT.repeat(3);
+    for example in examples.examples.iter() {
+        let _ = writeln!(out, "{indent}<example>");
+        for (element, content) in [("input", &example.input), ("output", &example.output)] {
+            let _ = write!(out, "{body_indent}<{element}>");
+            write_body(out, element, content, &body_indent);
+            let _ = writeln!(out, "</{element}>");
+        }
+        let _ = writeln!(out, "{indent}</example>");
+    }
+    let _ = writeln!(out, "{INDENT}</examples>");
+}
+
+/// The body of an `element` opened at `indent`. Markup in it is wrapped in
+/// CDATA, leaving the rest as plain text; bodies that the parser’s unindent
+/// would change are written as CDATA alone, and so read back verbatim.
+fn write_body(out: &mut String, element: &str, content: &str, indent: &str) {
+    if super::unindent_body(content) != content {
+        out.push_str(&cdata(content));
+        return
+    }
+    let end_tag = format!("</{element}");
+    let content = content
+        .replace("<![CDATA[", "<![CDATA[<![CDATA[]]>")
+        .replace(&end_tag, &format!("<![CDATA[{end_tag}]]>"));
+    if content.contains('\n') {
+        // Indented to match, which the parser’s unindent removes again.
+        out.push('\n');
+        for line in content.split('\n') {
+            if !line.is_empty() {
+                let _ = write!(out, "{indent}{INDENT}{line}");
+            }
+            out.push('\n');
+        }
+        out.push_str(indent);
+    } else {
+        out.push_str(&content);
+    }
+}
+
+/// Raw text that is kept exactly (`<stop>`, `<parameters>`).
+fn raw_text(element: &str, text: &str) -> String {
+    let has_markup = text.contains(&format!("</{element}")) || text.contains("<![CDATA[");
+    match has_markup {
+        true => cdata(text),
+        false => text.to_string(),
+    }
+}
+
+/// `text` as CDATA, split wherever it contains `]]>`.
+fn cdata(text: &str) -> String {
+    format!("<![CDATA[{}]]>", text.replace("]]>", "]]]]><![CDATA[>"))
+}
+
+fn write_attribute(out: &mut String, name: &str, value: &str) {
+    let value = value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;");
+    let _ = write!(out, " {name}=\"{value}\"");
+}
@@ -0,0 +1,154 @@
+//! Loads a prompt file together with the files it pulls in: Liquid partials
+//! (`{% include "header.liquid" %}`) and `<include src="…"/>` elements, both
+//! resolved relative to the file that references them.
+//!
+//! An `<include>` is replaced by the elements of the included file, so it can
+//! stand for whole prompts at the top level, or for messages and `<var>`s
+//! inside a `<prompt>`. Included files are rendered with the same globals and
+//! validated on their own, so their diagnostics point into them.
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+
+use super::diagnostics::{self, Diagnostic, DiagnosticKind, Diagnostics};
+use super::scanner::{self, Element};
+use super::Parser;
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// PARTIALS
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+/// Liquid partials read from files relative to a directory.
+#[derive(Debug, Clone)]
+struct FilePartials {
+    directory: PathBuf,
+}
+
+impl liquid::partials::PartialSource for FilePartials {
+    fn contains(&self, name: &str) -> bool {
+        self.directory.join(name).is_file()
+    }
+    fn names(&self) -> Vec<&str> {
+        Vec::new()
+    }
+    fn try_get<'a>(&'a self, name: &str) -> Option<Cow<'a, str>> {
+        std::fs::read_to_string(self.directory.join(name)).ok().map(Cow::Owned)
+    }
+}
+
+/// A Liquid parser whose partials are read relative to `directory`, or to
+/// the working directory.
+pub(crate) fn liquid_parser(directory: Option<&Path>) -> Result<liquid::Parser, liquid::Error> {
+    let directory = directory.map(Path::to_path_buf).unwrap_or_default();
+    let partials = liquid::partials::LazyCompiler::new(FilePartials { directory });
+    liquid::ParserBuilder::with_stdlib()
+        .partials(partials)
+        .build()
+}
+
+/// The directory references in `file` are relative to.
+pub(crate) fn directory_of(file: Option<&Path>) -> Option<&Path> {
+    file.and_then(Path::parent)
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// LOADER
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+pub(crate) struct Loader<'p, 'a> {
+    parser: &'p Parser<'a>,
+    /// The files being loaded, outermost first, to catch include cycles.
+    stack: Vec<PathBuf>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl<'p, 'a> Loader<'p, 'a> {
+    pub fn new(parser: &'p Parser<'a>) -> Self {
+        Self { parser, stack: Vec::new(), diagnostics: Vec::new() }
+    }
+    /// The elements of `source` with its includes expanded. `parent` is
+    /// `"file"` for a prompt file, or `"prompt"` for a fragment included in one.
+    pub fn load(&mut self, source: &str, file: Option<&Path>, parent: &str) -> Vec<Element> {
+        let owner = file.map(Path::to_path_buf);
+        let source = match self.parser.globals {
+            Some(globals) => match render_template(source, file, globals) {
+                Ok(source) => source,
+                Err(error) => {
+                    let message = error.to_string();
+                    let mut diagnostics = Diagnostics::new(self.parser.mode, owner, source);
+                    diagnostics.report_at(template_location(&message), DiagnosticKind::Template(message));
+                    self.diagnostics.extend(diagnostics.items);
+                    return Vec::new()
+                }
+            },
+            None => source.to_string(),
+        };
+        let mut diagnostics = Diagnostics::new(self.parser.mode, owner, &source);
+        let elements = scanner::scan(&source).unwrap_or_else(|error| {
+            diagnostics.report(Some(error.offset), DiagnosticKind::Syntax(error.message));
+            Vec::new()
+        });
+        diagnostics::validate(&elements, parent, &mut diagnostics);
+        if let Some(file) = file {
+            self.stack.push(canonical(file));
+        }
+        let elements = self.expand(elements, file, parent, &mut diagnostics);
+        if file.is_some() {
+            self.stack.pop();
+        }
+        self.diagnostics.extend(diagnostics.items);
+        elements
+    }
+    fn expand(
+        &mut self,
+        elements: Vec<Element>,
+        file: Option<&Path>,
+        parent: &str,
+        diagnostics: &mut Diagnostics,
+    ) -> Vec<Element> {
+        let mut result = Vec::new();
+        for mut element in elements {
+            if element.name != "include" {
+                element.children = self.expand(element.children, file, &element.name, diagnostics);
+                result.push(element);
+                continue
+            }
+            let Some(src) = element.attr("src") else {
+                continue
+            };
+            let path = directory_of(file).unwrap_or(Path::new("")).join(src);
+            let problem = if self.stack.contains(&canonical(&path)) {
+                Err(String::from("the file includes itself"))
+            } else {
+                std::fs::read_to_string(&path).map_err(|x| x.to_string())
+            };
+            match problem {
+                Ok(source) => result.extend(self.load(&source, Some(&path), parent)),
+                Err(message) => diagnostics.report(Some(element.start), DiagnosticKind::Include {
+                    src: src.to_string(),
+                    message,
+                }),
+            }
+        }
+        result
+    }
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Liquid reports syntax errors as ` --> line:column`.
+fn template_location(message: &str) -> Option<(usize, usize)> {
+    let (_, rest) = message.split_once("--> ")?;
+    let (line, rest) = rest.split_once(':')?;
+    let column = rest.split(|c: char| !c.is_ascii_digit()).next()?;
+    Some((line.trim().parse().ok()?, column.parse().ok()?))
+}
+
+fn render_template(
+    source: &str,
+    file: Option<&Path>,
+    globals: &dyn liquid::ObjectView,
+) -> Result<String, liquid::Error> {
+    liquid_parser(directory_of(file))?
+        .parse(source)?
+        .render(&globals)
+}
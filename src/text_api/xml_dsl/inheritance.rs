@@ -0,0 +1,107 @@
+//! `<prompt name="b" extends="a">`: a prompt that starts from another one.
+//!
+//! The child’s attributes override the parent’s (other than `name`), and its
+//! `<var>`s override those of the same name. System messages come first —
+//! the child’s if it has any, otherwise the parent’s — followed by the
+//! parent’s other messages and then the child’s. `<stop>`, `<logit-bias>`,
+//! `<tool>` and `<examples>` elements are inherited unless the child has some
+//! of the same kind. `<test>`s aren’t inherited. Parents may themselves
+//! extend other prompts, and may come from included files.
+use std::collections::HashMap;
+
+use super::diagnostics::{DiagnosticKind, Diagnostics};
+use super::scanner::Element;
+
+/// Every prompt in `prompts` with its `extends` chain merged in. Prompts with
+/// an unknown parent, or in a cycle, are kept as written.
+pub(crate) fn resolve(prompts: &[&Element], diagnostics: &mut Diagnostics) -> Vec<Element> {
+    let mut by_name = HashMap::new();
+    for prompt in prompts {
+        if let Some(name) = prompt.attr("name") {
+            by_name.entry(name).or_insert(*prompt);
+        }
+    }
+    let mut resolver = Resolver { by_name, resolved: HashMap::new(), visiting: Vec::new(), diagnostics };
+    prompts.iter().map(|prompt| resolver.resolve(prompt)).collect()
+}
+
+struct Resolver<'e, 'd, 's> {
+    by_name: HashMap<&'e str, &'e Element>,
+    resolved: HashMap<String, Element>,
+    visiting: Vec<String>,
+    diagnostics: &'d mut Diagnostics<'s>,
+}
+
+impl<'e, 'd, 's> Resolver<'e, 'd, 's> {
+    fn resolve(&mut self, prompt: &Element) -> Element {
+        let Some(parent_name) = prompt.attr("extends") else {
+            return prompt.clone()
+        };
+        let name = prompt.attr("name").map(str::to_string);
+        if let Some(name) = name.as_ref() {
+            if let Some(resolved) = self.resolved.get(name) {
+                return resolved.clone()
+            }
+            if self.visiting.contains(name) {
+                self.diagnostics.report(Some(prompt.start), DiagnosticKind::InheritanceCycle(name.clone()));
+                return prompt.clone()
+            }
+        }
+        let Some(parent) = self.by_name.get(parent_name).copied() else {
+            self.diagnostics.report(Some(prompt.start), DiagnosticKind::UnknownParent {
+                prompt: name,
+                parent: parent_name.to_string(),
+            });
+            return prompt.clone()
+        };
+        self.visiting.extend(name.clone());
+        let parent = self.resolve(parent);
+        let merged = merge(&parent, prompt);
+        if let Some(name) = name {
+            self.visiting.retain(|x| *x != name);
+            self.resolved.insert(name, merged.clone());
+        }
+        merged
+    }
+}
+
+fn merge(parent: &Element, child: &Element) -> Element {
+    let mut attributes = parent.attributes
+        .iter()
+        .filter(|x| !matches!(x.name.as_str(), "name" | "extends"))
+        .filter(|x| child.attribute(&x.name).is_none())
+        .cloned()
+        .collect::<Vec<_>>();
+    attributes.extend(child.attributes.iter().filter(|x| x.name != "extends").cloned());
+    let is_system = |x: &&Element| x.name == "message" && x.attr("role") == Some("system");
+    let is_other_message = |x: &&Element| x.name == "message" && x.attr("role") != Some("system");
+    let child_vars = child.children
+        .iter()
+        .filter(|x| x.name == "var")
+        .filter_map(|x| x.attr("name"))
+        .collect::<Vec<_>>();
+    let mut children = parent.children
+        .iter()
+        .filter(|x| x.name == "var")
+        .filter(|x| x.attr("name").map(|name| !child_vars.contains(&name)).unwrap_or(true))
+        .cloned()
+        .collect::<Vec<_>>();
+    children.extend(child.children.iter().filter(|x| x.name == "var").cloned());
+    let child_systems = child.children.iter().filter(is_system).collect::<Vec<_>>();
+    if child_systems.is_empty() {
+        children.extend(parent.children.iter().filter(is_system).cloned());
+    } else {
+        children.extend(child_systems.into_iter().cloned());
+    }
+    children.extend(parent.children.iter().filter(is_other_message).cloned());
+    children.extend(child.children.iter().filter(is_other_message).cloned());
+    for kind in ["stop", "logit-bias", "tool", "examples"] {
+        let owner = match child.children.iter().any(|x| x.name == kind) {
+            true => child,
+            false => parent,
+        };
+        children.extend(owner.children.iter().filter(|x| x.name == kind).cloned());
+    }
+    children.extend(child.children.iter().filter(|x| x.name == "test").cloned());
+    Element { name: child.name.clone(), attributes, children, text: None, verbatim: false, start: child.start }
+}
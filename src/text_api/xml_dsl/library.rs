@@ -0,0 +1,307 @@
+//! Every `*.prompt.liquid` file under a directory, with prompts addressed by
+//! their file’s path relative to the root and their name:
+//! `support/triage#classify` for `<prompt name="classify">` in
+//! `support/triage.prompt.liquid`. A bare name (`classify`) works too, as
+//! long as only one file defines it.
+//!
+//! Files are parsed without globals; render prompts per call with
+//! [`Prompt::render`].
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock, RwLockReadGuard};
+use std::time::{Duration, SystemTime};
+
+use colored::Colorize;
+
+use super::{Diagnostic, DiagnosticKind, InvalidPrompts, Mode, Parser, Prompt, Severity};
+
+const EXTENSION: &str = ".prompt.liquid";
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// LIBRARY
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+#[derive(Debug, Clone)]
+pub struct PromptLibrary {
+    root: PathBuf,
+    mode: Mode,
+    /// By namespace, e.g. `support/triage`.
+    files: BTreeMap<String, LibraryFile>,
+    /// A fingerprint of every file under the root, prompt files or not,
+    /// since includes and partials can be anywhere.
+    snapshot: HashMap<PathBuf, Fingerprint>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct LibraryFile {
+    prompts: Vec<Prompt>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// A bare prompt name defined by more than one file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Collision {
+    pub name: String,
+    /// The full keys of every prompt with the name.
+    pub keys: Vec<String>,
+}
+
+impl PromptLibrary {
+    /// Loads every prompt file under `root`. Files with errors are left out
+    /// and their diagnostics kept; see [`PromptLibrary::diagnostics`].
+    pub fn open(root: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::open_with_mode(root, Mode::Lenient)
+    }
+    /// In strict mode any diagnostic, and any collision, fails the load.
+    pub fn open_with_mode(root: impl AsRef<Path>, mode: Mode) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut library = Self {
+            root: root.as_ref().to_path_buf(),
+            mode,
+            files: BTreeMap::new(),
+            snapshot: HashMap::new(),
+        };
+        library.reload()?;
+        if mode == Mode::Strict {
+            let diagnostics = library.diagnostics().into_iter().cloned().collect::<Vec<_>>();
+            if !diagnostics.is_empty() {
+                return Err(Box::new(InvalidPrompts { diagnostics }))
+            }
+            let collisions = library.collisions();
+            if !collisions.is_empty() {
+                return Err(Box::new(PromptCollisions { collisions }))
+            }
+        }
+        Ok(library)
+    }
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+    /// A prompt by `path#name`, or by bare name if it is unique.
+    pub fn get(&self, key: impl AsRef<str>) -> Option<&Prompt> {
+        let key = key.as_ref();
+        if let Some((namespace, name)) = key.split_once('#') {
+            return self.files
+                .get(namespace)?
+                .prompts
+                .iter()
+                .find(|x| x.name.as_deref() == Some(name))
+        }
+        let mut matches = self.prompts().filter(|(_, x)| x.name.as_deref() == Some(key));
+        let (_, prompt) = matches.next()?;
+        matches.next().is_none().then_some(prompt)
+    }
+    /// Every named prompt with its full key, ordered by key.
+    pub fn prompts(&self) -> impl Iterator<Item = (String, &Prompt)> {
+        self.files.iter().flat_map(|(namespace, file)| {
+            file.prompts
+                .iter()
+                .filter_map(move |x| Some((format!("{namespace}#{}", x.name.as_ref()?), x)))
+        })
+    }
+    /// The namespaces of the loaded files.
+    pub fn namespaces(&self) -> impl Iterator<Item = &str> {
+        self.files.keys().map(String::as_str)
+    }
+    /// Warnings, and the errors of files that failed to load.
+    pub fn diagnostics(&self) -> Vec<&Diagnostic> {
+        self.files.values().flat_map(|x| x.diagnostics.iter()).collect()
+    }
+    /// Bare names defined by more than one file, which can then only be
+    /// looked up by their full key.
+    pub fn collisions(&self) -> Vec<Collision> {
+        let mut by_name: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for (key, prompt) in self.prompts() {
+            if let Some(name) = prompt.name.as_deref() {
+                by_name.entry(name).or_default().push(key);
+            }
+        }
+        by_name
+            .into_iter()
+            .filter(|(_, keys)| keys.len() > 1)
+            .map(|(name, keys)| Collision { name: name.to_string(), keys })
+            .collect()
+    }
+    /// Whether any file under the root was added, changed or removed since
+    /// the last load.
+    pub fn has_changes(&self) -> Result<bool, std::io::Error> {
+        Ok(snapshot(&self.root, &self.snapshot)? != self.snapshot)
+    }
+    /// Re-reads the prompt files that changed, returning their namespaces.
+    /// Everything is re-read when another file changes, as it may be an
+    /// include or partial. A file that no longer reads or parses keeps its
+    /// previous prompts, with the new diagnostics.
+    pub fn reload(&mut self) -> Result<Vec<String>, std::io::Error> {
+        let snapshot = snapshot(&self.root, &self.snapshot)?;
+        let is_prompt_file = |path: &Path| path.to_string_lossy().ends_with(EXTENSION);
+        let others_changed = snapshot
+            .iter()
+            .filter(|(path, _)| !is_prompt_file(path))
+            .any(|(path, modified)| self.snapshot.get(path) != Some(modified))
+            || self.snapshot.keys().any(|x| !is_prompt_file(x) && !snapshot.contains_key(x));
+        let mut reloaded = Vec::new();
+        let mut files = BTreeMap::new();
+        for (path, modified) in snapshot.iter().filter(|(path, _)| is_prompt_file(path)) {
+            let namespace = self.namespace(path);
+            let previous = self.files.remove(&namespace);
+            let unchanged = self.snapshot.get(path) == Some(modified) && !others_changed;
+            let file = match previous {
+                Some(previous) if unchanged => previous,
+                previous => {
+                    reloaded.push(namespace.clone());
+                    self.load_file(path, previous)
+                }
+            };
+            files.insert(namespace, file);
+        }
+        self.files = files;
+        self.snapshot = snapshot;
+        reloaded.sort();
+        Ok(reloaded)
+    }
+    fn load_file(&self, path: &Path, previous: Option<LibraryFile>) -> LibraryFile {
+        let parser = Parser { mode: self.mode, globals: None };
+        let source = match std::fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(error) => {
+                let diagnostic = Diagnostic {
+                    severity: Severity::Error,
+                    kind: DiagnosticKind::Include { src: path.display().to_string(), message: error.to_string() },
+                    file: Some(path.to_path_buf()),
+                    location: None,
+                };
+                return LibraryFile {
+                    prompts: previous.map(|x| x.prompts).unwrap_or_default(),
+                    diagnostics: vec![diagnostic],
+                }
+            }
+        };
+        match parser.parse_source(&source, Some(path)) {
+            Ok(collection) => LibraryFile {
+                prompts: collection.prompts,
+                diagnostics: collection.diagnostics,
+            },
+            Err(error) => LibraryFile {
+                prompts: previous.map(|x| x.prompts).unwrap_or_default(),
+                diagnostics: error.diagnostics,
+            },
+        }
+    }
+    /// `support/triage` for `<root>/support/triage.prompt.liquid`.
+    fn namespace(&self, path: &Path) -> String {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        let relative = relative
+            .components()
+            .map(|x| x.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        relative.strip_suffix(EXTENSION).unwrap_or(&relative).to_string()
+    }
+    /// Polls the root every `interval` on a background thread, reloading
+    /// changed files. The thread stops once every handle is dropped.
+    pub fn watch(self, interval: Duration) -> WatchedLibrary {
+        let library = Arc::new(RwLock::new(self));
+        let weak = Arc::downgrade(&library);
+        std::thread::spawn(move || loop {
+            std::thread::sleep(interval);
+            let Some(library) = weak.upgrade() else {
+                return
+            };
+            let has_changes = library.read().unwrap().has_changes().unwrap_or(false);
+            if has_changes {
+                let _ = library.write().unwrap().reload();
+            }
+        });
+        WatchedLibrary { library }
+    }
+}
+
+/// What a file is compared by: its length and a hash of its contents, so
+/// that a file touched without being edited isn’t reloaded. The modification
+/// time only decides whether the contents are hashed again.
+#[derive(Debug, Clone)]
+struct Fingerprint {
+    modified: SystemTime,
+    len: u64,
+    hash: u64,
+}
+
+impl PartialEq for Fingerprint {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.hash == other.hash
+    }
+}
+
+impl Eq for Fingerprint {}
+
+/// The fingerprint of every file under `root`. Files whose modification time
+/// and length match `previous` keep its hash instead of being read again.
+fn snapshot(root: &Path, previous: &HashMap<PathBuf, Fingerprint>) -> Result<HashMap<PathBuf, Fingerprint>, std::io::Error> {
+    let mut files = HashMap::new();
+    let mut directories = vec![root.to_path_buf()];
+    while let Some(directory) = directories.pop() {
+        for entry in std::fs::read_dir(&directory)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_dir() {
+                directories.push(entry.path());
+            } else {
+                let path = entry.path();
+                let modified = metadata.modified()?;
+                let len = metadata.len();
+                let hash = match previous.get(&path) {
+                    Some(x) if x.modified == modified && x.len == len => x.hash,
+                    _ => {
+                        let mut hasher = DefaultHasher::new();
+                        // Unreadable files are reported when they’re loaded.
+                        std::fs::read(&path).unwrap_or_default().hash(&mut hasher);
+                        hasher.finish()
+                    }
+                };
+                files.insert(path, Fingerprint { modified, len, hash });
+            }
+        }
+    }
+    Ok(files)
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// WATCHED LIBRARY
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+/// A shared handle to a library that reloads itself; see [`PromptLibrary::watch`].
+#[derive(Debug, Clone)]
+pub struct WatchedLibrary {
+    library: Arc<RwLock<PromptLibrary>>,
+}
+
+impl WatchedLibrary {
+    /// A copy of the current version of a prompt.
+    pub fn get(&self, key: impl AsRef<str>) -> Option<Prompt> {
+        self.read().get(key).cloned()
+    }
+    /// The current state of the library. Reloads wait while this is held.
+    pub fn read(&self) -> RwLockReadGuard<'_, PromptLibrary> {
+        self.library.read().unwrap()
+    }
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// ERRORS
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+#[derive(Debug, Clone)]
+pub struct PromptCollisions {
+    pub collisions: Vec<Collision>,
+}
+
+impl std::fmt::Display for PromptCollisions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let collisions = self.collisions
+            .iter()
+            .map(|x| format!("\n  {:?} is defined as {}", x.name, x.keys.join(", ")))
+            .collect::<String>();
+        let msg = format!("Error: Prompt Collisions!{collisions}");
+        let msg = msg.red();
+        write!(f, "{msg}")
+    }
+}
+
+impl std::error::Error for PromptCollisions {}
@@ -0,0 +1,540 @@
+use std::{collections::HashMap, path::{Path, PathBuf}, str::FromStr};
+
+pub use liquid::object;
+
+pub mod chain;
+pub mod diagnostics;
+pub mod examples;
+pub mod variables;
+mod export;
+mod includes;
+mod inheritance;
+pub mod library;
+mod schema;
+pub mod testing;
+mod scanner;
+
+pub use chain::{Chain, ChainExecutor, ChainRun};
+pub use examples::{Example, ExampleEmbedder, Examples, Selection};
+pub use diagnostics::{Diagnostic, DiagnosticKind, InvalidPrompts, Mode, Severity};
+pub use variables::{InvalidVariables, Variable, VariableProblem, VariableType};
+pub use library::{PromptLibrary, WatchedLibrary};
+pub use testing::{Cassette, Expectation, TestCase, TestReport, TestRunner};
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// PARSER
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+/// How prompt files are read: whether problems fail the parse, and the
+/// Liquid globals, if the file is a template.
+#[derive(Clone, Copy, Default)]
+pub struct Parser<'a> {
+    pub mode: Mode,
+    pub globals: Option<&'a dyn liquid::ObjectView>,
+}
+
+impl<'a> Parser<'a> {
+    /// Collects recoverable problems as warnings on the collection.
+    pub fn lenient() -> Self {
+        Self { mode: Mode::Lenient, globals: None }
+    }
+    /// Fails on any problem, including unknown attributes and bad values.
+    pub fn strict() -> Self {
+        Self { mode: Mode::Strict, globals: None }
+    }
+    /// Renders the source as a Liquid template with `globals` before parsing it.
+    /// Locations then refer to the rendered text. Partials are read relative
+    /// to the file.
+    pub fn with_globals(mut self, globals: &'a dyn liquid::ObjectView) -> Self {
+        self.globals = Some(globals);
+        self
+    }
+    pub fn open(&self, file_path: impl AsRef<Path>) -> Result<PromptCollection, Box<dyn std::error::Error>> {
+        let file_path = file_path.as_ref();
+        let source = std::fs::read_to_string(file_path)?;
+        let collection = self.parse_source(&source, Some(file_path))?;
+        Ok(collection)
+    }
+    pub fn parse(&self, contents: impl AsRef<str>) -> Result<PromptCollection, InvalidPrompts> {
+        self.parse_source(contents.as_ref(), None)
+    }
+    fn parse_source(&self, source: &str, file_path: Option<&Path>) -> Result<PromptCollection, InvalidPrompts> {
+        let mut loader = includes::Loader::new(self);
+        let elements = loader.load(source, file_path, "file");
+        let prompts = elements
+            .iter()
+            .flat_map(scanner::Element::descendants)
+            .filter(|x| x.name == "prompt")
+            .collect::<Vec<_>>();
+        let mut diagnostics = diagnostics::Diagnostics::new(self.mode, file_path.map(Path::to_path_buf), source);
+        diagnostics.items = loader.diagnostics;
+        let prompts = inheritance::resolve(&prompts, &mut diagnostics);
+        let chains = elements
+            .iter()
+            .flat_map(scanner::Element::descendants)
+            .filter(|x| x.name == "chain")
+            .filter_map(chain::Chain::from_element)
+            .collect::<Vec<_>>();
+        let names = prompts.iter().filter_map(|x| x.attr("name")).collect::<Vec<_>>();
+        for chain in chains.iter() {
+            for step in chain.steps().filter(|x| !names.contains(&x.prompt.as_str())) {
+                diagnostics.report(None, DiagnosticKind::UnknownStepPrompt {
+                    chain: chain.name.clone(),
+                    prompt: step.prompt.clone(),
+                });
+            }
+        }
+        let warnings = diagnostics.finish()?;
+        let prompts = prompts
+            .iter()
+            .filter_map(|x| process_prompt_element(x, includes::directory_of(file_path)))
+            .map(|prompt| Prompt { file: file_path.map(Path::to_path_buf), templated: self.globals.is_none(), ..prompt })
+            .collect::<Vec<_>>();
+        Ok(PromptCollection { prompts, chains, diagnostics: warnings })
+    }
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// PROMPT COLLECTION
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+#[derive(Debug, Clone)]
+pub struct PromptCollection {
+    prompts: Vec<Prompt>,
+    chains: Vec<Chain>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl PromptCollection {
+    pub fn new(prompts: Vec<Prompt>) -> Self {
+        Self { prompts, chains: Vec::new(), diagnostics: Vec::new() }
+    }
+    pub fn with_chains(mut self, chains: Vec<Chain>) -> Self {
+        self.chains = chains;
+        self
+    }
+    pub fn prompts(&self) -> &[Prompt] {
+        &self.prompts
+    }
+    pub fn chains(&self) -> &[Chain] {
+        &self.chains
+    }
+    pub fn chain(&self, name: impl AsRef<str>) -> Option<&Chain> {
+        self.chains.iter().find(|x| x.name == name.as_ref())
+    }
+    pub fn open(file_path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        Parser::lenient().open(file_path)
+    }
+    pub fn open_with(
+        file_path: impl AsRef<Path>,
+        globals: &dyn liquid::ObjectView
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Parser::lenient().with_globals(globals).open(file_path)
+    }
+    pub fn parse_with(
+        contents: impl AsRef<str>,
+        globals: &dyn liquid::ObjectView
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let collection = Parser::lenient().with_globals(globals).parse(contents)?;
+        Ok(collection)
+    }
+    pub fn parse(contents: impl AsRef<str>) -> Result<Self, Box<dyn std::error::Error>> {
+        let collection = Parser::lenient().parse(contents)?;
+        Ok(collection)
+    }
+    /// The warnings found while parsing in lenient mode.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+    pub fn get(&self, target_name: impl AsRef<str>) -> Option<Prompt> {
+        let target_name = target_name.as_ref();
+        for prompt in self.prompts.iter() {
+            let is_match = prompt.name
+                .as_ref()
+                .map(|name| name == target_name)
+                .unwrap_or(false);
+            if is_match {
+                return Some(prompt.clone());
+            }
+        }
+        None
+    }
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// PROMPT
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+#[derive(Debug, Clone)]
+pub struct Prompt {
+    pub name: Option<String>,
+    pub request: super::request::RequestBuilder,
+    /// The `<var>` declarations. Message contents are Liquid templates over these.
+    pub variables: Vec<Variable>,
+    /// The file the prompt was read from. Partials are read relative to it.
+    pub file: Option<PathBuf>,
+    /// The `<test>` cases; see [`TestRunner`].
+    pub tests: Vec<TestCase>,
+    /// The few-shot examples to choose from on each call.
+    pub examples: Option<Examples>,
+    /// Whether the messages are still Liquid templates, which [`Prompt::render`]
+    /// renders. `false` once the file was rendered with globals.
+    pub templated: bool,
+    /// The indices of messages whose body is only CDATA. They are sent as
+    /// written, without rendering.
+    pub verbatim_messages: Vec<usize>,
+}
+
+impl Prompt {
+    pub fn new(name: impl AsRef<str>, request: super::request::RequestBuilder) -> Self {
+        let name = Some(name.as_ref().to_string());
+        Self {
+            name,
+            request,
+            variables: Vec::new(),
+            file: None,
+            tests: Vec::new(),
+            examples: None,
+            templated: true,
+            verbatim_messages: Vec::new(),
+        }
+    }
+    pub fn with_variables(mut self, variables: Vec<Variable>) -> Self {
+        self.variables = variables;
+        self
+    }
+    pub fn with_tests(mut self, tests: Vec<TestCase>) -> Self {
+        self.tests = tests;
+        self
+    }
+    pub fn with_examples(mut self, examples: Examples) -> Self {
+        self.examples = Some(examples);
+        self
+    }
+    pub fn open(file_path: impl AsRef<Path>, prompt_name: impl AsRef<str>) -> Result<Self, Box<dyn std::error::Error>> {
+        let prompt_name = prompt_name.as_ref();
+        let collection = PromptCollection::open(file_path)?;
+        let prompt = collection.get(prompt_name)
+            .ok_or(Box::new(PromptNotFound(prompt_name.to_string())))?;
+        Ok(prompt)
+    }
+    pub fn open_with(file_path: impl AsRef<Path>, prompt_name: impl AsRef<str>, globals: &dyn liquid::ObjectView) -> Result<Self, Box<dyn std::error::Error>> {
+        let prompt_name = prompt_name.as_ref();
+        let collection = PromptCollection::open_with(file_path, globals)?;
+        let prompt = collection.get(prompt_name)
+            .ok_or(Box::new(PromptNotFound(prompt_name.to_string())))?;
+        Ok(prompt)
+    }
+    pub fn parse_with(contents: impl AsRef<str>, prompt_name: impl AsRef<str>, globals: &dyn liquid::ObjectView) -> Result<Self, Box<dyn std::error::Error>> {
+        let prompt_name = prompt_name.as_ref();
+        let collection = PromptCollection::parse_with(contents, globals)?;
+        let prompt = collection.get(prompt_name)
+            .ok_or(Box::new(PromptNotFound(prompt_name.to_string())))?;
+        Ok(prompt)
+    }
+    pub fn parse(contents: impl AsRef<str>, prompt_name: impl AsRef<str>) -> Result<Self, Box<dyn std::error::Error>> {
+        let prompt_name = prompt_name.as_ref();
+        let collection = PromptCollection::parse(contents)?;
+        let prompt = collection.get(prompt_name)
+            .ok_or(Box::new(PromptNotFound(prompt_name.to_string())))?;
+        Ok(prompt)
+    }
+    /// Checks `vars` against the prompt’s `<var>` declarations.
+    pub fn check_variables(&self, vars: &dyn liquid::ObjectView) -> Result<(), InvalidVariables> {
+        let problems = variables::check(&self.variables, vars);
+        if !problems.is_empty() {
+            return Err(InvalidVariables { prompt: self.name.clone(), problems })
+        }
+        Ok(())
+    }
+    /// The request with every message rendered as a Liquid template with
+    /// `vars`, after checking them against the declarations. Optional
+    /// variables that aren’t given are nil.
+    ///
+    /// Parse the file without globals so the templates are kept for this;
+    /// the prompt can then be rendered any number of times. A prompt that was
+    /// rendered with globals isn’t rendered again, and bodies that are only
+    /// CDATA are never rendered.
+    ///
+    /// Examples chosen by similarity are taken in file order instead; use
+    /// [`Prompt::render_with_embeddings`] for those.
+    pub fn render(&self, vars: &dyn liquid::ObjectView) -> Result<super::request::RequestBuilder, Box<dyn std::error::Error>> {
+        let mut request = self.render_messages(vars)?;
+        if let Some(examples) = self.examples.as_ref() {
+            examples.apply(&mut request, None);
+        }
+        Ok(request)
+    }
+    /// Like [`Prompt::render`], but chooses `similar` examples by comparing
+    /// their inputs to the last user message.
+    pub async fn render_with_embeddings(
+        &self,
+        vars: &dyn liquid::ObjectView,
+        embedder: &ExampleEmbedder,
+    ) -> Result<super::request::RequestBuilder, Box<dyn std::error::Error>> {
+        let mut request = self.render_messages(vars)?;
+        let Some(examples) = self.examples.as_ref() else {
+            return Ok(request)
+        };
+        let similarities = match examples.selection {
+            Selection::Similar => {
+                let query = request.messages
+                    .iter()
+                    .rev()
+                    .find(|x| matches!(x, super::request::Message::User { .. }))
+                    .map(|x| x.content().to_string())
+                    .unwrap_or_default();
+                Some(embedder.similarities(examples, &query).await?)
+            }
+            _ => None,
+        };
+        examples.apply(&mut request, similarities.as_deref());
+        Ok(request)
+    }
+    fn render_messages(&self, vars: &dyn liquid::ObjectView) -> Result<super::request::RequestBuilder, Box<dyn std::error::Error>> {
+        self.check_variables(vars)?;
+        let vars = variables::with_optionals(&self.variables, vars);
+        let parser = includes::liquid_parser(includes::directory_of(self.file.as_deref()))?;
+        let mut request = self.request.clone();
+        if !self.templated {
+            return Ok(request)
+        }
+        for (index, message) in request.messages.iter_mut().enumerate() {
+            if self.verbatim_messages.contains(&index) {
+                continue
+            }
+            let content = parser.parse(message.content())?.render(&vars)?;
+            *message.content_mut() = content;
+        }
+        Ok(request)
+    }
+    pub fn client_builder(self) -> super::client::ApiCallBuilder {
+        super::client::ApiCallBuilder::default()
+            .with_request_body(self.request)
+    }
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// ERRORS
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+#[derive(Debug, Clone)]
+pub struct PromptNotFound(pub String);
+impl std::fmt::Display for PromptNotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Cannot find prompt: {:?}.", self.0)
+    }
+}
+impl std::error::Error for PromptNotFound {}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// PROMPT ELEMENTS
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+/// `directory` is where `json-schema` files of tests are read from.
+fn process_prompt_element(element: &scanner::Element, directory: Option<&Path>) -> Option<Prompt> {
+    let name = element.attr("name")
+        .map(str::to_string);
+    let model = element.attr("model")
+        .map(str::to_string);
+    let stream = element.attr("stream")
+        .and_then(|x| bool::from_str(&x).ok());
+    let temperature = element.attr("temperature")
+        .and_then(|x| f32::from_str(&x).ok());
+    let n = element.attr("n")
+        .and_then(|x| super::common::Integer::from_str(&x).ok());
+    let max_tokens = element.attr("max-tokens")
+        .and_then(|x| super::common::Integer::from_str(&x).ok());
+    let top_p = element.attr("top-p")
+        .and_then(|x| f32::from_str(&x).ok());
+    let frequency_penalty = element.attr("frequency-penalty")
+        .and_then(|x| f32::from_str(&x).ok());
+    let presence_penalty = element.attr("presence-penalty")
+        .and_then(|x| f32::from_str(&x).ok());
+    let logprobs = element.attr("logprobs")
+        .and_then(|x| bool::from_str(&x).ok());
+    let top_logprobs = element.attr("top-logprobs")
+        .and_then(|x| super::common::Integer::from_str(&x).ok());
+    let response_format = element
+        .attr("response-format")
+        .and_then(|x| {
+            match x.to_lowercase().as_str() {
+                "json-object" => Some(super::request::ResponseFormat::JSON_OBJECT),
+                "json_object" => Some(super::request::ResponseFormat::JSON_OBJECT),
+                "text" => Some(super::request::ResponseFormat::TEXT),
+                _ => None
+            }
+        });
+    let seed = element.attr("seed")
+        .and_then(|x| super::common::Integer::from_str(x).ok());
+    let user = element.attr("user")
+        .map(str::to_string);
+    let tool_choice = element.attr("tool-choice")
+        .map(|x| {
+            match x.to_lowercase().as_str() {
+                "none" => super::request::ToolChoice::NONE,
+                "auto" => super::request::ToolChoice::AUTO,
+                "required" => super::request::ToolChoice::REQUIRED,
+                _ => super::request::ToolChoice::function(x),
+            }
+        });
+    // Stop sequences are kept exactly, whitespace included.
+    let stop = element.attr("stop")
+        .map(str::to_string)
+        .into_iter()
+        .chain(element.children.iter().filter(|x| x.name == "stop").filter_map(|x| x.text.clone()))
+        .collect::<Vec<_>>();
+    let logit_bias = element.children
+        .iter()
+        .filter(|x| x.name == "logit-bias")
+        .filter_map(|x| {
+            let token = x.attr("token")?.to_string();
+            let value = isize::from_str(x.attr("value")?).ok()?;
+            Some((token, value))
+        })
+        .collect::<HashMap<_, _>>();
+    let tools = element.children
+        .iter()
+        .filter(|x| x.name == "tool")
+        .filter_map(|tool_element| {
+            let name = tool_element.attr("name")?;
+            let description = tool_element.attr("description").map(str::to_string);
+            let parameters = tool_element.children
+                .iter()
+                .find(|x| x.name == "parameters")
+                .and_then(|x| serde_json::from_str(x.text.as_deref()?).ok());
+            Some(super::request::Tool::function(name, description, parameters))
+        })
+        .collect::<Vec<_>>();
+    // - * -
+    let mut request = super::request::RequestBuilder::default();
+    request.model = model;
+    request.stream = stream;
+    request.temperature = temperature;
+    request.n = n;
+    request.max_tokens = max_tokens;
+    request.top_p = top_p;
+    request.frequency_penalty = frequency_penalty;
+    request.presence_penalty = presence_penalty;
+    request.logprobs = logprobs;
+    request.top_logprobs = top_logprobs;
+    request.response_format = response_format;
+    request.seed = seed;
+    request.user = user;
+    request.tool_choice = tool_choice;
+    request.stop = (!stop.is_empty()).then_some(stop);
+    request.logit_bias = (!logit_bias.is_empty()).then_some(logit_bias);
+    request.tools = (!tools.is_empty()).then_some(tools);
+    // - * -
+    let verbatim_messages = element.children
+        .iter()
+        .filter(|x| x.name == "message")
+        .enumerate()
+        .filter(|(_, x)| x.verbatim)
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    let messages = element.children
+        .iter()
+        .filter(|x| x.name == "message")
+        .map(|message_element| -> super::request::Message {
+            let content = message_content(message_element);
+            let role: &str = message_element.attr("role").unwrap_or("user");
+            let name = message_element.attr("name");
+            match (role, name) {
+                ("system", None) => super::request::Message::system(content),
+                ("system", Some(name)) => super::request::Message::named_system(name, content),
+                ("assistant", None) => super::request::Message::assistant(content),
+                ("assistant", Some(name)) => super::request::Message::named_assistant(name, content),
+                ("tool", _) => {
+                    let tool_call_id = message_element.attr("tool-call-id").unwrap_or_default();
+                    super::request::Message::tool(content, tool_call_id)
+                }
+                ("function", name) => super::request::Message::function(content, name.unwrap_or_default()),
+                (_, Some(name)) => super::request::Message::named_user(name, content),
+                (_, None) => super::request::Message::user(content),
+            }
+        })
+        .collect::<Vec<_>>();
+    request.messages = messages;
+    // - * -
+    let variables = element.children
+        .iter()
+        .filter(|x| x.name == "var")
+        .filter_map(|var_element| {
+            let name = var_element.attr("name")?;
+            let kind = var_element.attr("type")
+                .and_then(VariableType::from_name)
+                .unwrap_or_default();
+            let required = var_element.attribute("required")
+                .map(|x| x.value.as_deref().map(|x| x != "false").unwrap_or(true))
+                .unwrap_or(false);
+            Some(Variable::new(name, kind).with_required(required))
+        })
+        .collect::<Vec<_>>();
+    let tests = element.children
+        .iter()
+        .filter(|x| x.name == "test")
+        .map(|test_element| {
+            let name = test_element.attr("name").unwrap_or_default().to_string();
+            let vars = test_element.attr("vars")
+                .and_then(|x| serde_json::from_str(x).ok())
+                .unwrap_or_default();
+            let expectations = test_element.children
+                .iter()
+                .filter(|x| x.name == "expect")
+                .flat_map(|x| x.attributes.iter())
+                .filter_map(|x| testing::Expectation::from_attribute(&x.name, x.value.as_deref()?, directory).ok())
+                .collect::<Vec<_>>();
+            TestCase { name, vars, expectations }
+        })
+        .collect::<Vec<_>>();
+    let examples = element.children
+        .iter()
+        .find(|x| x.name == "examples")
+        .map(|examples_element| {
+            let selection = match examples_element.attr("select") {
+                Some("random") => {
+                    let seed = examples_element.attr("seed").and_then(|x| u64::from_str(x).ok());
+                    Selection::Random { seed: seed.unwrap_or_default() }
+                }
+                Some("similar") => Selection::Similar,
+                _ => Selection::First,
+            };
+            let examples = examples_element.children
+                .iter()
+                .filter(|x| x.name == "example")
+                .filter_map(|example_element| {
+                    let body = |name: &str| example_element.children.iter().find(|x| x.name == name).map(message_content);
+                    Some(Example { input: body("input")?, output: body("output")? })
+                })
+                .collect::<Vec<_>>();
+            Examples {
+                examples,
+                selection,
+                count: examples_element.attr("count").and_then(|x| usize::from_str(x).ok()),
+                max_tokens: examples_element.attr("max-tokens").and_then(|x| usize::from_str(x).ok()),
+            }
+        });
+    // - * -
+    let prompt = Prompt {
+        name,
+        request,
+        variables,
+        file: None,
+        tests,
+        examples,
+        templated: true,
+        verbatim_messages,
+    };
+    Some(prompt)
+}
+
+/// A message body as sent: trimmed, with the indentation common to its lines
+/// (other than the first) removed, and otherwise exactly as written. Bodies
+/// that are only CDATA are used as they are.
+fn message_content(element: &scanner::Element) -> String {
+    let body = element.text.as_deref().unwrap_or_default();
+    if element.verbatim {
+        return body.to_string()
+    }
+    unindent_body(body)
+}
+
+pub(crate) fn unindent_body(body: &str) -> String {
+    unindent::unindent(body.trim())
+}
@@ -0,0 +1,313 @@
+//! A small scanner for the DSL’s XML-like syntax that keeps the byte offset
+//! of every element and attribute, so problems can be reported by line and
+//! column.
+//!
+//! It is deliberately forgiving where HTML is: unknown `<!…>` and `<?…>`
+//! constructs are skipped, attributes may be unquoted or have no value, and
+//! a `<` that doesn’t start a tag is plain text.
+//!
+//! `<message>`, `<stop>`, `<parameters>`, `<input>` and `<output>` bodies are
+//! raw text: they aren’t scanned for tags and entities aren’t decoded, so code
+//! and markup come through exactly as written. A body runs until the first
+//! matching end tag; wrap text containing that in `<![CDATA[…]]>`, whose
+//! markers are removed. A body that is nothing but CDATA sections (and
+//! whitespace around them) is marked verbatim. Attribute values do decode the
+//! XML entities (`&amp;`, `&lt;`, `&gt;`, `&quot;`, `&apos;`, `&#…;`).
+/// Elements whose bodies are text, not markup.
+const RAW_TEXT_ELEMENTS: &[&str] = &["message", "stop", "parameters", "input", "output"];
+
+#[derive(Debug, Clone)]
+pub(crate) struct Element {
+    pub name: String,
+    pub attributes: Vec<Attribute>,
+    pub children: Vec<Element>,
+    /// The body of a raw-text element, with CDATA sections unwrapped.
+    pub text: Option<String>,
+    /// Whether `text` came only from CDATA sections, so it should be used
+    /// exactly, without trimming or unindenting.
+    pub verbatim: bool,
+    /// The offset of the opening `<`.
+    pub start: usize,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Attribute {
+    pub name: String,
+    /// `None` for attributes written without `=`.
+    pub value: Option<String>,
+    /// The offset of the attribute name.
+    pub start: usize,
+}
+
+impl Element {
+    pub fn attribute(&self, name: &str) -> Option<&Attribute> {
+        self.attributes.iter().find(|x| x.name == name)
+    }
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attribute(name).and_then(|x| x.value.as_deref())
+    }
+    /// This element and all of its descendants, depth first.
+    pub fn descendants(&self) -> Vec<&Element> {
+        let mut result = vec![self];
+        for child in self.children.iter() {
+            result.extend(child.descendants());
+        }
+        result
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct SyntaxError {
+    pub offset: usize,
+    pub message: String,
+}
+
+/// The top-level elements of `source`.
+pub(crate) fn scan(source: &str) -> Result<Vec<Element>, SyntaxError> {
+    let mut scanner = Scanner { source, pos: 0 };
+    scanner.nodes(None)
+}
+
+/// The 1-based line and column (in characters) of a byte offset.
+pub(crate) fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map(|x| x + 1).unwrap_or(0);
+    let column = before[line_start..].chars().count() + 1;
+    (line, column)
+}
+
+struct Scanner<'a> {
+    source: &'a str,
+    pos: usize,
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':' | '.')
+}
+
+impl<'a> Scanner<'a> {
+    fn rest(&self) -> &'a str {
+        &self.source[self.pos..]
+    }
+    fn error<T>(&self, offset: usize, message: impl Into<String>) -> Result<T, SyntaxError> {
+        Err(SyntaxError { offset, message: message.into() })
+    }
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+    fn name(&mut self) -> String {
+        let rest = self.rest();
+        let length = rest.find(|c: char| !is_name_char(c)).unwrap_or(rest.len());
+        self.pos += length;
+        rest[..length].to_string()
+    }
+    /// Skips past `terminator`, failing with `message` if it never comes.
+    fn skip_past(&mut self, terminator: &str, start: usize, message: &str) -> Result<(), SyntaxError> {
+        match self.rest().find(terminator) {
+            Some(index) => {
+                self.pos += index + terminator.len();
+                Ok(())
+            }
+            None => self.error(start, message),
+        }
+    }
+    /// Scans nodes until the end of input, or until the end tag of `parent`
+    /// (its name and start offset).
+    fn nodes(&mut self, parent: Option<(&str, usize)>) -> Result<Vec<Element>, SyntaxError> {
+        let mut elements = Vec::new();
+        loop {
+            let Some(index) = self.rest().find('<') else {
+                if let Some((name, start)) = parent {
+                    return self.error(start, format!("<{name}> is never closed"))
+                }
+                self.pos = self.source.len();
+                return Ok(elements)
+            };
+            self.pos += index;
+            let start = self.pos;
+            let rest = self.rest();
+            if rest.starts_with("<!--") {
+                self.skip_past("-->", start, "unterminated comment")?;
+            } else if rest.starts_with("<![CDATA[") {
+                self.skip_past("]]>", start, "unterminated CDATA section")?;
+            } else if rest.starts_with("<!") || rest.starts_with("<?") {
+                self.skip_past(">", start, "unterminated declaration")?;
+            } else if rest.starts_with("</") {
+                self.pos += 2;
+                let name = self.name();
+                self.skip_whitespace();
+                if !self.rest().starts_with('>') {
+                    return self.error(start, format!("malformed end tag </{name}"))
+                }
+                self.pos += 1;
+                match parent {
+                    Some((parent, _)) if parent == name => return Ok(elements),
+                    Some((parent, _)) => {
+                        return self.error(start, format!("expected </{parent}>, found </{name}>"))
+                    }
+                    None => return self.error(start, format!("unexpected </{name}>")),
+                }
+            } else if rest[1..].starts_with(|c: char| c.is_ascii_alphabetic()) {
+                elements.push(self.element()?);
+            } else {
+                // A lone `<` in text.
+                self.pos += 1;
+            }
+        }
+    }
+    fn element(&mut self) -> Result<Element, SyntaxError> {
+        let start = self.pos;
+        self.pos += 1;
+        let name = self.name();
+        let mut attributes = Vec::new();
+        loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            if rest.is_empty() {
+                return self.error(start, format!("<{name}> tag is never closed with '>'"))
+            }
+            if rest.starts_with("/>") {
+                self.pos += 2;
+                let text = RAW_TEXT_ELEMENTS.contains(&name.as_str()).then(String::new);
+                return Ok(Element { name, attributes, children: Vec::new(), text, verbatim: false, start })
+            }
+            if rest.starts_with('>') {
+                self.pos += 1;
+                break
+            }
+            attributes.push(self.attribute()?);
+        }
+        if RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+            let (text, verbatim) = self.raw_text(&name, start)?;
+            return Ok(Element { name, attributes, children: Vec::new(), text: Some(text), verbatim, start })
+        }
+        let children = self.nodes(Some((&name, start)))?;
+        Ok(Element { name, attributes, children, text: None, verbatim: false, start })
+    }
+    /// Reads a raw-text body up to and including the end tag of `name`, and
+    /// whether it is only CDATA sections.
+    fn raw_text(&mut self, name: &str, start: usize) -> Result<(String, bool), SyntaxError> {
+        let end_tag = format!("</{name}");
+        let mut text = String::new();
+        // The text of the CDATA sections alone, while the rest is whitespace.
+        let mut verbatim: Option<String> = None;
+        let mut only_cdata = true;
+        loop {
+            let rest = self.rest();
+            let cdata = rest.find("<![CDATA[");
+            let end = rest.match_indices(&end_tag)
+                .map(|(index, _)| index)
+                .find(|index| !rest[index + end_tag.len()..].starts_with(is_name_char));
+            match (cdata, end) {
+                (Some(cdata), end) if end.map(|end| cdata < end).unwrap_or(true) => {
+                    text.push_str(&rest[..cdata]);
+                    only_cdata &= rest[..cdata].trim().is_empty();
+                    let section = self.pos + cdata;
+                    let contents = &rest[cdata + "<![CDATA[".len()..];
+                    let Some(length) = contents.find("]]>") else {
+                        return self.error(section, "unterminated CDATA section")
+                    };
+                    text.push_str(&contents[..length]);
+                    verbatim.get_or_insert_with(String::new).push_str(&contents[..length]);
+                    self.pos = section + "<![CDATA[".len() + length + "]]>".len();
+                }
+                (_, Some(end)) => {
+                    text.push_str(&rest[..end]);
+                    only_cdata &= rest[..end].trim().is_empty();
+                    self.pos += end + end_tag.len();
+                    self.skip_whitespace();
+                    if !self.rest().starts_with('>') {
+                        return self.error(self.pos, format!("malformed end tag </{name}"))
+                    }
+                    self.pos += 1;
+                    return match verbatim.filter(|_| only_cdata) {
+                        Some(verbatim) => Ok((verbatim, true)),
+                        None => Ok((text, false)),
+                    }
+                }
+                (_, None) => return self.error(start, format!("<{name}> is never closed")),
+            }
+        }
+    }
+    fn attribute(&mut self) -> Result<Attribute, SyntaxError> {
+        let start = self.pos;
+        let rest = self.rest();
+        let length = match rest.find(|c: char| c.is_whitespace() || matches!(c, '=' | '>' | '/')) {
+            Some(0) => rest.chars().next().map(char::len_utf8).unwrap_or_default(),
+            Some(length) => length,
+            None => rest.len(),
+        };
+        let name = rest[..length].to_string();
+        self.pos += length;
+        self.skip_whitespace();
+        if !self.rest().starts_with('=') {
+            return Ok(Attribute { name, value: None, start })
+        }
+        self.pos += 1;
+        self.skip_whitespace();
+        let rest = self.rest();
+        let value = match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let Some(length) = rest[1..].find(quote) else {
+                    return self.error(start, format!("unterminated value for attribute {name:?}"))
+                };
+                self.pos += length + 2;
+                unescape(&rest[1..length + 1])
+            }
+            _ => {
+                let length = rest
+                    .find(|c: char| c.is_whitespace() || c == '>')
+                    .unwrap_or(rest.len());
+                self.pos += length;
+                unescape(&rest[..length])
+            }
+        };
+        Ok(Attribute { name, value: Some(value), start })
+    }
+}
+
+/// Decodes the predefined XML entities and character references. Anything
+/// else that starts with `&` is kept as written.
+pub(crate) fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(index) = rest.find('&') {
+        result.push_str(&rest[..index]);
+        rest = &rest[index..];
+        let decoded = rest
+            .find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| entity(&rest[1..end]).map(|c| (c, end)));
+        match decoded {
+            Some((c, end)) => {
+                result.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+fn entity(name: &str) -> Option<char> {
+    match name {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        _ => {
+            let code = match name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => name.strip_prefix('#')?.parse().ok()?,
+            };
+            char::from_u32(code)
+        }
+    }
+}
@@ -0,0 +1,154 @@
+//! Enough of JSON Schema to check model output in prompt tests: `type`,
+//! `enum`, `const`, `properties`, `required`, `additionalProperties`,
+//! `items`, `minItems`/`maxItems`, `minLength`/`maxLength`, `pattern`,
+//! `minimum`/`maximum`, and `allOf`/`anyOf`/`oneOf`. Other keywords are
+//! ignored.
+use serde_json::Value;
+
+/// Every way `instance` fails `schema`, each prefixed with a JSON pointer.
+pub(crate) fn validate(schema: &Value, instance: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    check(schema, instance, "", &mut errors);
+    errors
+}
+
+fn check(schema: &Value, instance: &Value, path: &str, errors: &mut Vec<String>) {
+    let Some(schema) = schema.as_object() else {
+        if schema == &Value::Bool(false) {
+            push(errors, path, String::from("no value is allowed here"));
+        }
+        return
+    };
+    if let Some(expected) = schema.get("type") {
+        let types = match expected {
+            Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+            other => other.as_str().into_iter().collect::<Vec<_>>(),
+        };
+        if !types.is_empty() && !types.iter().any(|x| has_type(instance, x)) {
+            push(errors, path, format!("expected {}, found {}", types.join(" or "), type_name(instance)));
+            return
+        }
+    }
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        if !values.contains(instance) {
+            push(errors, path, format!("{instance} is not one of {}", Value::Array(values.clone())));
+        }
+    }
+    if let Some(value) = schema.get("const") {
+        if value != instance {
+            push(errors, path, format!("expected {value}, found {instance}"));
+        }
+    }
+    match instance {
+        Value::String(string) => {
+            let length = string.chars().count() as u64;
+            if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+                if length < min {
+                    push(errors, path, format!("shorter than {min} characters"));
+                }
+            }
+            if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+                if length > max {
+                    push(errors, path, format!("longer than {max} characters"));
+                }
+            }
+            if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+                let is_match = regex::Regex::new(pattern).map(|x| x.is_match(string)).unwrap_or(false);
+                if !is_match {
+                    push(errors, path, format!("doesn’t match {pattern:?}"));
+                }
+            }
+        }
+        Value::Number(number) => {
+            let number = number.as_f64().unwrap_or_default();
+            if let Some(min) = schema.get("minimum").and_then(Value::as_f64) {
+                if number < min {
+                    push(errors, path, format!("less than {min}"));
+                }
+            }
+            if let Some(max) = schema.get("maximum").and_then(Value::as_f64) {
+                if number > max {
+                    push(errors, path, format!("greater than {max}"));
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+                if (items.len() as u64) < min {
+                    push(errors, path, format!("fewer than {min} items"));
+                }
+            }
+            if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+                if items.len() as u64 > max {
+                    push(errors, path, format!("more than {max} items"));
+                }
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    check(item_schema, item, &format!("{path}/{index}"), errors);
+                }
+            }
+        }
+        Value::Object(object) => {
+            for name in schema.get("required").and_then(Value::as_array).into_iter().flatten() {
+                let Some(name) = name.as_str() else { continue };
+                if !object.contains_key(name) {
+                    push(errors, path, format!("missing property {name:?}"));
+                }
+            }
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for (name, value) in object {
+                let property_path = format!("{path}/{}", name.replace('~', "~0").replace('/', "~1"));
+                match (properties.and_then(|x| x.get(name)), schema.get("additionalProperties")) {
+                    (Some(property), _) => check(property, value, &property_path, errors),
+                    (None, Some(additional)) => check(additional, value, &property_path, errors),
+                    (None, None) => {}
+                }
+            }
+        }
+        _ => {}
+    }
+    if let Some(schemas) = schema.get("allOf").and_then(Value::as_array) {
+        for schema in schemas {
+            check(schema, instance, path, errors);
+        }
+    }
+    let passing = |schemas: &Vec<Value>| schemas.iter().filter(|x| validate(x, instance).is_empty()).count();
+    if let Some(schemas) = schema.get("anyOf").and_then(Value::as_array) {
+        if passing(schemas) == 0 {
+            push(errors, path, String::from("matches none of the anyOf schemas"));
+        }
+    }
+    if let Some(schemas) = schema.get("oneOf").and_then(Value::as_array) {
+        let count = passing(schemas);
+        if count != 1 {
+            push(errors, path, format!("matches {count} of the oneOf schemas instead of one"));
+        }
+    }
+}
+
+fn push(errors: &mut Vec<String>, path: &str, message: String) {
+    let path = match path {
+        "" => "/",
+        path => path,
+    };
+    errors.push(format!("{path}: {message}"));
+}
+
+fn has_type(instance: &Value, name: &str) -> bool {
+    match name {
+        "integer" => instance.as_f64().map(|x| x.fract() == 0.0).unwrap_or(false),
+        name => type_name(instance) == name,
+    }
+}
+
+fn type_name(instance: &Value) -> &'static str {
+    match instance {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
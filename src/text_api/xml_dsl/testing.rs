@@ -0,0 +1,388 @@
+//! Test cases kept next to the prompt they test:
+//!
+//! ```xml
+//! <prompt name="classify" model="gpt-4o-mini">
+//!     <var name="ticket" type="string" required/>
+//!     <message>Classify: {{ ticket }}</message>
+//!     <test name="refund" vars='{"ticket": "I want my money back"}'>
+//!         <expect contains="billing"/>
+//!         <expect regex="^(billing|support)$"/>
+//!         <expect json-schema="schemas/label.json"/>
+//!         <expect equals="billing"/>
+//!     </test>
+//! </prompt>
+//! ```
+//!
+//! A `json-schema` is inline JSON or a path relative to the prompt file,
+//! and the output may be wrapped in a Markdown code fence.
+//! [`TestRunner`] renders each test’s variables, gets a completion — from a
+//! [`Cassette`] of recorded responses, or from the API — and checks it.
+use std::path::Path;
+
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::super::client::ApiCallBuilder;
+use super::library::PromptLibrary;
+use super::{Prompt, PromptCollection};
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// TEST CASES
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestCase {
+    pub name: String,
+    /// The variables to render the prompt with.
+    pub vars: serde_json::Map<String, Value>,
+    pub expectations: Vec<Expectation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expectation {
+    Contains(String),
+    Regex(String),
+    /// The output parses as JSON and matches the schema.
+    JsonSchema(Value),
+    /// The output is exactly this, ignoring surrounding whitespace.
+    Equals(String),
+}
+
+impl Expectation {
+    /// The attribute the expectation is written with.
+    pub fn attribute(&self) -> &'static str {
+        match self {
+            Expectation::Contains(_) => "contains",
+            Expectation::Regex(_) => "regex",
+            Expectation::JsonSchema(_) => "json-schema",
+            Expectation::Equals(_) => "equals",
+        }
+    }
+    /// The attribute value, for `json-schema` as inline JSON.
+    pub fn value(&self) -> String {
+        match self {
+            Expectation::Contains(x) | Expectation::Regex(x) | Expectation::Equals(x) => x.clone(),
+            Expectation::JsonSchema(x) => x.to_string(),
+        }
+    }
+    /// Reads an expectation from its attribute; `json-schema` paths are
+    /// resolved against `directory`.
+    pub(crate) fn from_attribute(name: &str, value: &str, directory: Option<&Path>) -> Result<Self, String> {
+        match name {
+            "contains" => Ok(Expectation::Contains(value.to_string())),
+            "equals" => Ok(Expectation::Equals(value.to_string())),
+            "regex" => {
+                regex::Regex::new(value).map_err(|x| x.to_string())?;
+                Ok(Expectation::Regex(value.to_string()))
+            }
+            "json-schema" => {
+                let source = match value.trim_start().starts_with('{') {
+                    true => value.to_string(),
+                    false => {
+                        let path = directory.unwrap_or(Path::new("")).join(value);
+                        std::fs::read_to_string(&path).map_err(|x| format!("{}: {x}", path.display()))?
+                    }
+                };
+                serde_json::from_str(&source).map(Expectation::JsonSchema).map_err(|x| x.to_string())
+            }
+            _ => Err(format!("unknown expectation {name:?}")),
+        }
+    }
+    /// `None` if `output` meets the expectation.
+    pub fn check(&self, output: &str) -> Option<Failure> {
+        let failure = |message: String, diff: Option<String>| {
+            Some(Failure { expectation: self.clone(), message, diff })
+        };
+        match self {
+            Expectation::Contains(expected) if !output.contains(expected.as_str()) => {
+                failure(format!("output doesn’t contain {expected:?}"), None)
+            }
+            Expectation::Regex(pattern) => {
+                let is_match = regex::Regex::new(pattern).map(|x| x.is_match(output)).unwrap_or(false);
+                match is_match {
+                    true => None,
+                    false => failure(format!("output doesn’t match /{pattern}/"), None),
+                }
+            }
+            Expectation::JsonSchema(schema) => match serde_json::from_str::<Value>(super::chain::json_body(output)) {
+                Err(error) => failure(format!("output isn’t JSON: {error}"), None),
+                Ok(value) => {
+                    let errors = super::schema::validate(schema, &value);
+                    match errors.is_empty() {
+                        true => None,
+                        false => failure(format!("output doesn’t match the schema:\n{}", errors.join("\n")), None),
+                    }
+                }
+            },
+            Expectation::Equals(expected) if expected.trim() != output.trim() => {
+                failure(String::from("output differs"), Some(diff(expected.trim(), output.trim())))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A line diff from `expected` to `actual`, with `-` and `+` markers.
+pub fn diff(expected: &str, actual: &str) -> String {
+    let old = expected.lines().collect::<Vec<_>>();
+    let new = actual.lines().collect::<Vec<_>>();
+    // The longest common subsequence of lines, filled from the end.
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = match old[i] == new[j] {
+                true => lengths[i + 1][j + 1] + 1,
+                false => lengths[i + 1][j].max(lengths[i][j + 1]),
+            };
+        }
+    }
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(format!("  {}", old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lengths[i + 1][j] >= lengths[i][j + 1]) {
+            lines.push(format!("- {}", old[i]));
+            i += 1;
+        } else {
+            lines.push(format!("+ {}", new[j]));
+            j += 1;
+        }
+    }
+    lines.join("\n")
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// CASSETTE
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+/// Recorded completions, matched by the exact request body.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: Value,
+    pub response: String,
+}
+
+impl Cassette {
+    pub fn open(file_path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let source = std::fs::read_to_string(file_path.as_ref())?;
+        let cassette = serde_json::from_str::<Self>(&source)?;
+        Ok(cassette)
+    }
+    pub fn save(&self, file_path: impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>> {
+        let file_path = file_path.as_ref();
+        if let Some(parent) = file_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(file_path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+    pub fn get(&self, request: &Value) -> Option<&str> {
+        self.interactions
+            .iter()
+            .find(|x| &x.request == request)
+            .map(|x| x.response.as_str())
+    }
+    pub fn record(&mut self, request: Value, response: impl AsRef<str>) {
+        let response = response.as_ref().to_string();
+        self.interactions.retain(|x| x.request != request);
+        self.interactions.push(Interaction { request, response });
+    }
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// RUNNER
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+/// Runs prompt tests. Responses come from the cassette when it has them;
+/// otherwise from the API, if configured, and are then recorded.
+#[derive(Default)]
+pub struct TestRunner {
+    /// Makes a builder with the URL, key and settings for each call.
+    api_call: Option<Box<dyn Fn() -> ApiCallBuilder>>,
+    cassette: Cassette,
+}
+
+impl std::fmt::Debug for TestRunner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TestRunner")
+            .field("live", &self.api_call.is_some())
+            .field("cassette", &self.cassette)
+            .finish()
+    }
+}
+
+impl TestRunner {
+    /// Calls the API with a builder from `api_call` (URL, key, timeout and
+    /// so on) for every request missing from the cassette.
+    pub fn live(api_call: impl Fn() -> ApiCallBuilder + 'static) -> Self {
+        Self { api_call: Some(Box::new(api_call)), cassette: Cassette::default() }
+    }
+    /// Only uses recorded responses; tests without one fail.
+    pub fn replay(cassette: Cassette) -> Self {
+        Self { api_call: None, cassette }
+    }
+    pub fn with_cassette(mut self, cassette: Cassette) -> Self {
+        self.cassette = cassette;
+        self
+    }
+    /// The recorded responses, including those from this run, to save.
+    pub fn cassette(&self) -> &Cassette {
+        &self.cassette
+    }
+    pub async fn run_prompt(&mut self, key: impl AsRef<str>, prompt: &Prompt) -> TestReport {
+        let mut results = Vec::new();
+        for test in prompt.tests.iter() {
+            let result = self.run_test(key.as_ref(), prompt, test).await;
+            results.push(result);
+        }
+        TestReport { results }
+    }
+    /// Runs the tests of every named prompt.
+    pub async fn run_collection(&mut self, collection: &PromptCollection) -> TestReport {
+        let mut results = Vec::new();
+        for prompt in collection.prompts() {
+            let Some(name) = prompt.name.as_ref() else { continue };
+            results.extend(self.run_prompt(name, prompt).await.results);
+        }
+        TestReport { results }
+    }
+    pub async fn run_library(&mut self, library: &PromptLibrary) -> TestReport {
+        let mut results = Vec::new();
+        for (key, prompt) in library.prompts() {
+            results.extend(self.run_prompt(key, prompt).await.results);
+        }
+        TestReport { results }
+    }
+    async fn run_test(&mut self, key: &str, prompt: &Prompt, test: &TestCase) -> TestResult {
+        let mut result = TestResult {
+            prompt: key.to_string(),
+            test: test.name.clone(),
+            output: None,
+            error: None,
+            failures: Vec::new(),
+        };
+        match self.complete(prompt, test).await {
+            Ok(output) => {
+                result.failures = test.expectations.iter().filter_map(|x| x.check(&output)).collect();
+                result.output = Some(output);
+            }
+            Err(error) => result.error = Some(error.to_string()),
+        }
+        result
+    }
+    async fn complete(&mut self, prompt: &Prompt, test: &TestCase) -> Result<String, Box<dyn std::error::Error>> {
+        let vars = liquid::to_object(&test.vars)?;
+        let request = prompt.render(&vars)?.with_stream(false);
+        let key = serde_json::to_value(&request)?;
+        if let Some(response) = self.cassette.get(&key) {
+            return Ok(response.to_string())
+        }
+        let api_call = self.api_call
+            .as_ref()
+            .ok_or("no recorded response for this request, and no API to call")?;
+        let response = api_call()
+            .with_request_body(request)
+            .build_batch_api_call()
+            .ok_or("the test can’t be run without an API URL, key and model")?
+            .execute()
+            .await?;
+        let output = response.choices
+            .first()
+            .and_then(|x| x.message.content.clone())
+            .unwrap_or_default();
+        self.cassette.record(key, &output);
+        Ok(output)
+    }
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// RESULTS
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+#[derive(Debug, Clone)]
+pub struct Failure {
+    pub expectation: Expectation,
+    pub message: String,
+    /// For `equals`, the line diff from the expected to the actual output.
+    pub diff: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TestResult {
+    /// The prompt’s name, or its `path#name` key in a library.
+    pub prompt: String,
+    pub test: String,
+    pub output: Option<String>,
+    /// Why no output was obtained (rendering, the API, a missing recording).
+    pub error: Option<String>,
+    pub failures: Vec<Failure>,
+}
+
+impl TestResult {
+    pub fn passed(&self) -> bool {
+        self.error.is_none() && self.failures.is_empty()
+    }
+}
+
+impl std::fmt::Display for TestResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let title = format!("{} › {}", self.prompt, self.test);
+        if self.passed() {
+            return write!(f, "{} {title}", "PASS".green())
+        }
+        write!(f, "{} {title}", "FAIL".red())?;
+        if let Some(error) = self.error.as_ref() {
+            write!(f, "\n  error: {error}")?;
+        }
+        for failure in self.failures.iter() {
+            write!(f, "\n  {}: {}", failure.expectation.attribute(), failure.message.replace('\n', "\n    "))?;
+            for line in failure.diff.iter().flat_map(|x| x.lines()) {
+                let line = match line.chars().next() {
+                    Some('-') => line.red(),
+                    Some('+') => line.green(),
+                    _ => line.normal(),
+                };
+                write!(f, "\n    {line}")?;
+            }
+        }
+        if let Some(output) = self.output.as_ref().filter(|_| !self.failures.is_empty()) {
+            write!(f, "\n  output: {output:?}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TestReport {
+    pub results: Vec<TestResult>,
+}
+
+impl TestReport {
+    pub fn passed(&self) -> usize {
+        self.results.iter().filter(|x| x.passed()).count()
+    }
+    pub fn failed(&self) -> usize {
+        self.results.len() - self.passed()
+    }
+    pub fn is_success(&self) -> bool {
+        self.failed() == 0
+    }
+}
+
+impl std::fmt::Display for TestReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for result in self.results.iter() {
+            writeln!(f, "{result}")?;
+        }
+        let summary = format!("{} passed, {} failed", self.passed(), self.failed());
+        match self.is_success() {
+            true => write!(f, "{}", summary.green()),
+            false => write!(f, "{}", summary.red()),
+        }
+    }
+}
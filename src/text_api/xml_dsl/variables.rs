@@ -0,0 +1,160 @@
+//! The variables a prompt declares with `<var name="…" type="…" required/>`,
+//! checked against the values given to [`super::Prompt::render`].
+use colored::Colorize;
+use liquid::ValueView;
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// VARIABLES
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VariableType {
+    String,
+    /// A whole or fractional number.
+    Number,
+    Integer,
+    Boolean,
+    Array,
+    Object,
+    #[default]
+    Any,
+}
+
+impl VariableType {
+    /// The names used for the `type` attribute.
+    pub const NAMES: &'static [&'static str] = &[
+        "string", "number", "integer", "boolean", "bool", "array", "object", "any",
+    ];
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "string" => Some(VariableType::String),
+            "number" => Some(VariableType::Number),
+            "integer" => Some(VariableType::Integer),
+            "boolean" | "bool" => Some(VariableType::Boolean),
+            "array" => Some(VariableType::Array),
+            "object" => Some(VariableType::Object),
+            "any" => Some(VariableType::Any),
+            _ => None,
+        }
+    }
+    pub fn name(&self) -> &'static str {
+        match self {
+            VariableType::String => "string",
+            VariableType::Number => "number",
+            VariableType::Integer => "integer",
+            VariableType::Boolean => "boolean",
+            VariableType::Array => "array",
+            VariableType::Object => "object",
+            VariableType::Any => "any",
+        }
+    }
+    /// Whether `value` is of this type. Nil only matches `Any`.
+    pub fn matches(&self, value: &dyn ValueView) -> bool {
+        matches!(
+            (self, value.type_name()),
+            (VariableType::Any, _)
+                | (VariableType::String, "string")
+                | (VariableType::Number, "whole number" | "fractional number")
+                | (VariableType::Integer, "whole number")
+                | (VariableType::Boolean, "boolean")
+                | (VariableType::Array, "array")
+                | (VariableType::Object, "object")
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variable {
+    pub name: String,
+    pub kind: VariableType,
+    /// Optional variables that aren’t given render as nil.
+    pub required: bool,
+}
+
+impl Variable {
+    pub fn new(name: impl AsRef<str>, kind: VariableType) -> Self {
+        Self { name: name.as_ref().to_string(), kind, required: false }
+    }
+    pub fn with_required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+}
+
+/// Checks `vars` against the declarations.
+pub fn check(variables: &[Variable], vars: &dyn liquid::ObjectView) -> Vec<VariableProblem> {
+    let mut problems = Vec::new();
+    for variable in variables {
+        match vars.get(&variable.name) {
+            None if variable.required => problems.push(VariableProblem::Missing(variable.name.clone())),
+            None => {}
+            Some(value) if !variable.kind.matches(value) => {
+                problems.push(VariableProblem::WrongType {
+                    name: variable.name.clone(),
+                    expected: variable.kind,
+                    found: value.type_name(),
+                });
+            }
+            Some(_) => {}
+        }
+    }
+    problems
+}
+
+/// `vars`, plus nil for every optional variable that isn’t given, so
+/// templates can test for them.
+pub(crate) fn with_optionals(variables: &[Variable], vars: &dyn liquid::ObjectView) -> liquid::Object {
+    let mut object = liquid::Object::new();
+    for (name, value) in vars.iter() {
+        object.insert(name.into_owned(), value.to_value());
+    }
+    for variable in variables {
+        if !object.contains_key(variable.name.as_str()) {
+            object.insert(variable.name.clone().into(), liquid::model::Value::Nil);
+        }
+    }
+    object
+}
+
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+// ERRORS
+//―――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――――
+#[derive(Debug, Clone, PartialEq)]
+pub enum VariableProblem {
+    Missing(String),
+    WrongType { name: String, expected: VariableType, found: &'static str },
+}
+
+impl std::fmt::Display for VariableProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VariableProblem::Missing(name) => write!(f, "{name:?} is required"),
+            VariableProblem::WrongType { name, expected, found } => {
+                write!(f, "{name:?} should be {}, found {found}", expected.name())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InvalidVariables {
+    pub prompt: Option<String>,
+    pub problems: Vec<VariableProblem>,
+}
+
+impl std::fmt::Display for InvalidVariables {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let prompt = self.prompt
+            .as_ref()
+            .map(|x| format!("prompt {x:?}"))
+            .unwrap_or_else(|| String::from("unnamed prompt"));
+        let problems = self.problems
+            .iter()
+            .map(|x| format!("\n  {x}"))
+            .collect::<String>();
+        let msg = format!("Error: Invalid Variables! For {prompt}:{problems}");
+        let msg = msg.red();
+        write!(f, "{msg}")
+    }
+}
+
+impl std::error::Error for InvalidVariables {}
@@ -0,0 +1,134 @@
+use ai_subsystems::batch_api::request::RequestBuilder;
+use ai_subsystems::batch_api::response::{Batch, BatchResults};
+use ai_subsystems::text_api::request::{Message, RequestBuilder as ChatRequestBuilder};
+
+fn chat(content: &str) -> ai_subsystems::text_api::request::Request {
+    ChatRequestBuilder::default()
+        .with_model("gpt-4o-mini")
+        .with_messages(vec![Message::user(content)])
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn requests_become_one_line_each() {
+    let request = RequestBuilder::default()
+        .with_request("a", chat("Hi"))
+        .with_request("b", chat("Hello"))
+        .build()
+        .unwrap();
+    assert_eq!(request.completion_window, "24h");
+    let jsonl = request.to_jsonl();
+    let lines = jsonl
+        .lines()
+        .map(|x| serde_json::from_str::<serde_json::Value>(x).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[1]["custom_id"], "b");
+    assert_eq!(lines[1]["method"], "POST");
+    assert_eq!(lines[1]["url"], "/v1/chat/completions");
+    assert_eq!(lines[1]["body"]["messages"][0]["content"], "Hello");
+}
+
+#[test]
+fn repeated_ids_and_streaming_requests_are_rejected() {
+    assert!(RequestBuilder::default().build().is_none());
+    let repeated = RequestBuilder::default().with_request("a", chat("Hi")).with_request("a", chat("Hello"));
+    assert!(repeated.build().is_none());
+    let mut streaming = chat("Hi");
+    streaming.stream = Some(true);
+    assert!(RequestBuilder::default().with_request("a", streaming).build().is_none());
+}
+
+fn batch() -> Batch {
+    serde_json::from_value(serde_json::json!({
+        "id": "batch_1",
+        "object": "batch",
+        "endpoint": "/v1/chat/completions",
+        "errors": null,
+        "input_file_id": "file-1",
+        "completion_window": "24h",
+        "status": "completed",
+        "output_file_id": "file-2",
+        "error_file_id": "file-3",
+        "created_at": 1,
+        "in_progress_at": null,
+        "expires_at": null,
+        "finalizing_at": null,
+        "completed_at": 2,
+        "failed_at": null,
+        "expired_at": null,
+        "cancelling_at": null,
+        "cancelled_at": null,
+        "request_counts": {"total": 4, "completed": 1, "failed": 3},
+        "metadata": null,
+    }))
+    .unwrap()
+}
+
+fn output_line(custom_id: &str, status_code: u16, body: serde_json::Value) -> String {
+    serde_json::json!({
+        "id": format!("batch_req_{custom_id}"),
+        "custom_id": custom_id,
+        "response": {"status_code": status_code, "request_id": "req", "body": body},
+        "error": null,
+    })
+    .to_string()
+}
+
+#[test]
+fn results_are_matched_by_custom_id() {
+    let completion = serde_json::json!({
+        "id": "chatcmpl-1",
+        "object": "chat.completion",
+        "created": 1,
+        "model": "gpt-4o-mini",
+        "system_fingerprint": null,
+        "choices": [{
+            "index": 0,
+            "finish_reason": "stop",
+            "logprobs": null,
+            "message": {"role": "assistant", "content": "Hello!", "tool_calls": null, "function_call": null},
+        }],
+        "usage": {"prompt_tokens": 5, "completion_tokens": 2, "total_tokens": 7},
+    });
+    let output = [
+        output_line("ok", 200, completion),
+        output_line("limited", 429, serde_json::json!({"error": {"code": "rate_limit_exceeded", "message": "Slow down"}})),
+        output_line("garbled", 200, serde_json::json!({"choices": "none"})),
+    ]
+    .join("\n");
+    let errors = r#"{"id": "batch_req_x", "custom_id": "expired", "response": null, "error": {"code": "batch_expired", "message": "Expired"}}"#;
+    let mut results = BatchResults::new(batch());
+    results.extend_from_jsonl(&output).unwrap();
+    results.extend_from_jsonl(&format!("\n{errors}\n")).unwrap();
+    assert_eq!(results.responses["ok"].choices[0].message.content.as_deref(), Some("Hello!"));
+    assert_eq!(results.responses.len(), 1);
+    assert_eq!(results.errors["limited"].code.as_deref(), Some("rate_limit_exceeded"));
+    assert_eq!(results.errors["limited"].message, "Slow down");
+    assert_eq!(results.errors["garbled"].code.as_deref(), Some("invalid_response"));
+    assert_eq!(results.errors["expired"].code.as_deref(), Some("batch_expired"));
+    assert!(results.extend_from_jsonl("not json").is_err());
+}
+
+#[tokio::test]
+async fn creating_calls_are_not_retried_after_a_timeout() {
+    use ai_subsystems::batch_api::client::{ApiError, RetryPolicy};
+    use std::time::Duration;
+    let policy = RetryPolicy::default().with_max_retries(2).with_initial_backoff(Duration::ZERO);
+    let calls = std::cell::Cell::new(0);
+    let fail_with = |error: ApiError| {
+        let calls = &calls;
+        move || {
+            calls.set(calls.get() + 1);
+            let error = error.clone();
+            async move { Err::<(), Box<dyn std::error::Error>>(Box::new(error)) }
+        }
+    };
+    assert!(policy.run_once_accepted(fail_with(ApiError::APITimeoutError)).await.is_err());
+    assert_eq!(calls.replace(0), 1);
+    assert!(policy.run_once_accepted(fail_with(ApiError::RateLimitError)).await.is_err());
+    assert_eq!(calls.replace(0), 3);
+    assert!(policy.run(fail_with(ApiError::APITimeoutError)).await.is_err());
+    assert_eq!(calls.replace(0), 3);
+}
@@ -0,0 +1,94 @@
+use ai_subsystems::text_api::context::{drop_oldest, keep_last_turns, ContextStrategy};
+use ai_subsystems::text_api::request::{Message, RequestBuilder};
+use ai_subsystems::text_api::tokens::TokenCounter;
+
+fn contents(messages: &[Message]) -> Vec<&str> {
+    messages.iter().map(|x| x.content()).collect()
+}
+
+/// A system prompt and three turns, the second with a tool call and result.
+fn chat() -> Vec<Message> {
+    vec![
+        Message::system("Be brief."),
+        Message::user("one"),
+        Message::assistant("1"),
+        Message::user("two"),
+        Message::assistant("calling"),
+        Message::tool("2", "call_1"),
+        Message::assistant("2"),
+        Message::system("Now in French."),
+        Message::user("three"),
+        Message::assistant("3"),
+    ]
+}
+
+#[test]
+fn the_last_turns_and_every_system_message_are_kept() {
+    let kept = keep_last_turns(&chat(), 2);
+    assert_eq!(contents(&kept), vec!["Be brief.", "two", "calling", "2", "2", "Now in French.", "three", "3"]);
+    assert_eq!(contents(&keep_last_turns(&chat(), 0)), vec!["Be brief.", "Now in French.", "three", "3"]);
+    assert_eq!(keep_last_turns(&chat(), 10).len(), chat().len());
+}
+
+#[test]
+fn whole_turns_are_dropped_until_the_budget_is_met() {
+    let counter = TokenCounter::default();
+    let messages = chat();
+    let last_two = keep_last_turns(&messages, 2);
+    let (kept, dropped) = drop_oldest(&messages, counter.count_messages(&last_two), &counter);
+    assert_eq!(contents(&kept), contents(&last_two));
+    assert_eq!(contents(&dropped), vec!["one", "1"]);
+    // The tool call isn't separated from its result.
+    let (kept, dropped) = drop_oldest(&messages, counter.count_messages(&last_two) - 1, &counter);
+    assert_eq!(contents(&kept), vec!["Be brief.", "Now in French.", "three", "3"]);
+    assert_eq!(contents(&dropped), vec!["one", "1", "two", "calling", "2", "2"]);
+}
+
+#[test]
+fn the_latest_turn_is_kept_even_over_budget() {
+    let (kept, dropped) = drop_oldest(&chat(), 1, &TokenCounter::default());
+    assert_eq!(contents(&kept), vec!["Be brief.", "Now in French.", "three", "3"]);
+    assert_eq!(dropped.len(), 6);
+}
+
+#[test]
+fn messages_before_the_first_user_message_form_a_turn() {
+    let messages = vec![Message::system("s"), Message::assistant("Hi, how can I help?"), Message::user("q"), Message::assistant("a")];
+    assert_eq!(contents(&keep_last_turns(&messages, 1)), vec!["s", "q", "a"]);
+}
+
+#[tokio::test]
+async fn strategies_trim_the_request_in_place() {
+    let mut request = RequestBuilder::default()
+        .with_model("gpt-4o")
+        .with_messages(chat())
+        .build()
+        .unwrap();
+    ContextStrategy::keep_last_turns(1).apply(&mut request).await.unwrap();
+    assert_eq!(contents(&request.messages), vec!["Be brief.", "Now in French.", "three", "3"]);
+    // The default budget is the model's context window, which the chat fits in.
+    let mut request = RequestBuilder::default()
+        .with_model("gpt-4o")
+        .with_messages(chat())
+        .build()
+        .unwrap();
+    ContextStrategy::drop_oldest().apply(&mut request).await.unwrap();
+    assert_eq!(request.messages.len(), chat().len());
+    ContextStrategy::drop_oldest().with_budget(1).apply(&mut request).await.unwrap();
+    assert_eq!(request.messages.len(), 4);
+}
+
+#[tokio::test]
+async fn summaries_without_an_api_fail_instead_of_panicking() {
+    use ai_subsystems::text_api::client::ApiCallBuilder;
+    use ai_subsystems::text_api::context::Summarizer;
+    let summarizer = Summarizer::new("gpt-4o-mini", ApiCallBuilder::default).with_budget(20);
+    let mut request = RequestBuilder::default()
+        .with_model("gpt-4o")
+        .with_messages(chat())
+        .build()
+        .unwrap();
+    let strategy = ContextStrategy::summarize(summarizer).with_budget(1);
+    assert!(strategy.apply(&mut request).await.is_err());
+    assert_eq!(request.messages.len(), chat().len());
+}
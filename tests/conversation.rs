@@ -0,0 +1,75 @@
+use ai_subsystems::text_api::client::ApiCallBuilder;
+use ai_subsystems::text_api::conversation::Conversation;
+use ai_subsystems::text_api::request::{Message, RequestBuilder};
+
+fn contents(conversation: &Conversation) -> Vec<&str> {
+    conversation.messages.iter().map(|x| x.content()).collect()
+}
+
+fn chat() -> Conversation {
+    let mut conversation = Conversation::new(RequestBuilder::default().with_model("gpt-4o")).with_system("Be brief.");
+    for (question, answer) in [("one", "1"), ("two", "2"), ("three", "3")] {
+        conversation.push(Message::user(question));
+        conversation.push(Message::assistant(answer));
+    }
+    conversation
+}
+
+#[test]
+fn undo_removes_the_last_exchange() {
+    let mut conversation = chat();
+    assert_eq!(conversation.turns(), 3);
+    let removed = conversation.undo().unwrap();
+    assert_eq!(removed.iter().map(|x| x.content()).collect::<Vec<_>>(), vec!["three", "3"]);
+    assert_eq!(contents(&conversation), vec!["Be brief.", "one", "1", "two", "2"]);
+    conversation.undo();
+    conversation.undo();
+    assert_eq!(contents(&conversation), vec!["Be brief."]);
+    assert!(conversation.undo().is_none());
+}
+
+#[test]
+fn forks_are_independent() {
+    let conversation = chat();
+    let mut fork = conversation.fork();
+    fork.push(Message::user("four"));
+    assert_eq!(conversation.turns(), 3);
+    assert_eq!(fork.turns(), 4);
+}
+
+#[test]
+fn branches_keep_the_first_exchanges() {
+    let conversation = chat();
+    let branch = conversation.branch_at(1);
+    assert_eq!(contents(&branch), vec!["Be brief.", "one", "1"]);
+    assert_eq!(branch.defaults.model.as_deref(), Some("gpt-4o"));
+    assert_eq!(contents(&conversation.branch_at(0)), vec!["Be brief."]);
+    assert_eq!(conversation.branch_at(10).messages.len(), conversation.messages.len());
+}
+
+#[test]
+fn requests_carry_the_defaults_and_the_history() {
+    let conversation = Conversation::new(RequestBuilder::default()
+        .with_model("gpt-4o")
+        .with_messages(vec![Message::system("Be brief.")]));
+    assert!(conversation.defaults.messages.is_empty());
+    let request = conversation.request();
+    assert_eq!(request.model.as_deref(), Some("gpt-4o"));
+    assert_eq!(request.messages.len(), 1);
+}
+
+#[tokio::test]
+async fn failed_sends_leave_the_history_unchanged() {
+    let mut conversation = chat();
+    assert!(conversation.send("four", ApiCallBuilder::default()).await.is_err());
+    assert_eq!(conversation.messages.len(), 7);
+}
+
+#[test]
+fn conversations_round_trip_through_files() {
+    let file_path = std::env::temp_dir().join(format!("conversation_{}.json", std::process::id()));
+    chat().save(&file_path).unwrap();
+    let conversation = Conversation::open(&file_path).unwrap();
+    assert_eq!(contents(&conversation), contents(&chat()));
+    assert_eq!(conversation.defaults.model.as_deref(), Some("gpt-4o"));
+}
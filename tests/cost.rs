@@ -0,0 +1,77 @@
+use ai_subsystems::cost::CostAccountant;
+use ai_subsystems::text_api::response::batch::Usage;
+
+fn usage(prompt_tokens: isize, completion_tokens: isize) -> Usage {
+    Usage {
+        prompt_tokens,
+        completion_tokens,
+        total_tokens: prompt_tokens + completion_tokens,
+    }
+}
+
+fn assert_close(a: f64, b: f64) {
+    assert!((a - b).abs() < 1e-9, "{a} != {b}");
+}
+
+#[test]
+fn calls_are_priced_from_the_registry() {
+    let accountant = CostAccountant::new();
+    // $0.15 and $0.60 per million prompt and completion tokens.
+    let cost = accountant.record_usage("gpt-4o-mini", &usage(1000, 500)).unwrap();
+    assert_close(cost, 0.00045);
+    // $0.04 per image.
+    assert_close(accountant.record_units("dall-e-3", 2.0).unwrap(), 0.08);
+    assert_eq!(accountant.record_usage("my-local-model", &usage(10, 10)), None);
+    let total = accountant.total();
+    assert_eq!((total.calls, total.unpriced_calls), (3, 1));
+    assert_eq!((total.prompt_tokens, total.completion_tokens), (1010, 510));
+    assert_close(total.cost, 0.08045);
+}
+
+#[test]
+fn totals_are_grouped_by_model_prompt_and_tag() {
+    let accountant = CostAccountant::new();
+    let summarize = accountant.with_prompt("summarize").with_tag("alice");
+    summarize.record_usage("gpt-4o-mini", &usage(1000, 0)).unwrap();
+    summarize.record_usage("gpt-4o", &usage(1000, 0)).unwrap();
+    accountant.with_tag("bob").record_usage("gpt-4o", &usage(1000, 0)).unwrap();
+    assert_eq!(accountant.by_model()["gpt-4o"].calls, 2);
+    assert_eq!(accountant.by_prompt()["summarize"].calls, 2);
+    assert_eq!(accountant.by_prompt().len(), 1);
+    assert_close(accountant.by_tag()["bob"].cost, 0.0025);
+    assert_eq!(accountant.charges()[0].prompt.as_deref(), Some("summarize"));
+    accountant.reset();
+    assert_eq!(accountant.total().calls, 0);
+}
+
+#[test]
+fn calls_fail_once_the_budget_is_spent() {
+    let accountant = CostAccountant::new().with_budget(0.001);
+    assert!(accountant.check_budget().is_ok());
+    accountant.record_usage("gpt-4o-mini", &usage(1000, 500));
+    assert_close(accountant.remaining().unwrap(), 0.00055);
+    accountant.record_usage("gpt-4o-mini", &usage(1000, 500));
+    assert!(accountant.check_budget().is_ok());
+    accountant.record_usage("gpt-4o-mini", &usage(1000, 500));
+    let error = accountant.check_budget().unwrap_err();
+    assert_close(error.spent, 0.00135);
+    assert_eq!(accountant.remaining(), Some(0.0));
+}
+
+#[test]
+fn only_registered_prices_count() {
+    let accountant = CostAccountant::new();
+    assert!(accountant.has_price("gpt-4o"));
+    assert!(accountant.has_price("dall-e-2"));
+    assert!(!accountant.has_price("stable-diffusion-xl-1024-v1-0"));
+}
+
+#[test]
+fn images_are_priced_by_quality_and_size() {
+    let accountant = CostAccountant::new();
+    assert_close(accountant.record_variant_units("dall-e-3", "hd 1792x1024", 1.0).unwrap(), 0.12);
+    assert_close(accountant.record_variant_units("dall-e-3", "standard 1024x1792", 2.0).unwrap(), 0.16);
+    assert_close(accountant.record_variant_units("dall-e-2", "256x256", 1.0).unwrap(), 0.016);
+    // Unknown variants fall back to the model's unit price.
+    assert_close(accountant.record_variant_units("dall-e-3", "hd 2048x2048", 1.0).unwrap(), 0.04);
+}
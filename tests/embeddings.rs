@@ -0,0 +1,69 @@
+use ai_subsystems::embeddings_api::request::Input;
+use ai_subsystems::embeddings_api::response::{EmbeddingVector, Response};
+use base64::prelude::*;
+
+fn strings(n: usize) -> Input {
+    Input::Strings((0..n).map(|x| x.to_string()).collect())
+}
+
+fn lens(chunks: &[Input]) -> Vec<usize> {
+    chunks.iter().map(Input::len).collect()
+}
+
+#[test]
+fn inputs_are_chunked_at_the_batch_size() {
+    assert_eq!(lens(&strings(4).chunks(4)), vec![4]);
+    assert_eq!(lens(&strings(5).chunks(4)), vec![4, 1]);
+    assert_eq!(lens(&strings(8).chunks(4)), vec![4, 4]);
+    assert_eq!(lens(&strings(3).chunks(0)), vec![1, 1, 1]);
+    let batches = Input::TokenBatches(vec![vec![1], vec![2], vec![3]]).chunks(2);
+    assert!(matches!(&batches[1], Input::TokenBatches(x) if x == &vec![vec![3]]));
+    // A single input is never split.
+    assert_eq!(lens(&Input::String(String::from("a long text")).chunks(1)), vec![1]);
+    let Input::Strings(second) = &strings(5).chunks(4)[1] else { panic!("not strings") };
+    assert_eq!(second, &vec![String::from("4")]);
+}
+
+fn response(vectors: &[Vec<f32>], prompt_tokens: isize) -> Response {
+    let data = vectors
+        .iter()
+        .enumerate()
+        .map(|(index, vector)| serde_json::json!({"object": "embedding", "index": index, "embedding": vector}))
+        .collect::<Vec<_>>();
+    serde_json::from_value(serde_json::json!({
+        "object": "list",
+        "data": data,
+        "model": "text-embedding-3-small",
+        "usage": {"prompt_tokens": prompt_tokens, "total_tokens": prompt_tokens},
+    }))
+    .unwrap()
+}
+
+#[test]
+fn merged_responses_are_renumbered_in_order() {
+    let merged = Response::merge([
+        response(&[vec![1.0], vec![2.0]], 3),
+        response(&[vec![3.0]], 4),
+        response(&[vec![4.0], vec![5.0]], 5),
+    ])
+    .unwrap();
+    let indices = merged.data.iter().map(|x| x.index).collect::<Vec<_>>();
+    assert_eq!(indices, vec![0, 1, 2, 3, 4]);
+    assert_eq!(merged.vectors().unwrap(), vec![vec![1.0], vec![2.0], vec![3.0], vec![4.0], vec![5.0]]);
+    assert_eq!((merged.usage.prompt_tokens, merged.usage.total_tokens), (12, 12));
+    assert!(Response::merge(Vec::new()).is_none());
+}
+
+#[test]
+fn base64_vectors_are_little_endian_floats() {
+    let floats = [1.0f32, -0.5, 0.25];
+    let bytes = floats.iter().flat_map(|x| x.to_le_bytes()).collect::<Vec<_>>();
+    let vector = EmbeddingVector::Base64(BASE64_STANDARD.encode(bytes));
+    assert_eq!(vector.decode().unwrap(), floats.to_vec());
+    // 1.0 is 0x3F800000, so its little-endian bytes end with 0x3F.
+    let one = EmbeddingVector::Base64(BASE64_STANDARD.encode([0x00, 0x00, 0x80, 0x3F]));
+    assert_eq!(one.decode().unwrap(), vec![1.0]);
+    assert!(EmbeddingVector::Base64(String::from("not base64!")).decode().is_err());
+    let json = serde_json::from_str::<EmbeddingVector>("\"AACAPw==\"").unwrap();
+    assert_eq!(json.decode().unwrap(), vec![1.0]);
+}
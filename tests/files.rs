@@ -0,0 +1,97 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+
+use ai_subsystems::files_api::client::ClientConfigurationBuilder;
+use ai_subsystems::files_api::request::{ListRequestBuilder, Purpose, UploadRequestBuilder};
+
+#[test]
+fn uploads_from_a_path_default_to_its_file_name() {
+    let request = UploadRequestBuilder::default()
+        .with_purpose(Purpose::batch())
+        .with_file_path(std::env::temp_dir().join("requests.jsonl"))
+        .build()
+        .unwrap();
+    assert_eq!(request.file_name, "requests.jsonl");
+    let request = UploadRequestBuilder::default()
+        .with_purpose(Purpose::batch())
+        .with_file_path(std::env::temp_dir().join("requests.jsonl"))
+        .with_file_name("batch.jsonl")
+        .build()
+        .unwrap();
+    assert_eq!(request.file_name, "batch.jsonl");
+    // Contents held in memory have no name to fall back on.
+    let bytes = UploadRequestBuilder::default()
+        .with_purpose(Purpose::batch())
+        .with_contents(b"{}".to_vec());
+    assert!(bytes.build().is_none());
+    let unnamed = UploadRequestBuilder::default().with_file_name("a.jsonl").with_contents(b"{}".to_vec());
+    assert!(unnamed.build().is_none());
+}
+
+fn file(id: &str) -> serde_json::Value {
+    serde_json::json!({
+        "id": id,
+        "object": "file",
+        "bytes": 2,
+        "created_at": 0,
+        "filename": format!("{id}.jsonl"),
+        "purpose": "batch",
+    })
+}
+
+fn page(ids: &[&str], has_more: bool) -> serde_json::Value {
+    let data = ids.iter().map(|id| file(id)).collect::<Vec<_>>();
+    serde_json::json!({ "object": "list", "data": data, "has_more": has_more })
+}
+
+/// Answers one request per body, in order, returning the requested paths.
+fn serve(bodies: Vec<serde_json::Value>) -> (String, std::thread::JoinHandle<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/v1/files", listener.local_addr().unwrap());
+    let handle = std::thread::spawn(move || {
+        let mut paths = Vec::new();
+        for body in bodies {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            paths.push(request_line.split(' ').nth(1).unwrap().to_string());
+            let body = body.to_string();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len(),
+            ).unwrap();
+        }
+        paths
+    });
+    (url, handle)
+}
+
+#[tokio::test]
+async fn listing_every_file_follows_the_cursor_until_there_are_no_more() {
+    let (url, server) = serve(vec![page(&["file-1", "file-2"], true), page(&["file-3"], false)]);
+    let configuration = ClientConfigurationBuilder::default()
+        .with_api_url(url)
+        .with_api_key("test")
+        .build()
+        .unwrap();
+    let files = ListRequestBuilder::default()
+        .with_purpose(Purpose::batch())
+        .with_limit(2)
+        .build()
+        .execute_all(&configuration)
+        .await
+        .unwrap();
+    let ids = files.iter().map(|x| x.id.as_str()).collect::<Vec<_>>();
+    assert_eq!(ids, vec!["file-1", "file-2", "file-3"]);
+    // Unset fields are left out of the query.
+    assert_eq!(server.join().unwrap(), vec![
+        "/v1/files?purpose=batch&limit=2",
+        "/v1/files?purpose=batch&limit=2&after=file-2",
+    ]);
+}
@@ -0,0 +1,92 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+
+use ai_subsystems::fine_tuning_api::client::ClientConfigurationBuilder;
+use ai_subsystems::fine_tuning_api::response::Job;
+
+fn job(status: &str) -> serde_json::Value {
+    serde_json::json!({
+        "id": "ftjob-1",
+        "object": "fine_tuning.job",
+        "created_at": 0,
+        "error": null,
+        "fine_tuned_model": null,
+        "finished_at": null,
+        "hyperparameters": null,
+        "model": "gpt-4o-mini",
+        "organization_id": null,
+        "status": status,
+        "trained_tokens": null,
+        "training_file": "file-1",
+        "validation_file": null,
+        "seed": null,
+        "estimated_finish": null,
+    })
+}
+
+fn events(ids: &[&str], has_more: bool) -> serde_json::Value {
+    let data = ids
+        .iter()
+        .map(|id| serde_json::json!({
+            "id": id,
+            "object": "fine_tuning.job.event",
+            "created_at": 0,
+            "level": "info",
+            "message": id,
+        }))
+        .collect::<Vec<_>>();
+    serde_json::json!({ "object": "list", "data": data, "has_more": has_more })
+}
+
+/// Answers each request with the body for its path, in order, recording the paths.
+fn serve(responses: Vec<(&'static str, serde_json::Value)>) -> (String, std::thread::JoinHandle<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/v1/fine_tuning/jobs", listener.local_addr().unwrap());
+    let handle = std::thread::spawn(move || {
+        let mut paths = Vec::new();
+        for (expected, body) in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            let path = request_line.split(' ').nth(1).unwrap().to_string();
+            assert_eq!(path, format!("/v1/fine_tuning/jobs{expected}"));
+            let body = body.to_string();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len(),
+            ).unwrap();
+            paths.push(path);
+        }
+        paths
+    });
+    (url, handle)
+}
+
+#[tokio::test]
+async fn watching_pages_back_to_the_last_seen_event() {
+    let (url, server) = serve(vec![
+        ("/ftjob-1/events?limit=100", events(&["e4", "e3"], true)),
+        ("/ftjob-1/events?limit=100&after=e3", events(&["e2", "e1"], false)),
+        ("/ftjob-1", job("succeeded")),
+        ("/ftjob-1/events?limit=100", events(&["e6", "e5"], true)),
+        ("/ftjob-1/events?limit=100&after=e5", events(&["e4", "e3"], true)),
+    ]);
+    let configuration = ClientConfigurationBuilder::default()
+        .with_api_url(url)
+        .with_api_key("test")
+        .with_poll_interval(std::time::Duration::from_millis(1))
+        .build()
+        .unwrap();
+    let job = serde_json::from_value::<Job>(job("running")).unwrap();
+    let mut seen = Vec::new();
+    let job = job.watch(&configuration, |x| seen.push(x.id.clone())).await.unwrap();
+    assert!(job.status.is_terminal());
+    assert_eq!(seen, vec!["e1", "e2", "e3", "e4", "e5", "e6"]);
+    assert_eq!(server.join().unwrap().len(), 5);
+}
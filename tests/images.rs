@@ -0,0 +1,104 @@
+use ai_subsystems::images_api::client::Backend;
+use ai_subsystems::images_api::request::{InvalidRequest, Model, RequestBuilder};
+
+#[test]
+fn openai_requests_are_checked_against_dall_e_2_by_default() {
+    let request = RequestBuilder::default()
+        .with_prompt("a lighthouse")
+        .with_size("768x768")
+        .build(&Backend::OpenAi);
+    assert!(matches!(request, Err(InvalidRequest::UnsupportedSize { .. })));
+}
+
+#[test]
+fn stability_requests_accept_their_own_sizes_and_styles() {
+    let request = RequestBuilder::default()
+        .with_prompt("a lighthouse")
+        .with_size("768x768")
+        .with_style("photographic")
+        .build(&Backend::Stability)
+        .unwrap();
+    assert_eq!(request.model, None);
+    assert_eq!(request.style.as_deref(), Some("photographic"));
+}
+
+#[test]
+fn automatic1111_requests_need_no_model() {
+    let request = RequestBuilder::default()
+        .with_prompt("a lighthouse")
+        .with_size("768x512")
+        .with_n(12)
+        .build(&Backend::Automatic1111)
+        .unwrap();
+    assert_eq!(request.model, None);
+    assert_eq!(request.split(&Backend::Automatic1111).len(), 1);
+}
+
+#[test]
+fn dall_e_3_checks_prompt_length_quality_and_style() {
+    let build = |prompt: &str, quality: &str, style: &str| RequestBuilder::default()
+        .with_model(Model::dall_e3())
+        .with_prompt(prompt)
+        .with_quality(quality)
+        .with_style(style)
+        .build(&Backend::OpenAi);
+    assert!(build("a lighthouse", "hd", "natural").is_ok());
+    let long_prompt = "a".repeat(4001);
+    assert!(matches!(build(&long_prompt, "hd", "natural"), Err(InvalidRequest::PromptTooLong { max: 4000, .. })));
+    assert!(matches!(build("a lighthouse", "ultra", "natural"), Err(InvalidRequest::UnsupportedQuality { .. })));
+    assert!(matches!(build("a lighthouse", "hd", "retro"), Err(InvalidRequest::UnsupportedStyle { .. })));
+}
+
+#[test]
+fn dall_e_2_rejects_styles_and_too_many_images() {
+    let styled = RequestBuilder::default()
+        .with_prompt("a lighthouse")
+        .with_style("vivid")
+        .build(&Backend::OpenAi);
+    assert!(matches!(styled, Err(InvalidRequest::UnsupportedStyle { .. })));
+    let many = RequestBuilder::default()
+        .with_prompt("a lighthouse")
+        .with_n(11)
+        .build(&Backend::OpenAi);
+    assert!(matches!(many, Err(InvalidRequest::UnsupportedN { max: 10, given: 11, .. })));
+    let missing = RequestBuilder::default().build(&Backend::OpenAi);
+    assert!(matches!(missing, Err(InvalidRequest::MissingPrompt)));
+}
+
+#[test]
+fn dall_e_3_requests_are_split_into_single_images() {
+    let request = RequestBuilder::default()
+        .with_model(Model::dall_e3())
+        .with_prompt("a lighthouse")
+        .with_n(3)
+        .build(&Backend::OpenAi)
+        .unwrap();
+    let requests = request.split(&Backend::OpenAi);
+    assert_eq!(requests.iter().map(|x| x.n).collect::<Vec<_>>(), vec![Some(1); 3]);
+    assert!(requests.iter().all(|x| x.prompt == "a lighthouse"));
+}
+
+#[test]
+fn dall_e_2_requests_are_sent_whole() {
+    let request = RequestBuilder::default()
+        .with_prompt("a lighthouse")
+        .with_n(4)
+        .build(&Backend::OpenAi)
+        .unwrap();
+    assert_eq!(request.split(&Backend::OpenAi).len(), 1);
+}
+
+#[test]
+fn openai_styles_are_not_sent_to_stability() {
+    let build = |style: &str| RequestBuilder::default()
+        .with_prompt("a lighthouse")
+        .with_size("1024x1024")
+        .with_style(style)
+        .build(&Backend::Stability)
+        .unwrap();
+    let body = Backend::Stability.encode(&build("vivid"));
+    assert!(body.get("style_preset").is_none());
+    assert_eq!(body["samples"], 1);
+    let body = Backend::Stability.encode(&build("photographic"));
+    assert_eq!(body["style_preset"], "photographic");
+}
@@ -0,0 +1,103 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use ai_subsystems::models_api::client::{ClientConfigurationBuilder, InvalidModel, RetryPolicy};
+use ai_subsystems::text_api::request::RequestBuilder;
+
+/// Serves `ids` as a model list to every request, counting them.
+fn serve(ids: &[&str]) -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/v1/models", listener.local_addr().unwrap());
+    let data = ids.iter().map(|id| serde_json::json!({ "id": id })).collect::<Vec<_>>();
+    let body = serde_json::json!({ "object": "list", "data": data }).to_string();
+    let requests = Arc::new(AtomicUsize::new(0));
+    let counter = requests.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            counter.fetch_add(1, Ordering::SeqCst);
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len(),
+            ).unwrap();
+        }
+    });
+    (url, requests)
+}
+
+/// A URL nothing listens on.
+fn unreachable() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    format!("http://{}/v1/models", listener.local_addr().unwrap())
+}
+
+#[tokio::test]
+async fn model_lists_are_cached_until_they_expire() {
+    let (url, requests) = serve(&["gpt-4o"]);
+    let catalog = ClientConfigurationBuilder::default()
+        .with_provider("open-ai", url.as_str(), "test")
+        .with_cache_ttl(Duration::from_secs(60))
+        .build()
+        .unwrap();
+    catalog.list("open-ai").await.unwrap();
+    catalog.list("open-ai").await.unwrap();
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+    catalog.invalidate();
+    catalog.list("open-ai").await.unwrap();
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+    let catalog = ClientConfigurationBuilder::default()
+        .with_provider("open-ai", url.as_str(), "test")
+        .with_cache_ttl(Duration::from_millis(10))
+        .build()
+        .unwrap();
+    catalog.list("open-ai").await.unwrap();
+    std::thread::sleep(Duration::from_millis(20));
+    catalog.list("open-ai").await.unwrap();
+    assert_eq!(requests.load(Ordering::SeqCst), 4);
+    assert!(catalog.list("mistral-ai").await.unwrap_err().to_string().contains("Unknown Provider"));
+}
+
+#[tokio::test]
+async fn unknown_models_are_reported_with_suggestions() {
+    let (url, _) = serve(&["gpt-4o", "gpt-4o-mini", "whisper-1"]);
+    let catalog = ClientConfigurationBuilder::default()
+        .with_provider("down", unreachable(), "test")
+        .with_provider("open-ai", url.as_str(), "test")
+        .with_retry_policy(RetryPolicy::none())
+        .build()
+        .unwrap();
+    // The provider that’s down is skipped.
+    let (provider, model) = catalog.find("gpt-4o-mini").await.unwrap().unwrap();
+    assert_eq!((provider.as_str(), model.id.as_str()), ("open-ai", "gpt-4o-mini"));
+    catalog.validate_request(&RequestBuilder::default().with_model("gpt-4o")).await.unwrap();
+    let error = catalog
+        .validate_request(&RequestBuilder::default().with_model("gpt-4"))
+        .await
+        .unwrap_err();
+    match error.downcast_ref::<InvalidModel>() {
+        Some(InvalidModel::Unknown { model, suggestions, .. }) => {
+            assert_eq!(model, "gpt-4");
+            assert_eq!(suggestions, &vec!["gpt-4o", "gpt-4o-mini"]);
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn finding_a_model_fails_only_when_no_provider_answers() {
+    let catalog = ClientConfigurationBuilder::default()
+        .with_provider("down", unreachable(), "test")
+        .with_retry_policy(RetryPolicy::none())
+        .build()
+        .unwrap();
+    assert!(catalog.find("gpt-4o").await.is_err());
+}
@@ -0,0 +1,51 @@
+use ai_subsystems::moderation_api::client::{ClientConfigurationBuilder, URL};
+use ai_subsystems::moderation_api::guard::ModerationGuard;
+use ai_subsystems::moderation_api::response::Response;
+
+fn guard() -> ModerationGuard {
+    let configuration = ClientConfigurationBuilder::default()
+        .with_api_url(URL::openai_v1_moderations())
+        .with_api_key("sk-test")
+        .build()
+        .unwrap();
+    ModerationGuard::new(configuration)
+}
+
+/// Two inputs: the first harmless, the second flagged for harassment.
+fn response() -> Response {
+    let result = |flagged: bool, harassment: f32, violence: f32| serde_json::json!({
+        "flagged": flagged,
+        "categories": {"harassment": flagged, "violence": false, "self-harm": false},
+        "category_scores": {"harassment": harassment, "violence": violence, "self-harm": 0.001},
+    });
+    serde_json::from_value(serde_json::json!({
+        "id": "modr-1",
+        "model": "omni-moderation-latest",
+        "results": [result(false, 0.01, 0.2), result(true, 0.9, 0.3)],
+    }))
+    .unwrap()
+}
+
+#[test]
+fn without_thresholds_flagged_categories_reject() {
+    let violations = guard().violations(&response()).unwrap();
+    assert_eq!(violations.len(), 1);
+    assert_eq!((violations[0].message_index, violations[0].category.as_str()), (1, "harassment"));
+    assert_eq!(violations[0].threshold, None);
+}
+
+#[test]
+fn thresholds_reject_scores_above_them() {
+    let violations = guard().with_threshold("violence", 0.25).violations(&response()).unwrap();
+    assert_eq!(violations.len(), 1);
+    assert_eq!((violations[0].message_index, violations[0].score), (1, 0.3));
+    // A score equal to the threshold passes.
+    assert!(guard().with_threshold("violence", 0.3).violations(&response()).unwrap().is_empty());
+}
+
+#[test]
+fn misspelled_categories_are_rejected() {
+    let error = guard().with_threshold("self_harm", 0.5).violations(&response()).unwrap_err();
+    assert_eq!(error.category, "self_harm");
+    assert_eq!(error.known, vec!["harassment", "self-harm", "violence"]);
+}
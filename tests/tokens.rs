@@ -0,0 +1,38 @@
+use ai_subsystems::text_api::request::Message;
+use ai_subsystems::text_api::tokens::{Encoding, TokenCounter};
+
+#[test]
+fn models_use_their_own_encoding() {
+    assert_eq!(Encoding::for_model("gpt-4"), Encoding::Cl100kBase);
+    assert_eq!(Encoding::for_model("gpt-4o-mini"), Encoding::O200kBase);
+    assert_eq!(Encoding::for_model("ft:gpt-4o-mini-2024-07-18:acme::abc123"), Encoding::O200kBase);
+    assert_eq!(Encoding::for_model("llama-3-70b-instruct"), Encoding::Estimate);
+    assert!(!Encoding::Estimate.is_exact());
+}
+
+#[test]
+fn text_is_counted_exactly() {
+    let counter = TokenCounter::default();
+    assert_eq!(counter.count_text("hello world"), 2);
+    assert_eq!(counter.count_text("tiktoken is great!"), 6);
+    assert_eq!(counter.count_text(""), 0);
+}
+
+#[test]
+fn messages_include_the_chat_overhead() {
+    let counter = TokenCounter::default();
+    // 3 to prime the reply, then 3 per message plus the role and content.
+    assert_eq!(counter.count_messages(&[]), 3);
+    assert_eq!(counter.count_messages(&[Message::user("hello world")]), 3 + 3 + 1 + 2);
+    // A name costs its tokens plus one.
+    let named = counter.count_messages(&[Message::named_user("bob", "hello world")]);
+    assert_eq!(named, 9 + counter.count_text("bob") + 1);
+}
+
+#[test]
+fn other_providers_are_overestimated() {
+    let text = "The quick brown fox jumps over the lazy dog, again and again.";
+    let exact = TokenCounter::default().count_text(text);
+    let estimate = TokenCounter::for_model("llama-3-70b-instruct").count_text(text);
+    assert!(estimate > exact);
+}
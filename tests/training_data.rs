@@ -0,0 +1,56 @@
+use ai_subsystems::fine_tuning_api::training_data::{IssueKind, TrainingDataBuilder};
+use ai_subsystems::text_api::request::Message;
+
+fn exchange(question: &str, answer: &str) -> Vec<Message> {
+    vec![Message::system("Answer briefly."), Message::user(question), Message::assistant(answer)]
+}
+
+#[test]
+fn valid_conversations_become_jsonl_lines() {
+    let data = TrainingDataBuilder::default()
+        .with_conversation(exchange("2 + 2?", "4"))
+        .with_conversation(exchange("Capital of France?", "Paris"))
+        .build()
+        .unwrap();
+    let jsonl = data.to_jsonl();
+    let lines = jsonl.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2);
+    let first = serde_json::from_str::<serde_json::Value>(lines[0]).unwrap();
+    assert_eq!(first["messages"][2], serde_json::json!({"role": "assistant", "content": "4"}));
+}
+
+#[test]
+fn every_problem_is_reported_at_once() {
+    let error = TrainingDataBuilder::default()
+        .with_conversation(Vec::new())
+        .with_conversation(vec![Message::user("Hi"), Message::tool("{}", "call_1")])
+        .with_conversation(vec![Message::user(" "), Message::assistant("Hello")])
+        .build()
+        .unwrap_err();
+    let issues = error.issues
+        .iter()
+        .map(|x| (x.example, x.message, x.kind.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(issues.len(), 4);
+    assert!(matches!(issues[0], (0, None, IssueKind::EmptyConversation)));
+    assert!(matches!(issues[1], (1, Some(1), IssueKind::UnsupportedRole("tool"))));
+    assert!(matches!(issues[2], (1, None, IssueKind::MissingAssistantMessage)));
+    assert!(matches!(issues[3], (2, Some(0), IssueKind::EmptyContent)));
+}
+
+#[test]
+fn training_data_needs_examples() {
+    let error = TrainingDataBuilder::default().build().unwrap_err();
+    assert!(matches!(error.issues[..], [ref issue] if matches!(issue.kind, IssueKind::NoExamples)));
+}
+
+#[test]
+fn long_examples_are_rejected() {
+    let error = TrainingDataBuilder::default()
+        .with_model("gpt-4o-mini")
+        .with_max_tokens_per_example(10)
+        .with_conversation(exchange("Tell me about the history of the printing press.", "It began with Gutenberg."))
+        .build()
+        .unwrap_err();
+    assert!(matches!(error.issues[0].kind, IssueKind::TooManyTokens { max: 10, given } if given > 10));
+}
@@ -0,0 +1,60 @@
+use ai_subsystems::embeddings_api::response::Response;
+use ai_subsystems::embeddings_api::vector_store::{Document, Filter, Query, VectorStore};
+
+fn store() -> VectorStore {
+    let mut store = VectorStore::new();
+    store.add(Document::new("north", "North").with_vector(vec![0.0, 1.0]).with_metadata("lang", "en"));
+    store.add(Document::new("east", "East").with_vector(vec![1.0, 0.0]).with_metadata("lang", "en"));
+    store.add(Document::new("northeast", "Nordost").with_vector(vec![0.7, 0.7]).with_metadata("lang", "de"));
+    store
+}
+
+fn ids(store: &VectorStore, query: &Query) -> Vec<String> {
+    store.search(query).into_iter().map(|x| x.document.id.clone()).collect()
+}
+
+#[test]
+fn results_are_ranked_best_first() {
+    let query = Query::new(vec![0.2, 1.0]).with_top_k(10);
+    assert_eq!(ids(&store(), &query), vec!["north", "northeast", "east"]);
+}
+
+#[test]
+fn top_k_limits_the_results() {
+    let query = Query::new(vec![0.2, 1.0]).with_top_k(2);
+    assert_eq!(ids(&store(), &query), vec!["north", "northeast"]);
+}
+
+#[test]
+fn filters_apply_to_metadata() {
+    let query = Query::new(vec![0.2, 1.0]).with_filter(Filter::eq("lang", "en"));
+    assert_eq!(ids(&store(), &query), vec!["north", "east"]);
+    let query = Query::new(vec![0.2, 1.0]).with_filter(Filter::is_in("lang", ["de", "fr"]));
+    assert_eq!(ids(&store(), &query), vec!["northeast"]);
+}
+
+#[test]
+fn documents_of_another_dimension_are_skipped() {
+    let mut store = store();
+    store.add(Document::new("unembedded", "Nothing"));
+    store.add(Document::new("other-model", "Other").with_vector(vec![0.0, 1.0, 0.0]));
+    let query = Query::new(vec![0.2, 1.0]).with_top_k(10);
+    assert_eq!(ids(&store, &query).len(), 3);
+}
+
+#[test]
+fn embeddings_must_match_the_documents() {
+    let response = serde_json::from_value::<Response>(serde_json::json!({
+        "object": "list",
+        "data": [{"object": "embedding", "index": 0, "embedding": [0.0, 1.0]}],
+        "model": "text-embedding-3-small",
+        "usage": {"prompt_tokens": 2, "total_tokens": 2},
+    }))
+    .unwrap();
+    let mut store = VectorStore::new();
+    let documents = vec![Document::new("a", "A"), Document::new("b", "B")];
+    assert!(store.add_embedded(documents, &response).is_err());
+    assert!(store.is_empty());
+    store.add_embedded(vec![Document::new("a", "A")], &response).unwrap();
+    assert_eq!(store.get("a").unwrap().vector, vec![0.0, 1.0]);
+}
@@ -0,0 +1,681 @@
+use ai_subsystems::text_api::request::Message;
+use ai_subsystems::text_api::xml_dsl::{Cassette, Parser, Prompt, TestRunner};
+
+fn contents_of(messages: &[Message]) -> Vec<&str> {
+    messages.iter().map(Message::content).collect()
+}
+
+fn contents(prompt: &Prompt) -> Vec<&str> {
+    contents_of(&prompt.request.messages)
+}
+
+#[test]
+fn code_is_kept_as_written() {
+    let source = r#"
+<prompt name="review" model="gpt-4o">
+    <message role="system">
+        Review this function:
+
+        ```rust
+        fn first<T: Clone>(items: &[T]) -> Option<T> {
+            if items.len() > 0 && true {
+                return items.first().cloned();
+            }
+            None
+        }
+        ```
+    </message>
+</prompt>
+"#;
+    let prompt = Prompt::parse(source, "review").unwrap();
+    let expected = "Review this function:\n\n```rust\nfn first<T: Clone>(items: &[T]) -> Option<T> {\n    if items.len() > 0 && true {\n        return items.first().cloned();\n    }\n    None\n}\n```";
+    assert_eq!(contents(&prompt), vec![expected]);
+}
+
+#[test]
+fn markup_and_entities_are_not_interpreted() {
+    let source = r#"
+<prompt name="html" model="gpt-4o">
+    <message>Fix this: <table><tr><td>a &lt; b</td></table> <br> &amp; <p>unclosed</message>
+</prompt>
+"#;
+    let prompt = Prompt::parse(source, "html").unwrap();
+    let expected = "Fix this: <table><tr><td>a &lt; b</td></table> <br> &amp; <p>unclosed";
+    assert_eq!(contents(&prompt), vec![expected]);
+}
+
+#[test]
+fn cdata_can_contain_the_end_tag() {
+    let source = r#"
+<prompt name="dsl" model="gpt-4o">
+    <message role="user"><![CDATA[Explain <message role="user">Hi</message>.]]></message>
+    <message role="assistant">Before <![CDATA[</message>]]> after</message>
+</prompt>
+"#;
+    let prompt = Prompt::parse(source, "dsl").unwrap();
+    assert_eq!(contents(&prompt), vec![
+        r#"Explain <message role="user">Hi</message>."#,
+        "Before </message> after",
+    ]);
+}
+
+#[test]
+fn xml_examples_keep_their_indentation() {
+    let source = "<prompt name=\"xml\" model=\"gpt-4o\">\n\t<message>\n\t\tConvert:\n\t\t<order id=\"1\">\n\t\t\t<item/>\n\t\t</order>\n\t</message>\n</prompt>";
+    let prompt = Prompt::parse(source, "xml").unwrap();
+    assert_eq!(contents(&prompt), vec!["Convert:\n<order id=\"1\">\n\t<item/>\n</order>"]);
+}
+
+#[test]
+fn attribute_values_decode_entities() {
+    let source = r#"<prompt name="a &amp; b" model="gpt-4o"><message>Hi</message></prompt>"#;
+    let prompt = Prompt::parse(source, "a & b").unwrap();
+    assert_eq!(contents(&prompt), vec!["Hi"]);
+}
+
+#[test]
+fn unterminated_bodies_are_syntax_errors() {
+    let source = r#"<prompt name="a" model="gpt-4o"><message>Hi <![CDATA[</message>"#;
+    let error = Parser::strict().parse(source).unwrap_err();
+    assert_eq!(error.diagnostics.len(), 1);
+    assert_eq!(error.diagnostics[0].location, Some((1, 45)));
+}
+
+const FLAWED_PROMPTS: &str = r#"
+<prompt name="a" model="gpt-4o" colour="red">
+    <message>Hi</message>
+</prompt>
+<prompt name="b" model="gpt-4o" temperature="warm">
+    <message>Hi</message>
+</prompt>
+<prompt name="a" model="gpt-4o">
+    <message>Again</message>
+</prompt>
+<prompt name="c" model="gpt-4o"></prompt>
+<prompt name="d">
+    <message>Hi</message>
+</prompt>
+"#;
+
+#[test]
+fn problems_are_reported_where_they_are() {
+    use ai_subsystems::text_api::xml_dsl::{DiagnosticKind, Severity};
+    let collection = Parser::lenient().parse(FLAWED_PROMPTS).unwrap();
+    let diagnostics = collection.diagnostics();
+    let kinds = diagnostics.iter().map(|x| x.kind.clone()).collect::<Vec<_>>();
+    assert_eq!(kinds, vec![
+        DiagnosticKind::UnknownAttribute { element: String::from("prompt"), attribute: String::from("colour") },
+        DiagnosticKind::BadValue { attribute: String::from("temperature"), value: String::from("warm"), expected: "a number" },
+        DiagnosticKind::DuplicatePromptName(String::from("a")),
+        DiagnosticKind::EmptyPrompt(Some(String::from("c"))),
+        DiagnosticKind::MissingModel(Some(String::from("d"))),
+    ]);
+    let locations = diagnostics.iter().map(|x| x.location).collect::<Vec<_>>();
+    assert_eq!(locations[..2], [Some((2, 33)), Some((5, 33))]);
+    assert!(diagnostics.iter().all(|x| x.severity == Severity::Warning));
+    assert_eq!(diagnostics[0].to_string(), "<input>:2:33: warning: unknown attribute \"colour\" on <prompt>");
+}
+
+#[test]
+fn strict_parsing_fails_on_any_problem() {
+    use ai_subsystems::text_api::xml_dsl::Severity;
+    let error = Parser::strict().parse(FLAWED_PROMPTS).unwrap_err();
+    assert_eq!(error.diagnostics.len(), 5);
+    assert!(error.diagnostics.iter().all(|x| x.severity == Severity::Error));
+}
+
+const TEMPLATED: &str = r#"
+<prompt name="explain" model="gpt-4o">
+    <var name="topic" type="string" required/>
+    <var name="depth" type="integer"/>
+    <message>Explain {{ topic }}{% if depth %} in {{ depth }} sentences{% endif %}.</message>
+</prompt>
+"#;
+
+#[test]
+fn prompts_render_with_different_variables() {
+    use ai_subsystems::text_api::xml_dsl::object;
+    let prompt = Prompt::parse(TEMPLATED, "explain").unwrap();
+    let request = prompt.render(&object!({ "topic": "tides" })).unwrap();
+    assert_eq!(request.messages[0].content(), "Explain tides.");
+    let request = prompt.render(&object!({ "topic": "rust", "depth": 3 })).unwrap();
+    assert_eq!(request.messages[0].content(), "Explain rust in 3 sentences.");
+}
+
+#[test]
+fn missing_and_mistyped_variables_are_reported() {
+    use ai_subsystems::text_api::xml_dsl::{object, VariableProblem, VariableType};
+    let prompt = Prompt::parse(TEMPLATED, "explain").unwrap();
+    let error = prompt.check_variables(&object!({ "depth": "three" })).unwrap_err();
+    assert_eq!(error.problems, vec![
+        VariableProblem::Missing(String::from("topic")),
+        VariableProblem::WrongType {
+            name: String::from("depth"),
+            expected: VariableType::Integer,
+            found: "string",
+        },
+    ]);
+    assert!(prompt.render(&object!({})).is_err());
+}
+
+/// Writes `files` to a fresh directory under the system temp dir.
+fn write_files(test: &str, files: &[(&str, &str)]) -> std::path::PathBuf {
+    let directory = std::env::temp_dir().join(format!("xml_dsl_{test}_{}", std::process::id()));
+    for (name, contents) in files {
+        let path = directory.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+    directory
+}
+
+#[test]
+fn includes_and_partials_are_relative_to_the_file() {
+    use ai_subsystems::text_api::xml_dsl::{object, PromptCollection};
+    let directory = write_files("includes", &[
+        ("main.prompt.liquid", r#"
+            <include src="shared/base.prompt.liquid"/>
+            <prompt name="ask" model="gpt-4o">
+                <include src="shared/system.xml"/>
+                <message>{% include "question.liquid" %}</message>
+            </prompt>
+        "#),
+        ("shared/base.prompt.liquid", r#"
+            <prompt name="base" model="gpt-4o-mini">
+                <include src="system.xml"/>
+                <message>Hello</message>
+            </prompt>
+        "#),
+        ("shared/system.xml", r#"<message role="system">Be brief.</message>"#),
+        ("question.liquid", "What is {{ topic }}?"),
+    ]);
+    let collection = PromptCollection::open(directory.join("main.prompt.liquid")).unwrap();
+    assert!(collection.diagnostics().is_empty(), "{:?}", collection.diagnostics());
+    assert_eq!(contents(&collection.get("base").unwrap()), vec!["Be brief.", "Hello"]);
+    let ask = collection.get("ask").unwrap();
+    let request = ask.render(&object!({ "topic": "Liquid" })).unwrap();
+    let messages = request.messages.iter().map(Message::content).collect::<Vec<_>>();
+    assert_eq!(messages, vec!["Be brief.", "What is Liquid?"]);
+}
+
+#[test]
+fn include_cycles_are_errors() {
+    use ai_subsystems::text_api::xml_dsl::PromptCollection;
+    let directory = write_files("cycles", &[
+        ("a.prompt.liquid", r#"<include src="b.prompt.liquid"/>"#),
+        ("b.prompt.liquid", r#"<include src="a.prompt.liquid"/>"#),
+    ]);
+    assert!(PromptCollection::open(directory.join("a.prompt.liquid")).is_err());
+}
+
+#[test]
+fn prompts_inherit_from_their_parent() {
+    let source = r#"
+<prompt name="base" model="gpt-4o" temperature="0.2" max-tokens="100">
+    <var name="topic" type="string" required/>
+    <message role="system">You are terse.</message>
+    <message>Topic: {{ topic }}</message>
+</prompt>
+<prompt name="child" extends="base" max-tokens="500">
+    <message>Now expand on it.</message>
+</prompt>
+<prompt name="grandchild" extends="child">
+    <message role="system">You are verbose.</message>
+</prompt>
+"#;
+    let child = Prompt::parse(source, "child").unwrap();
+    assert_eq!(child.request.model.as_deref(), Some("gpt-4o"));
+    assert_eq!(child.request.temperature, Some(0.2));
+    assert_eq!(child.request.max_tokens, Some(500));
+    assert_eq!(child.variables.len(), 1);
+    assert_eq!(contents(&child), vec!["You are terse.", "Topic: {{ topic }}", "Now expand on it."]);
+    let grandchild = Prompt::parse(source, "grandchild").unwrap();
+    assert_eq!(contents(&grandchild), vec!["You are verbose.", "Topic: {{ topic }}", "Now expand on it."]);
+}
+
+#[test]
+fn unknown_parents_are_reported() {
+    use ai_subsystems::text_api::xml_dsl::DiagnosticKind;
+    let source = r#"<prompt name="a" extends="missing"><message>Hi</message></prompt>"#;
+    assert!(Parser::strict().parse(source).is_err());
+    let collection = Parser::lenient().parse(source).unwrap();
+    let kinds = collection.diagnostics().iter().map(|x| x.kind.clone()).collect::<Vec<_>>();
+    assert_eq!(kinds, vec![DiagnosticKind::UnknownParent {
+        prompt: Some(String::from("a")),
+        parent: String::from("missing"),
+    }]);
+    assert_eq!(collection.diagnostics()[0].location, Some((1, 1)));
+}
+
+#[test]
+fn every_request_field_can_be_set() {
+    use ai_subsystems::text_api::request::{Tool, ToolChoice};
+    let source = r#"
+<prompt name="all" model="gpt-4o" seed="7" user="user-42" stop="END" tool-choice="get_weather">
+    <stop>
+
+</stop>
+    <logit-bias token="50256" value="-100"/>
+    <tool name="get_weather" description="Current weather for a city.">
+        <parameters>{"type": "object", "properties": {"city": {"type": "string"}}}</parameters>
+    </tool>
+    <message>What’s the weather in Oslo?</message>
+</prompt>
+"#;
+    let collection = Parser::strict().parse(source).unwrap();
+    let request = collection.get("all").unwrap().request;
+    assert_eq!(request.seed, Some(7));
+    assert_eq!(request.user.as_deref(), Some("user-42"));
+    assert_eq!(request.stop, Some(vec![String::from("END"), String::from("\n\n")]));
+    assert_eq!(request.logit_bias.as_ref().unwrap().get("50256"), Some(&-100));
+    assert_eq!(request.tool_choice, Some(ToolChoice::function("get_weather")));
+    let parameters = serde_json::json!({"type": "object", "properties": {"city": {"type": "string"}}});
+    let tool = Tool::function("get_weather", Some(String::from("Current weather for a city.")), Some(parameters));
+    assert_eq!(request.tools, Some(vec![tool]));
+    let json = serde_json::to_value(request.build().unwrap()).unwrap();
+    assert_eq!(json["tool_choice"], serde_json::json!({"type": "function", "function": {"name": "get_weather"}}));
+}
+
+#[test]
+fn invalid_tool_parameters_are_reported() {
+    let source = r#"<prompt model="gpt-4o"><tool name="f"><parameters>{oops}</parameters></tool><message>Hi</message></prompt>"#;
+    assert!(Parser::strict().parse(source).is_err());
+}
+
+#[test]
+fn prompts_round_trip_through_the_dsl() {
+    use std::collections::HashMap;
+    use ai_subsystems::text_api::request::{RequestBuilder, ResponseFormat, Tool, ToolChoice};
+    use ai_subsystems::text_api::xml_dsl::{PromptCollection, Variable, VariableType};
+    let parameters = serde_json::json!({"type": "object", "properties": {"q": {"type": "string", "pattern": "</parameters>"}}});
+    let request = RequestBuilder::default()
+        .with_model("gpt-4o")
+        .with_temperature(0.7)
+        .with_max_tokens(256)
+        .with_seed(3)
+        .with_user("a \"quoted\" <user> & co")
+        .with_response_format(ResponseFormat::JSON_OBJECT)
+        .with_stop(vec![String::from("\n\n"), String::from(" </stop> ")])
+        .with_logit_bias(HashMap::from([(String::from("1"), 5), (String::from("2"), -5)]))
+        .with_tools(vec![Tool::function("search", Some(String::from("Search & find")), Some(parameters))])
+        .with_tool_choice(ToolChoice::REQUIRED)
+        .with_messages(vec![
+            Message::system("Be precise."),
+            Message::user("Steps:\n\n1. Read\n  - carefully\n2. Answer"),
+            Message::named_user("ada", "Line one\n  indented\n\n\tlast"),
+            Message::user("    leading and trailing whitespace\n"),
+            Message::assistant("  a\n  b"),
+            Message::user("Contains </message> and ]]> and <![CDATA[ too"),
+            Message::tool("{\"ok\": true}", "call_1"),
+            Message::user(""),
+        ]);
+    let prompt = Prompt::new("tricky", request)
+        .with_variables(vec![Variable::new("q", VariableType::String).with_required(true)]);
+    let collection = PromptCollection::new(vec![prompt.clone(), Prompt::new("plain", RequestBuilder::default())]);
+    let parsed = PromptCollection::parse(collection.to_dsl()).unwrap();
+    let round_tripped = parsed.get("tricky").unwrap();
+    assert_eq!(
+        serde_json::to_value(&round_tripped.request).unwrap(),
+        serde_json::to_value(&prompt.request).unwrap(),
+        "{}", collection.to_dsl(),
+    );
+    assert_eq!(round_tripped.variables, prompt.variables);
+    assert_eq!(parsed.to_dsl(), collection.to_dsl());
+}
+
+#[test]
+fn libraries_namespace_prompts_by_path() {
+    use ai_subsystems::text_api::xml_dsl::{Mode, PromptLibrary};
+    let directory = write_files("library", &[
+        ("support/triage.prompt.liquid", r#"<prompt name="classify" model="gpt-4o"><message>Triage</message></prompt>"#),
+        ("billing/triage.prompt.liquid", r#"<prompt name="classify" model="gpt-4o"><message>Billing</message></prompt>"#),
+        ("greet.prompt.liquid", r#"<prompt name="hello" model="gpt-4o"><message>Hi</message></prompt>"#),
+        ("notes.txt", "not a prompt file"),
+    ]);
+    let library = PromptLibrary::open(&directory).unwrap();
+    let keys = library.prompts().map(|(key, _)| key).collect::<Vec<_>>();
+    assert_eq!(keys, vec!["billing/triage#classify", "greet#hello", "support/triage#classify"]);
+    assert_eq!(contents(library.get("support/triage#classify").unwrap()), vec!["Triage"]);
+    assert_eq!(contents(library.get("hello").unwrap()), vec!["Hi"]);
+    assert!(library.get("classify").is_none());
+    let collisions = library.collisions();
+    assert_eq!(collisions.len(), 1);
+    assert_eq!(collisions[0].keys, vec!["billing/triage#classify", "support/triage#classify"]);
+    assert!(PromptLibrary::open_with_mode(&directory, Mode::Strict).is_err());
+}
+
+#[test]
+fn libraries_reload_changed_files() {
+    use ai_subsystems::text_api::xml_dsl::PromptLibrary;
+    let directory = write_files("reload", &[
+        ("a.prompt.liquid", r#"<prompt name="a" model="gpt-4o"><message>One</message></prompt>"#),
+        ("b.prompt.liquid", r#"<prompt name="b" model="gpt-4o"><message>B</message></prompt>"#),
+    ]);
+    let mut library = PromptLibrary::open(&directory).unwrap();
+    assert!(!library.has_changes().unwrap());
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    std::fs::write(directory.join("a.prompt.liquid"), r#"<prompt name="a" model="gpt-4o"><message>Two</message></prompt>"#).unwrap();
+    std::fs::write(directory.join("c.prompt.liquid"), r#"<prompt name="c" model="gpt-4o"><message>C</message></prompt>"#).unwrap();
+    assert_eq!(library.reload().unwrap(), vec!["a", "c"]);
+    assert_eq!(contents(library.get("a").unwrap()), vec!["Two"]);
+    // A broken edit keeps the last good version.
+    std::fs::write(directory.join("a.prompt.liquid"), r#"<prompt name="a"><message>Three"#).unwrap();
+    library.reload().unwrap();
+    assert_eq!(contents(library.get("a").unwrap()), vec!["Two"]);
+    assert!(!library.diagnostics().is_empty());
+    // So does one that can’t be read.
+    std::fs::write(directory.join("a.prompt.liquid"), b"<prompt name=\"a\">\xff</prompt>").unwrap();
+    assert_eq!(library.reload().unwrap(), vec!["a"]);
+    assert_eq!(contents(library.get("a").unwrap()), vec!["Two"]);
+    assert!(library.diagnostics().iter().any(|x| x.to_string().contains("a.prompt.liquid")));
+    std::fs::remove_file(directory.join("b.prompt.liquid")).unwrap();
+    library.reload().unwrap();
+    assert!(library.get("b").is_none());
+}
+
+#[test]
+fn rewriting_a_file_unchanged_does_not_reload_it() {
+    use ai_subsystems::text_api::xml_dsl::PromptLibrary;
+    let source = r#"<prompt name="a" model="gpt-4o"><message>One</message></prompt>"#;
+    let directory = write_files("touch", &[("a.prompt.liquid", source)]);
+    let mut library = PromptLibrary::open(&directory).unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    std::fs::write(directory.join("a.prompt.liquid"), source).unwrap();
+    assert!(!library.has_changes().unwrap());
+    assert!(library.reload().unwrap().is_empty());
+}
+
+#[test]
+fn watched_libraries_pick_up_changes() {
+    use ai_subsystems::text_api::xml_dsl::PromptLibrary;
+    let directory = write_files("watch", &[
+        ("a.prompt.liquid", r#"<prompt name="a" model="gpt-4o"><message>One</message></prompt>"#),
+    ]);
+    let library = PromptLibrary::open(&directory).unwrap().watch(std::time::Duration::from_millis(10));
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    std::fs::write(directory.join("a.prompt.liquid"), r#"<prompt name="a" model="gpt-4o"><message>Two</message></prompt>"#).unwrap();
+    let mut content = String::new();
+    for _ in 0..100 {
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        content = library.get("a").unwrap().request.messages[0].content().to_string();
+        if content == "Two" {
+            break
+        }
+    }
+    assert_eq!(content, "Two");
+}
+
+const TESTED_PROMPT: &str = r#"
+<prompt name="classify" model="gpt-4o-mini">
+    <var name="ticket" type="string" required/>
+    <message role="user">Classify: {{ ticket }}</message>
+    <test name="refund" vars='{"ticket": "I want my money back"}'>
+        <expect contains="billing"/>
+        <expect regex="^billing$"/>
+    </test>
+    <test name="outage" vars='{"ticket": "Nothing loads"}'>
+        <expect equals="support&#10;urgent"/>
+    </test>
+    <test name="json" vars='{"ticket": "Where is my parcel?"}'>
+        <expect json-schema='{"type": "object", "required": ["label"]}'/>
+    </test>
+</prompt>
+"#;
+
+fn cassette_for(prompt: &Prompt, responses: &[(&str, &str)]) -> Cassette {
+    let mut cassette = Cassette::default();
+    for (test, response) in responses {
+        let test = prompt.tests.iter().find(|x| x.name == *test).unwrap();
+        let vars = liquid::to_object(&test.vars).unwrap();
+        let request = prompt.render(&vars).unwrap().with_stream(false);
+        cassette.record(serde_json::to_value(&request).unwrap(), response);
+    }
+    cassette
+}
+
+#[tokio::test]
+async fn prompt_tests_replay_recorded_responses() {
+    let collection = Parser::strict().parse(TESTED_PROMPT).unwrap();
+    let prompt = collection.get("classify").unwrap();
+    assert_eq!(prompt.tests.len(), 3);
+    let cassette = cassette_for(&prompt, &[
+        ("refund", "billing"),
+        ("outage", "support\nlow"),
+        ("json", r#"{"category": "shipping"}"#),
+    ]);
+    let report = TestRunner::replay(cassette).run_collection(&collection).await;
+    assert_eq!((report.passed(), report.failed()), (1, 2));
+    let outage = &report.results[1];
+    assert_eq!(outage.failures[0].diff.as_deref(), Some("  support\n- urgent\n+ low"));
+    let json = &report.results[2];
+    assert!(json.failures[0].message.contains("missing property \"label\""));
+}
+
+#[test]
+fn json_schema_expectations_accept_fenced_output() {
+    use ai_subsystems::text_api::xml_dsl::Expectation;
+    let expectation = Expectation::JsonSchema(serde_json::json!({"type": "object", "required": ["label"]}));
+    assert!(expectation.check("```json\n{\"label\": \"billing\"}\n```").is_none());
+    assert!(expectation.check("```json\n{}\n```").is_some());
+}
+
+#[tokio::test]
+async fn prompt_tests_without_a_recording_fail() {
+    let collection = Parser::strict().parse(TESTED_PROMPT).unwrap();
+    let report = TestRunner::default().run_collection(&collection).await;
+    assert!(report.results.iter().all(|x| x.error.is_some()));
+    assert!(!report.is_success());
+}
+
+#[test]
+fn invalid_expectations_are_reported() {
+    let source = r#"
+    <prompt name="p" model="m">
+        <message>Hi</message>
+        <test name="t" vars='[1]'>
+            <expect regex="("/>
+            <expect/>
+        </test>
+    </prompt>
+    "#;
+    let error = Parser::strict().parse(source).unwrap_err();
+    assert_eq!(error.diagnostics.len(), 3);
+}
+
+#[test]
+fn prompt_tests_round_trip_through_the_dsl() {
+    let collection = Parser::strict().parse(TESTED_PROMPT).unwrap();
+    let again = Parser::strict().parse(collection.to_dsl()).unwrap();
+    assert_eq!(collection.get("classify").unwrap().tests, again.get("classify").unwrap().tests);
+}
+
+const CHAINED_PROMPTS: &str = r#"
+<prompt name="classify" model="gpt-4o-mini"><message>Classify: {{ ticket }}</message></prompt>
+<prompt name="extract" model="gpt-4o-mini" response-format="json-object"><message>Extract: {{ ticket }}</message></prompt>
+<prompt name="reply" model="gpt-4o"><message>Reply to {{ customer }} about {{ label }}</message></prompt>
+<prompt name="summarize" model="gpt-4o-mini"><message>Summarize: {{ ticket }}</message></prompt>
+<chain name="support">
+    <step prompt="classify" output="label"/>
+    <step prompt="extract" output="details" format="json">
+        <field name="customer" path="customer.name"/>
+        <field name="first_item" path="items.0"/>
+    </step>
+    <parallel>
+        <step prompt="reply" output="reply"/>
+        <step prompt="summarize" output="summary"/>
+    </parallel>
+</chain>
+"#;
+
+#[test]
+fn chains_are_parsed_in_order() {
+    use ai_subsystems::text_api::xml_dsl::chain::Stage;
+    let collection = Parser::strict().parse(CHAINED_PROMPTS).unwrap();
+    let chain = collection.chain("support").unwrap();
+    let steps = chain.steps().map(|x| x.prompt.as_str()).collect::<Vec<_>>();
+    assert_eq!(steps, vec!["classify", "extract", "reply", "summarize"]);
+    assert!(matches!(&chain.stages[2], Stage::Parallel(steps) if steps.len() == 2));
+    let again = Parser::strict().parse(collection.to_dsl()).unwrap();
+    assert_eq!(again.chain("support"), Some(chain));
+}
+
+#[test]
+fn step_outputs_become_variables() {
+    let collection = Parser::strict().parse(CHAINED_PROMPTS).unwrap();
+    let extract = collection.chain("support").unwrap().steps().nth(1).unwrap();
+    let output = "```json\n{\"customer\": {\"name\": \"Ada\"}, \"items\": [\"lamp\"]}\n```";
+    let vars = extract.variables(output).unwrap();
+    let names = vars.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>();
+    assert_eq!(names, vec!["customer", "first_item", "details"]);
+    assert_eq!(vars[0].1, liquid::model::Value::scalar("Ada"));
+    assert!(extract.variables("{\"customer\": {}}").unwrap_err().contains("customer.name"));
+    assert!(extract.variables("not json").is_err());
+}
+
+#[test]
+fn chains_with_unknown_prompts_are_reported() {
+    let source = r#"
+    <prompt name="a" model="m"><message>Hi</message></prompt>
+    <chain name="c"><step prompt="a" output="x"/><step prompt="b"/></chain>
+    <chain name="empty"></chain>
+    "#;
+    let collection = Parser::lenient().parse(source).unwrap();
+    let messages = collection.diagnostics().iter().map(|x| x.kind.to_string()).collect::<Vec<_>>();
+    assert_eq!(messages, vec![
+        "chain \"empty\" has no steps",
+        "chain \"c\" runs unknown prompt \"b\"",
+    ]);
+}
+
+#[test]
+fn field_paths_escape_pointer_characters() {
+    use ai_subsystems::text_api::xml_dsl::chain::{OutputFormat, Step};
+    let step = Step::new("p").with_format(OutputFormat::Json).with_field("x", "a/b.c~d");
+    let vars = step.variables("{\"a/b\": {\"c~d\": 1}}").unwrap();
+    assert_eq!(vars[0].1, liquid::model::Value::scalar(1));
+}
+
+#[test]
+fn parallel_steps_with_the_same_output_are_reported() {
+    let source = r#"
+    <prompt name="a" model="m"><message>Hi</message></prompt>
+    <chain name="c"><parallel><step prompt="a" output="x"/><step prompt="a" output="x"/></parallel></chain>
+    "#;
+    let error = Parser::strict().parse(source).unwrap_err();
+    assert_eq!(error.diagnostics.len(), 1);
+    assert_eq!(error.diagnostics[0].kind.to_string(), "more than one step of the <parallel> stores \"x\"");
+}
+
+const EXAMPLE_PROMPT: &str = r#"
+<prompt name="classify" model="gpt-4o-mini">
+    <message role="system">Label the ticket.</message>
+    <examples count="2">
+        <example><input>I was charged twice</input><output>billing</output></example>
+        <example><input>The app crashes on start</input><output>bug</output></example>
+        <example><input>How do I export my data?</input><output>question</output></example>
+        <example>
+            <input>
+                Refund please:
+                  order 42
+            </input>
+            <output><![CDATA[billing </output>]]></output>
+        </example>
+    </examples>
+    <message role="user">{{ ticket }}</message>
+</prompt>
+"#;
+
+#[test]
+fn examples_become_message_pairs() {
+    let prompt = Prompt::parse(EXAMPLE_PROMPT, "classify").unwrap();
+    let request = prompt.render(&liquid::object!({ "ticket": "Card declined" })).unwrap();
+    assert_eq!(contents_of(&request.messages), vec![
+        "Label the ticket.",
+        "I was charged twice",
+        "billing",
+        "The app crashes on start",
+        "bug",
+        "Card declined",
+    ]);
+    assert!(matches!(request.messages[2], Message::Assistant { .. }));
+    let examples = prompt.examples.as_ref().unwrap();
+    assert_eq!(examples.examples[3].input, "Refund please:\norder 42");
+    assert_eq!(examples.examples[3].output, "billing </output>");
+}
+
+#[test]
+fn examples_can_be_chosen_at_random_or_by_similarity() {
+    use ai_subsystems::text_api::tokens::TokenCounter;
+    use ai_subsystems::text_api::xml_dsl::Selection;
+    let prompt = Prompt::parse(EXAMPLE_PROMPT, "classify").unwrap();
+    let counter = TokenCounter::default();
+    let inputs = |examples: Vec<&ai_subsystems::text_api::xml_dsl::Example>| {
+        examples.into_iter().map(|x| x.input.clone()).collect::<Vec<_>>()
+    };
+    let random = prompt.examples.clone().unwrap().with_selection(Selection::Random { seed: 7 });
+    assert_eq!(inputs(random.select(&counter, None)), inputs(random.select(&counter, None)));
+    assert_eq!(random.select(&counter, None).len(), 2);
+    let similar = prompt.examples.clone().unwrap().with_selection(Selection::Similar);
+    let chosen = inputs(similar.select(&counter, Some(&[0.1, 0.9, 0.2, 0.8])));
+    assert_eq!(chosen, vec!["The app crashes on start", "Refund please:\norder 42"]);
+}
+
+#[test]
+fn examples_stay_within_the_token_budget() {
+    use ai_subsystems::text_api::tokens::TokenCounter;
+    let prompt = Prompt::parse(EXAMPLE_PROMPT, "classify").unwrap();
+    let counter = TokenCounter::default();
+    let examples = prompt.examples.clone().unwrap().with_count(4);
+    let first = counter.count_messages(&examples.examples[0].messages()) - 3;
+    let budgeted = examples.with_max_tokens(first + 1);
+    let chosen = budgeted.select(&counter, None);
+    assert_eq!(chosen.len(), 1);
+    assert_eq!(chosen[0].input, "I was charged twice");
+}
+
+#[test]
+fn examples_are_inherited_and_round_trip() {
+    let source = format!(r#"{EXAMPLE_PROMPT}<prompt name="strict" extends="classify"><message>Be strict.</message></prompt>"#);
+    let collection = Parser::strict().parse(&source).unwrap();
+    let parent = collection.get("classify").unwrap();
+    assert_eq!(collection.get("strict").unwrap().examples, parent.examples);
+    let again = Parser::strict().parse(collection.to_dsl()).unwrap();
+    assert_eq!(again.get("classify").unwrap().examples, parent.examples);
+}
+
+#[test]
+fn incomplete_examples_are_reported() {
+    let source = r#"<prompt model="m"><examples select="best"><example><input>Hi</input></example></examples><message>Hi</message></prompt>"#;
+    let error = Parser::strict().parse(source).unwrap_err();
+    assert_eq!(error.diagnostics.len(), 2);
+}
+
+#[test]
+fn random_examples_need_a_seed() {
+    let source = r#"<prompt model="m"><examples select="random"><example><input>Hi</input><output>Hey</output></example></examples><message>Hi</message></prompt>"#;
+    let error = Parser::strict().parse(source).unwrap_err();
+    assert_eq!(error.diagnostics.len(), 1);
+    assert!(error.diagnostics[0].to_string().contains("\"seed\""));
+    assert!(Parser::strict().parse(source.replace("random\"", "random\" seed=\"3\"")).is_ok());
+}
+
+#[test]
+fn verbatim_bodies_and_rendered_prompts_are_not_rendered_again() {
+    let source = r#"
+<prompt name="p" model="gpt-4o">
+    <var name="name" type="bool"/>
+    <message role="system"><![CDATA[Use {{ handlebars }} and {% raw %} as written.]]></message>
+    <message>Hello {{ name }}</message>
+</prompt>
+"#;
+    let prompt = Parser::strict().parse(source).unwrap().get("p").unwrap();
+    let request = prompt.render(&liquid::object!({ "name": true })).unwrap();
+    assert_eq!(contents_of(&request.messages), vec!["Use {{ handlebars }} and {% raw %} as written.", "Hello true"]);
+    let again = Parser::strict().parse(Parser::strict().parse(source).unwrap().to_dsl()).unwrap();
+    assert_eq!(again.get("p").unwrap().verbatim_messages, vec![0]);
+    // Globals that produce template syntax are used as they are.
+    let globals = liquid::object!({ "name": "{{ name }}" });
+    let rendered = Prompt::parse_with("<prompt name=\"p\" model=\"m\"><message>Hi {{ name }}</message></prompt>", "p", &globals).unwrap();
+    let request = rendered.render(&liquid::object!({})).unwrap();
+    assert_eq!(contents_of(&request.messages), vec!["Hi {{ name }}"]);
+}